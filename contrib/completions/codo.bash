@@ -0,0 +1,30 @@
+# Bash completion for codo. Source this file, e.g. from ~/.bashrc:
+#   source /path/to/codo.bash
+
+_codo() {
+    local cur="${COMP_WORDS[COMP_CWORD]}"
+    local prev="${COMP_WORDS[COMP_CWORD-1]}"
+
+    case "$prev" in
+        --assignee|--issue|--due)
+            local IFS=$'\n'
+            COMPREPLY=($(codo _complete "$prev" "$cur" 2>/dev/null))
+            return
+            ;;
+        --group-by)
+            COMPREPLY=($(compgen -W "assignee due issue root language path" -- "$cur"))
+            return
+            ;;
+        --path|--config|--out-dir)
+            COMPREPLY=($(compgen -f -- "$cur"))
+            return
+            ;;
+    esac
+
+    # Keep in step with the subcommands in src/main.rs.
+    if [[ $COMP_CWORD -eq 1 ]]; then
+        COMPREPLY=($(compgen -W "list stat validate lint format export digest mod serve due agenda budget issues sync review explain auth config schema" -- "$cur"))
+    fi
+}
+
+complete -F _codo codo
@@ -0,0 +1,85 @@
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::Path;
+
+use crate::{is_overdue, parse_due_date, ScanRoot, Todo};
+
+/// Reads the repositories of `codo agenda`, one per line like `--path`,
+/// optionally labeled as `label=path`. Blank lines and `#` comments are
+/// skipped, and relative paths are relative to the file's directory.
+/// Unlabeled repositories are labeled with their last path component.
+pub fn read_repos(source: &str) -> Result<Vec<ScanRoot>, String> {
+    let contents =
+        fs::read_to_string(source).map_err(|e| format!("Failed to read {}: {}", source, e))?;
+    let base = Path::new(source).parent().unwrap_or(Path::new(""));
+
+    let mut repos: Vec<ScanRoot> = vec![];
+    for (index, line) in contents.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+
+        let root = ScanRoot::parse(line);
+        let path = base.join(&root.path);
+        if !path.exists() {
+            return Err(format!(
+                "{}:{}: {} doesn't exist",
+                source,
+                index + 1,
+                path.display()
+            ));
+        }
+
+        let label = root.label.unwrap_or_else(|| {
+            let name = path.file_stem().unwrap_or(path.as_os_str());
+            name.to_string_lossy().into_owned()
+        });
+        repos.push(ScanRoot {
+            label: Some(label),
+            path: path.to_string_lossy().into_owned(),
+        });
+    }
+
+    if repos.is_empty() {
+        return Err(format!("No repositories listed in {}", source));
+    }
+
+    Ok(repos)
+}
+
+/// The TODOs of each assignee, or only of `assignees` when given, the
+/// earliest due first and those without a due date last. TODOs with several
+/// assignees are listed under each of them.
+pub fn render(todos: &[Todo], assignees: Option<&[String]>) -> String {
+    let mut agenda: BTreeMap<&String, Vec<&Todo>> = BTreeMap::new();
+    for todo in todos {
+        for assignee in &todo.metadata.assignees {
+            if assignees.is_none_or(|selected| selected.contains(assignee)) {
+                agenda.entry(assignee).or_default().push(todo);
+            }
+        }
+    }
+
+    agenda
+        .into_iter()
+        .map(|(assignee, mut todos)| {
+            todos.sort_by_key(|todo| {
+                let due = todo.due().to_owned().and_then(parse_due_date);
+                (due.is_none(), due, todo.root.clone())
+            });
+
+            let overdue_count = todos.iter().filter(|todo| is_overdue(todo)).count();
+            let mut lines = vec![match overdue_count {
+                0 => format!("{} ({})", assignee, todos.len()),
+                _ => format!("{} ({}, {} overdue)", assignee, todos.len(), overdue_count),
+            }];
+            lines.extend(todos.iter().map(|todo| {
+                let due = todo.due().as_deref().unwrap_or_default();
+                format!("  {:<10}  {}", due, todo.as_search_result())
+            }));
+            lines.join("\n")
+        })
+        .collect::<Vec<String>>()
+        .join("\n\n")
+}
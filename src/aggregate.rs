@@ -0,0 +1,128 @@
+use std::collections::HashMap;
+
+use crate::config::Config;
+use crate::{is_overdue, GroupSort, Grouping, Todo};
+
+/// TODO counts per group, as printed by `stat --group-by`. TODOs can be added
+/// one at a time, so large snapshots don't have to be grouped at once.
+pub struct Report {
+    grouping: Grouping,
+    /// TODOs in several groups, like those with several assignees, count as
+    /// a share in each rather than one.
+    split: bool,
+    counts: HashMap<String, f64>,
+    overdue: HashMap<String, f64>,
+}
+
+impl Report {
+    pub fn new(grouping: Grouping, split: bool) -> Self {
+        Report {
+            grouping,
+            split,
+            counts: HashMap::new(),
+            overdue: HashMap::new(),
+        }
+    }
+
+    pub fn add(&mut self, todo: &Todo, config: &Config) {
+        let keys = self.grouping.keys(todo, config);
+        let share = if self.split {
+            1.0 / keys.len() as f64
+        } else {
+            1.0
+        };
+        for key in keys {
+            if is_overdue(todo) {
+                *self.overdue.entry(key.clone()).or_insert(0.0) += share;
+            }
+            *self.counts.entry(key).or_insert(0.0) += share;
+        }
+    }
+
+    /// Lists the group even without TODOs.
+    pub fn add_empty(&mut self, key: String) {
+        self.counts.entry(key).or_insert(0.0);
+    }
+
+    /// The groups and their counts, in the order given. Ties in count fall
+    /// back to key order, so output is stable.
+    pub fn entries(&self, sort: &GroupSort) -> Vec<(&str, f64)> {
+        let mut entries: Vec<(&str, f64)> = self
+            .counts
+            .iter()
+            .map(|(key, count)| (key.as_str(), *count))
+            .collect();
+        entries.sort_by(|(a_key, a), (b_key, b)| match sort {
+            GroupSort::Key => self.grouping.compare_keys(a_key, b_key),
+            GroupSort::Count => b
+                .total_cmp(a)
+                .then_with(|| self.grouping.compare_keys(a_key, b_key)),
+        });
+        entries
+    }
+
+    pub fn as_text(&self, sort: &GroupSort) -> String {
+        self.entries(sort)
+            .iter()
+            .map(|(key, count)| format!("{}: {}", key, format_count(*count)))
+            .collect::<Vec<String>>()
+            .join("\n")
+    }
+
+    /// A header and a row per group, with the fields separated by
+    /// `separator`, like CSV with `,` and TSV with a tab.
+    pub fn as_delimited(&self, sort: &GroupSort, separator: char) -> String {
+        let header = ["group", "count", "overdue_count"].join(&separator.to_string());
+        let rows = self.entries(sort).into_iter().map(|(key, count)| {
+            let overdue = self.overdue.get(key).copied().unwrap_or(0.0);
+            format!(
+                "{}{}{}{}{}",
+                delimited_field(key, separator),
+                separator,
+                format_count(count),
+                separator,
+                format_count(overdue)
+            )
+        });
+
+        std::iter::once(header)
+            .chain(rows)
+            .collect::<Vec<String>>()
+            .join("\n")
+    }
+}
+
+/// Whole counts as integers, and shares of split TODOs to two decimals,
+/// like `2.5`.
+fn format_count(count: f64) -> String {
+    let rounded = format!("{:.2}", count);
+    rounded
+        .trim_end_matches('0')
+        .trim_end_matches('.')
+        .to_owned()
+}
+
+/// Quotes fields holding the separator, quotes, or line breaks, doubling the
+/// quotes, like spreadsheets expect.
+fn delimited_field(field: &str, separator: char) -> String {
+    if field.contains([separator, '"', '\n', '\r']) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_owned()
+    }
+}
+
+/// Counts `todos` per group, splitting TODOs in several between them if
+/// `split`.
+pub fn aggregate<'a>(
+    todos: impl IntoIterator<Item = &'a Todo>,
+    grouping: Grouping,
+    split: bool,
+    config: &Config,
+) -> Report {
+    let mut report = Report::new(grouping, split);
+    for todo in todos {
+        report.add(todo, config);
+    }
+    report
+}
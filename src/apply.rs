@@ -0,0 +1,302 @@
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+
+use serde_json::{Map, Value};
+
+use crate::config::Config;
+use crate::globs::normalize;
+use crate::{
+    is_valid_assignee, parse_due_str, parse_issue, parse_priority, Todo, TodoMetadata, TodoUpdate,
+    UpdateAction,
+};
+
+/// Fields of `export json` that can't be edited, and are ignored so its
+/// entries can be piped back in.
+pub const READ_ONLY_FIELDS: [&str; 5] = ["extra", "root", "context", "marker", "permalink"];
+
+enum Selector {
+    Id(String),
+    Location {
+        path: PathBuf,
+        cell: Option<usize>,
+        line: u64,
+    },
+}
+
+impl Selector {
+    fn matches(&self, todo: &Todo) -> bool {
+        match self {
+            Selector::Id(id) => todo.metadata.id.as_ref() == Some(id),
+            Selector::Location { path, cell, line } => {
+                normalize(&todo.path) == normalize(path)
+                    && todo.cell == *cell
+                    && todo.line_number == *line
+            }
+        }
+    }
+
+    fn describe(&self) -> String {
+        match self {
+            Selector::Id(id) => format!("id:{}", id),
+            Selector::Location { path, cell, line } => match cell {
+                Some(cell) => format!("{}[{}]:{}", path.display(), cell, line),
+                None => format!("{}:{}", path.display(), line),
+            },
+        }
+    }
+}
+
+/// One object of `mod apply --stdin`: which TODO to edit, by `id` or by
+/// `path` and `line` (and `cell`), and its new field values. `null` removes a
+/// field and missing fields are kept.
+pub struct Edit {
+    selector: Selector,
+    fields: Map<String, Value>,
+}
+
+/// Exported TODOs in generated files are skipped, like by every `mod`.
+fn parse_edit(value: Value) -> Result<Option<Edit>, String> {
+    let Value::Object(mut fields) = value else {
+        return Err(format!("Expected a JSON object, got {}", value));
+    };
+    if fields.remove("generated") == Some(Value::Bool(true)) {
+        return Ok(None);
+    }
+
+    let id = fields.remove("id");
+    let path = fields.remove("path");
+    let cell = fields.remove("cell");
+    let line = fields.remove("line");
+
+    let selector = match (id, path, line) {
+        (Some(Value::String(id)), _, _) => Selector::Id(id),
+        (_, Some(Value::String(path)), Some(line)) => Selector::Location {
+            path: PathBuf::from(path),
+            cell: match cell {
+                None | Some(Value::Null) => None,
+                Some(cell) => Some(
+                    cell.as_u64()
+                        .ok_or_else(|| format!("Invalid cell {}", cell))?
+                        as usize,
+                ),
+            },
+            line: line
+                .as_u64()
+                .ok_or_else(|| format!("Invalid line {}", line))?,
+        },
+        _ => return Err("Each edit needs an \"id\", or a \"path\" and \"line\"".to_owned()),
+    };
+
+    for field in READ_ONLY_FIELDS {
+        fields.remove(field);
+    }
+
+    Ok(Some(Edit { selector, fields }))
+}
+
+/// Reads a stream of JSON objects, separated by whitespace or newlines. A
+/// document printed by `export json` stands for each of its `todos`.
+pub fn parse_edits(input: &str) -> Result<Vec<Edit>, String> {
+    let mut edits: Vec<Edit> = vec![];
+    for value in serde_json::Deserializer::from_str(input).into_iter::<Value>() {
+        match value.map_err(|e| format!("Invalid JSON: {}", e))? {
+            Value::Object(mut document) if document.contains_key("schema_version") => {
+                let Some(Value::Array(todos)) = document.remove("todos") else {
+                    return Err("Expected an array for \"todos\"".to_owned());
+                };
+                for todo in todos {
+                    edits.extend(parse_edit(todo)?);
+                }
+            }
+            value => edits.extend(parse_edit(value)?),
+        }
+    }
+    Ok(edits)
+}
+
+/// `0` to `9`, or `p0` to `p9` as written in TODOs.
+fn priority_field(value: &Value) -> Result<Option<u8>, String> {
+    let priority = match value {
+        Value::Null => return Ok(None),
+        Value::Number(number) => number.as_u64().filter(|n| *n <= 9).map(|n| n as u8),
+        Value::String(priority) => parse_priority(priority),
+        _ => None,
+    };
+    priority
+        .map(Some)
+        .ok_or_else(|| format!("Invalid priority {}", value))
+}
+
+fn string_field(field: &str, value: &Value) -> Result<Option<String>, String> {
+    match value {
+        Value::Null => Ok(None),
+        Value::String(value) => Ok(Some(value.to_owned())),
+        _ => Err(format!("Expected a string or null for \"{}\"", field)),
+    }
+}
+
+fn assignees_field(value: &Value) -> Result<Vec<String>, String> {
+    let Value::Array(values) = value else {
+        return Err("Expected an array for \"assignees\"".to_owned());
+    };
+
+    let mut assignees: Vec<String> = vec![];
+    for value in values {
+        let assignee = value
+            .as_str()
+            .ok_or("Expected strings in \"assignees\"")?
+            .trim_start_matches('@');
+        if !is_valid_assignee(assignee) {
+            return Err(format!("Invalid assignee \"{}\"", assignee));
+        }
+        if !assignees.iter().any(|a| a == assignee) {
+            assignees.push(assignee.to_owned());
+        }
+    }
+    Ok(assignees)
+}
+
+/// Sets the fields of an edit, which are also how saved TODOs are read back
+/// by `snapshot`.
+pub fn edit_metadata(
+    metadata: &mut TodoMetadata,
+    note: &mut String,
+    delimiter: &str,
+    fields: &Map<String, Value>,
+    config: &Config,
+) -> Result<(), String> {
+    // All assignees are set before `assignee`, the first, so exported TODOs
+    // can be edited with either.
+    if let Some(value) = fields.get("assignees") {
+        metadata.assignees = assignees_field(value)?;
+    }
+
+    if let Some(value) = fields.get("priority") {
+        metadata.priority = priority_field(value)?;
+    }
+
+    for (field, value) in fields {
+        if field == "assignees" || field == "priority" {
+            continue;
+        }
+        let value = string_field(field, value)?;
+        match field.as_str() {
+            "note" => {
+                let value = value.ok_or("A TODO's \"note\" can't be removed")?;
+                // Keep the end of single line block comments.
+                *note = if delimiter == "/*" && note.ends_with("*/") {
+                    format!("{} */", value.trim_end())
+                } else {
+                    value
+                };
+            }
+            "issue" => {
+                metadata.issue = value
+                    .map(|issue| parse_issue(&issue).ok_or(format!("Invalid issue \"{}\"", issue)))
+                    .transpose()?;
+            }
+            "blocked_by" => {
+                metadata.blocked_by = value
+                    .map(|issue| parse_issue(&issue).ok_or(format!("Invalid issue \"{}\"", issue)))
+                    .transpose()?;
+            }
+            "assignee" => {
+                let value = value.map(|a| a.trim_start_matches('@').to_owned());
+                if let Some(assignee) = value.as_ref().filter(|a| !is_valid_assignee(a)) {
+                    return Err(format!("Invalid assignee \"{}\"", assignee));
+                }
+                match value {
+                    Some(assignee) if metadata.assignee() != Some(&assignee) => {
+                        metadata.assignees.retain(|a| *a != assignee);
+                        match metadata.assignees.first_mut() {
+                            Some(first) => *first = assignee,
+                            None => metadata.assignees.push(assignee),
+                        }
+                    }
+                    Some(_) => {}
+                    None => metadata.assignees.clear(),
+                }
+            }
+            "due" => {
+                metadata.due = value
+                    .map(|due| {
+                        parse_due_str(&due, config).ok_or(format!("Invalid due \"{}\"", due))
+                    })
+                    .transpose()?;
+            }
+            "every" => metadata.every = value,
+            "snooze" => {
+                metadata.snooze = value
+                    .map(|snooze| {
+                        parse_due_str(&snooze, config)
+                            .ok_or(format!("Invalid snooze \"{}\"", snooze))
+                    })
+                    .transpose()?;
+            }
+            _ => return Err(format!("Unknown field \"{}\"", field)),
+        }
+    }
+
+    Ok(())
+}
+
+/// Matches each edit to exactly one TODO. Nothing is written unless every
+/// edit is valid.
+pub fn updates(
+    edits: Vec<Edit>,
+    todos: &[Todo],
+    config: &Config,
+) -> Result<Vec<TodoUpdate>, String> {
+    let mut edited: HashSet<(&Path, Option<usize>, u64)> = HashSet::new();
+    let mut updates: Vec<TodoUpdate> = vec![];
+
+    for edit in edits {
+        let describe = edit.selector.describe();
+        let mut found = todos.iter().filter(|todo| edit.selector.matches(todo));
+        let todo = found
+            .next()
+            .ok_or_else(|| format!("No TODO at {}", describe))?;
+        if found.next().is_some() {
+            return Err(format!("Several TODOs match {}", describe));
+        }
+        if !edited.insert((&todo.path, todo.cell, todo.line_number)) {
+            return Err(format!("Several edits for {}", describe));
+        }
+
+        // Entries piped back from `export json` unchanged leave the TODO as
+        // written.
+        let current = todo.as_json();
+        if edit
+            .fields
+            .iter()
+            .all(|(field, value)| current.get(field) == Some(value))
+        {
+            continue;
+        }
+
+        let mut metadata = todo.metadata.clone();
+        let mut note = todo.note.clone();
+        edit_metadata(
+            &mut metadata,
+            &mut note,
+            &todo.delimiter,
+            &edit.fields,
+            config,
+        )
+        .map_err(|e| format!("{}: {}", describe, e))?;
+
+        updates.push(TodoUpdate {
+            metadata,
+            note,
+            path: todo.path.clone(),
+            line_number: todo.line_number,
+            cell: todo.cell,
+            delimiter: todo.delimiter.clone(),
+            marker: todo.marker.clone(),
+            raw: todo.raw.clone(),
+            action: UpdateAction::Edit,
+        });
+    }
+
+    Ok(updates)
+}
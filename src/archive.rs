@@ -0,0 +1,85 @@
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+use crate::Todo;
+
+const ARCHIVE_SUFFIXES: [&str; 4] = [".zip", ".tar", ".tar.gz", ".tgz"];
+
+pub fn is_archive(path: &Path) -> bool {
+    let name = path.to_string_lossy().to_lowercase();
+    ARCHIVE_SUFFIXES.iter().any(|suffix| name.ends_with(suffix))
+}
+
+/// Where a file inside `archive` is reported, like `vendor.zip!/lib/util.py`.
+fn inner_path(archive: &Path, inner: &Path) -> PathBuf {
+    PathBuf::from(format!("{}!/{}", archive.display(), inner.display()))
+}
+
+/// Extracts `archive` into a temporary directory with `unzip` or `tar`, scans
+/// every file in it with `scan_file`, and reports the TODOs at their paths
+/// inside the archive. The directory is removed when it's dropped.
+pub fn scan(
+    archive: &Path,
+    mut scan_file: impl FnMut(&Path) -> Vec<Todo>,
+) -> Result<Vec<Todo>, String> {
+    let temp_dir = tempfile::Builder::new()
+        .prefix("codo-archive-")
+        .tempdir()
+        .map_err(|e| format!("Failed to create a temporary directory: {}", e))?;
+    let dir = temp_dir.path();
+
+    extract(archive, dir).map(|()| {
+        let mut walk_builder = ignore::WalkBuilder::new(dir);
+        // Vendored drops are audited as shipped, whatever they ignore.
+        walk_builder.standard_filters(false);
+        walk_builder.sort_by_file_name(|a, b| a.cmp(b));
+
+        let mut todos: Vec<Todo> = vec![];
+        for entry in walk_builder.build().flatten() {
+            if !entry.file_type().is_some_and(|f| f.is_file()) {
+                continue;
+            }
+
+            let inner = entry.path().strip_prefix(dir).unwrap_or(entry.path());
+            todos.extend(scan_file(entry.path()).into_iter().map(|todo| Todo {
+                path: inner_path(archive, inner),
+                ..todo
+            }));
+        }
+        todos
+    })
+}
+
+fn extract(archive: &Path, dir: &Path) -> Result<(), String> {
+    let is_zip = archive.to_string_lossy().to_lowercase().ends_with(".zip");
+    let output = if is_zip {
+        Command::new("unzip")
+            .args(["-qq", "-o"])
+            .arg(archive)
+            .arg("-d")
+            .arg(dir)
+            .output()
+    } else {
+        Command::new("tar")
+            .arg("-xf")
+            .arg(archive)
+            .arg("-C")
+            .arg(dir)
+            .output()
+    };
+
+    match output {
+        Ok(output) if output.status.success() => Ok(()),
+        Ok(output) => Err(format!(
+            "Failed to extract {}: {}",
+            archive.display(),
+            String::from_utf8_lossy(&output.stderr).trim()
+        )),
+        Err(e) => Err(format!(
+            "Failed to extract {}, {} is required: {}",
+            archive.display(),
+            if is_zip { "unzip" } else { "tar" },
+            e
+        )),
+    }
+}
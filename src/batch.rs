@@ -0,0 +1,87 @@
+use std::io::{BufRead, Write};
+use std::path::{Path, PathBuf};
+
+use grep::regex::RegexMatcher;
+use serde::Deserialize;
+use serde_json::{json, Value};
+
+use crate::config::Config;
+use crate::{containers, get_validation_errors, parse_todo_line, Todo, ValidationRules};
+
+/// A buffer to lint for `lint --stdin-json`, as an editor has it.
+#[derive(Deserialize)]
+struct Request {
+    /// Echoed back, so editors can match responses to requests.
+    #[serde(default)]
+    id: Value,
+    path: PathBuf,
+    content: String,
+}
+
+fn parse_content(matcher: &RegexMatcher, config: &Config, path: &Path, content: &str) -> Vec<Todo> {
+    content
+        .lines()
+        .enumerate()
+        .filter_map(|(num, line)| {
+            let line_number = num as u64 + 1;
+            if containers::is_markdown(path) {
+                containers::parse_markdown_line(matcher, config, path, line_number, line)
+            } else {
+                parse_todo_line(matcher, config, path, line_number, line)
+            }
+        })
+        .collect()
+}
+
+fn respond(line: &str, matcher: &RegexMatcher, rules: &ValidationRules, config: &Config) -> Value {
+    let request: Request = match serde_json::from_str(line) {
+        Ok(request) => request,
+        Err(e) => return json!({ "error": format!("Invalid request: {}", e) }),
+    };
+
+    let diagnostics: Vec<Value> = parse_content(matcher, config, &request.path, &request.content)
+        .iter()
+        .flat_map(|todo| {
+            get_validation_errors(todo, rules, config)
+                .into_iter()
+                .map(|message| {
+                    json!({
+                        "line": todo.line_number,
+                        "column": todo.column,
+                        "message": message,
+                    })
+                })
+        })
+        .collect();
+
+    json!({
+        "id": request.id,
+        "path": request.path,
+        "diagnostics": diagnostics,
+    })
+}
+
+/// Reads one JSON request per line from stdin, like
+/// `{"path": "src/main.rs", "content": "..."}`, and writes the validation
+/// errors of the TODOs in `content` as one JSON line each, until stdin
+/// closes.
+pub fn serve(matcher: &RegexMatcher, rules: &ValidationRules, config: &Config) {
+    let stdin = std::io::stdin();
+    let mut stdout = std::io::stdout();
+    for line in stdin.lock().lines() {
+        let Ok(line) = line else {
+            break;
+        };
+        if line.trim().is_empty() {
+            continue;
+        }
+
+        let response = respond(&line, matcher, rules, config);
+        if writeln!(stdout, "{}", response)
+            .and_then(|()| stdout.flush())
+            .is_err()
+        {
+            break;
+        }
+    }
+}
@@ -0,0 +1,52 @@
+use std::collections::BTreeMap;
+use std::path::Path;
+
+use crate::globs::normalize;
+use crate::Todo;
+
+/// How many TODOs a budgeted directory has against its allowance.
+pub struct BudgetStatus {
+    pub directory: String,
+    pub count: usize,
+    pub allowance: usize,
+}
+
+impl BudgetStatus {
+    pub fn is_exceeded(&self) -> bool {
+        self.count > self.allowance
+    }
+
+    pub fn as_cli_result(&self) -> String {
+        let headroom = if self.is_exceeded() {
+            format!("{} over", self.count - self.allowance)
+        } else {
+            format!("{} remaining", self.allowance - self.count)
+        };
+
+        format!(
+            "{}: {} of {} TODOs ({})",
+            self.directory, self.count, self.allowance, headroom
+        )
+    }
+}
+
+/// Counts the TODOs under each directory of `budgets`, keyed by path
+/// relative to the working directory.
+pub fn statuses(todos: &[Todo], budgets: &BTreeMap<String, usize>) -> Vec<BudgetStatus> {
+    budgets
+        .iter()
+        .map(|(directory, allowance)| {
+            let directory_path = normalize(Path::new(directory));
+            let count = todos
+                .iter()
+                .filter(|todo| normalize(&todo.path).starts_with(directory_path))
+                .count();
+
+            BudgetStatus {
+                directory: directory.to_owned(),
+                count,
+                allowance: *allowance,
+            }
+        })
+        .collect()
+}
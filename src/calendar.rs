@@ -0,0 +1,111 @@
+use std::collections::BTreeMap;
+
+use chrono::{Datelike, Months, NaiveDate};
+
+use crate::{parse_due_date, Todo};
+
+/// Width of each day in the grid, room for the day and a count like `(12)`.
+const DAY_WIDTH: usize = 6;
+
+const WEEKDAYS: [&str; 7] = ["Mo", "Tu", "We", "Th", "Fr", "Sa", "Su"];
+
+/// TODOs with a due date, by date.
+fn by_due_date<'a>(todos: &[&'a Todo]) -> BTreeMap<NaiveDate, Vec<&'a Todo>> {
+    let mut dates: BTreeMap<NaiveDate, Vec<&Todo>> = BTreeMap::new();
+    for todo in todos {
+        if let Some(due) = todo.due().to_owned().and_then(parse_due_date) {
+            dates.entry(due).or_default().push(todo);
+        }
+    }
+    dates
+}
+
+/// Each due date with the TODOs due then.
+pub fn timeline(todos: &[&Todo]) -> String {
+    by_due_date(todos)
+        .iter()
+        .map(|(date, todos)| {
+            let lines: Vec<String> = todos
+                .iter()
+                .map(|todo| format!("  {}", todo.as_search_result()))
+                .collect();
+            format!("{} ({})\n{}", date, todos.len(), lines.join("\n"))
+        })
+        .collect::<Vec<String>>()
+        .join("\n")
+}
+
+/// A grid for each of `months` months from the one `first` is in, marking
+/// days with the number of TODOs due and listing them under the grid.
+pub fn render(todos: &[&Todo], first: NaiveDate, months: u32) -> String {
+    let dates = by_due_date(todos);
+    let start = first.with_day(1).unwrap_or(first);
+    let end = start
+        .checked_add_months(Months::new(months))
+        .unwrap_or(NaiveDate::MAX);
+
+    let mut sections: Vec<String> = vec![];
+    let mut month = start;
+    while month < end {
+        let Some(next) = month.checked_add_months(Months::new(1)) else {
+            break;
+        };
+        let due: BTreeMap<&NaiveDate, &Vec<&Todo>> = dates.range(month..next).collect();
+
+        let mut lines = vec![
+            month.format("%B %Y").to_string(),
+            WEEKDAYS
+                .iter()
+                .map(|day| format!("{:<width$}", day, width = DAY_WIDTH))
+                .collect::<String>()
+                .trim_end()
+                .to_owned(),
+        ];
+
+        let mut week = " ".repeat(DAY_WIDTH * month.weekday().num_days_from_monday() as usize);
+        let mut day = month;
+        while day < next {
+            let count = due
+                .get(&day)
+                .map(|todos| format!("({})", todos.len()))
+                .unwrap_or_default();
+            week.push_str(&format!(
+                "{:>2}{:<width$}",
+                day.day(),
+                count,
+                width = DAY_WIDTH - 2
+            ));
+
+            if day.weekday().num_days_from_monday() == 6 {
+                lines.push(week.trim_end().to_owned());
+                week = String::new();
+            }
+            day = day.succ_opt().unwrap_or(next);
+        }
+        if !week.is_empty() {
+            lines.push(week.trim_end().to_owned());
+        }
+
+        for (date, todos) in due {
+            lines.push(format!("\n{}", date));
+            for todo in todos {
+                lines.push(format!("  {}", todo.as_search_result()));
+            }
+        }
+
+        sections.push(lines.join("\n"));
+        month = next;
+    }
+
+    let before: usize = dates.range(..start).map(|(_, todos)| todos.len()).sum();
+    if before > 0 {
+        sections.push(format!("Due before {} ({})", start.format("%B %Y"), before));
+    }
+    let after: usize = dates.range(end..).map(|(_, todos)| todos.len()).sum();
+    if after > 0 {
+        let last = end - Months::new(1);
+        sections.push(format!("Due after {} ({})", last.format("%B %Y"), after));
+    }
+
+    sections.join("\n\n")
+}
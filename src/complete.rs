@@ -0,0 +1,96 @@
+use std::collections::hash_map::DefaultHasher;
+use std::collections::{BTreeMap, BTreeSet};
+use std::fs;
+use std::hash::{Hash, Hasher};
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use crate::{stats, Todo};
+
+/// Completions are recomputed at most this often, so pressing tab doesn't
+/// rescan the repository every time.
+const CACHE_TTL: Duration = Duration::from_secs(60);
+
+type Values = BTreeMap<String, BTreeSet<String>>;
+
+/// A file in the user's cache directory, like `~/.cache/codo/`, per working
+/// directory and the scan's inputs.
+fn cache_path(scan_inputs: &[Vec<String>]) -> Option<PathBuf> {
+    let mut hasher = DefaultHasher::new();
+    std::env::current_dir().ok().hash(&mut hasher);
+    scan_inputs.hash(&mut hasher);
+
+    let cache_dir = std::env::var_os("XDG_CACHE_HOME")
+        .filter(|dir| !dir.is_empty())
+        .map(PathBuf::from)
+        .or_else(|| std::env::var_os("HOME").map(|home| Path::new(&home).join(".cache")))?;
+    let dir = cache_dir.join("codo");
+    fs::create_dir_all(&dir).ok()?;
+    Some(dir.join(format!("complete-{:x}.json", hasher.finish())))
+}
+
+fn read_cache(path: &PathBuf) -> Option<Values> {
+    let age = fs::metadata(path).ok()?.modified().ok()?.elapsed().ok()?;
+    if age > CACHE_TTL {
+        return None;
+    }
+
+    serde_json::from_str(&fs::read_to_string(path).ok()?).ok()
+}
+
+fn collect_values(todos: &[Todo]) -> Values {
+    let mut values = Values::new();
+    for todo in todos {
+        let metadata = &todo.metadata;
+        let pairs = metadata
+            .assignees
+            .iter()
+            .map(|assignee| ("assignee", Some(assignee.clone())))
+            .chain([
+                ("issue", metadata.issue.as_ref().map(|i| i.as_string())),
+                ("due", metadata.due.clone()),
+            ]);
+        for (flag, value) in pairs {
+            if let Some(value) = value {
+                values.entry(flag.to_owned()).or_default().insert(value);
+            }
+        }
+    }
+
+    values
+}
+
+/// Existing values of `flag` (`--assignee`, `--issue`, or `--due`) starting
+/// with `prefix`, for shell completion scripts.
+pub fn complete(
+    flag: &str,
+    prefix: &str,
+    scan_inputs: &[Vec<String>],
+    scan: &dyn Fn() -> Vec<Todo>,
+) -> Vec<String> {
+    let cache = cache_path(scan_inputs);
+    stats::count(&stats::CACHE_LOOKUPS, 1);
+    let values = cache
+        .as_ref()
+        .and_then(read_cache)
+        .inspect(|_| stats::count(&stats::CACHE_HITS, 1));
+    let values = values.unwrap_or_else(|| {
+        let values = collect_values(&scan());
+        if let (Some(cache), Ok(contents)) = (&cache, serde_json::to_string(&values)) {
+            let _ = fs::write(cache, contents);
+        }
+        values
+    });
+
+    let flag = flag.trim_start_matches('-');
+    values
+        .get(flag)
+        .map(|values| {
+            values
+                .iter()
+                .filter(|value| value.starts_with(prefix))
+                .cloned()
+                .collect()
+        })
+        .unwrap_or_default()
+}
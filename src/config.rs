@@ -0,0 +1,472 @@
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::Path;
+
+use chrono::{NaiveDate, Weekday};
+use encoding_rs::Encoding;
+use globset::{Glob, GlobMatcher};
+use serde::Deserialize;
+
+use crate::globs::{OwnerRules, PathFilter};
+use crate::http::HttpConfig;
+use crate::markers::{self, Marker};
+use crate::tracker::TrackerConfig;
+use crate::{MetaKind, DEFAULT_META_ORDER, DELIMITERS};
+
+pub const DEFAULT_CONFIG_PATH: &str = ".codo.toml";
+
+#[derive(Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Config {
+    /// Extra due date formats (chrono strftime syntax) accepted besides
+    /// ISO 8601. Dates in these formats are rewritten as ISO by `format`.
+    pub date_formats: Vec<String>,
+
+    /// The order `format` writes metadata in, either a list of `issue`,
+    /// `assignee`, and `due`, or `"preserve"` to keep each TODO's own order.
+    pub meta_order: MetaOrder,
+
+    /// Encodings of files matching each glob, for legacy files without a
+    /// byte order mark, e.g. `"legacy/**" = "windows-1252"`.
+    pub encodings: BTreeMap<String, String>,
+
+    /// The comment delimiter `mod change-delimiter` converts TODOs to, per
+    /// file extension, e.g. `cpp = "//"`.
+    pub comment_styles: BTreeMap<String, String>,
+
+    /// How `format` writes TODOs per file extension, e.g.
+    /// `py = { colon = false }` for `# TODO(@alice) note` in Python.
+    pub todo_styles: BTreeMap<String, TodoStyle>,
+
+    /// Keywords scanned for besides TODO, like
+    /// `{ keyword = "HACK", severity = "warning" }`.
+    pub markers: Vec<Marker>,
+
+    /// The repository's web URL permalinks point to, e.g.
+    /// `https://github.com/org/repo`, instead of the origin remote's.
+    pub repo_url: Option<String>,
+
+    /// Networking settings for issue tracker integrations.
+    pub http: HttpConfig,
+
+    pub tracker: Option<TrackerConfig>,
+
+    /// Validation rules for TODOs in files matching the given globs, which
+    /// override the command line flags. Later entries take precedence.
+    pub rules: Vec<ScopedRules>,
+
+    /// Lines `format` and the code mods write longer than this are handled
+    /// per `long_lines`.
+    pub max_line_length: Option<usize>,
+
+    pub long_lines: LongLines,
+
+    /// Assignees still on the team, for `validate --forbid-assignee-left`.
+    /// Without it, anyone who committed recently counts.
+    pub active_members: Option<Vec<String>>,
+
+    /// Where to link or mention each assignee in reports, keyed by name.
+    pub assignees: BTreeMap<String, AssigneeProfile>,
+
+    /// The most TODOs allowed under each directory, e.g. `"src/api" = 25`,
+    /// enforced by `budget` and `validate`.
+    pub budget: BTreeMap<String, usize>,
+
+    /// Default assignees of paths matching each glob, e.g.
+    /// `"src/auth/**" = "@security-team"`, for `mod assign-by-rules`.
+    pub owners: BTreeMap<String, String>,
+
+    /// Due dates TODOs citing an issue inherit when they have none of their
+    /// own, either a date or the name of one of the `milestones`.
+    pub issue_due_dates: BTreeMap<String, String>,
+
+    /// Dates of named milestones, e.g. `v2 = "2025-06-30"`.
+    pub milestones: BTreeMap<String, String>,
+
+    /// Languages of file extensions for `--group-by language`, adding to or
+    /// overriding the built-in ones, e.g. `jsx = "React"`.
+    pub languages: BTreeMap<String, String>,
+
+    /// Weekday names accepted by `due:` besides English ones, mapped to the
+    /// English name, e.g. `vendredi = "fri"`.
+    pub weekday_names: BTreeMap<String, String>,
+
+    /// Also reads issues in brackets and assignees in parentheses before
+    /// the note, like `TODO [ABC-123] (alice): note`.
+    pub bracket_metadata: bool,
+
+    /// Also reads metadata in parentheses after the note, like
+    /// `TODO: note (due 2025-01-01)`.
+    pub trailing_metadata: bool,
+
+    /// The command `list --open` runs, with `{path}`, `{line}`, and
+    /// `{column}` replaced, e.g. `code -g {path}:{line}`. Without it,
+    /// `$VISUAL` or `$EDITOR` is run like `vim +12 src/main.rs`.
+    pub editor: Option<String>,
+
+    /// Applies the global gitignore, set by git's `core.excludesFile`, like
+    /// git and ripgrep do.
+    pub global_gitignore: bool,
+
+    /// Applies the repository's `.git/info/exclude`.
+    pub git_exclude: bool,
+
+    /// Lets edits write files marked as generated, from `--include-generated`
+    /// rather than the config file.
+    #[serde(skip)]
+    pub include_generated: bool,
+
+    /// The globs of `encodings`, compiled when the config is loaded rather
+    /// than for every file scanned.
+    #[serde(skip)]
+    pub encoding_globs: Vec<(GlobMatcher, &'static Encoding)>,
+}
+
+#[derive(Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct AssigneeProfile {
+    /// Profile page linked from Markdown and HTML reports.
+    pub url: Option<String>,
+    /// Slack member ID, e.g. `U012AB3CD`, mentioned in Slack reports.
+    pub slack: Option<String>,
+    /// Git author names and emails this assignee commits as.
+    pub aliases: Vec<String>,
+    /// Lets anyone assign TODOs to them, e.g. for a team, with
+    /// `--require-author-assignee`.
+    pub shared: bool,
+}
+
+#[derive(Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct TodoStyle {
+    /// The keyword as written, `TODO` in any case.
+    pub keyword: String,
+    /// Whether a colon follows the keyword and metadata.
+    pub colon: bool,
+}
+
+impl Default for TodoStyle {
+    fn default() -> Self {
+        TodoStyle {
+            keyword: "TODO".to_owned(),
+            colon: true,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Deserialize, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum LongLines {
+    /// Wraps the note onto continuation comment lines.
+    Wrap,
+    /// Writes the line as is, with a warning.
+    Warn,
+}
+
+#[derive(Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ScopedRules {
+    pub paths: Vec<String>,
+    pub require_assignees: Option<bool>,
+    pub require_issues: Option<bool>,
+    pub require_due_dates: Option<bool>,
+    pub forbid_bare: Option<bool>,
+}
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+pub enum MetaOrder {
+    Preserve(PreserveOrder),
+    Fixed(Vec<MetaKind>),
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum PreserveOrder {
+    Preserve,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Config {
+            date_formats: vec!["%Y.%m.%d".to_owned(), "%b %d %Y".to_owned()],
+            meta_order: MetaOrder::Fixed(DEFAULT_META_ORDER.to_vec()),
+            encodings: BTreeMap::new(),
+            comment_styles: BTreeMap::new(),
+            todo_styles: BTreeMap::new(),
+            markers: markers::registry(vec![]).expect("valid default markers"),
+            repo_url: None,
+            http: HttpConfig::default(),
+            tracker: None,
+            rules: vec![],
+            max_line_length: None,
+            long_lines: LongLines::Wrap,
+            active_members: None,
+            assignees: BTreeMap::new(),
+            budget: BTreeMap::new(),
+            owners: BTreeMap::new(),
+            issue_due_dates: BTreeMap::new(),
+            milestones: BTreeMap::new(),
+            languages: BTreeMap::new(),
+            weekday_names: BTreeMap::new(),
+            bracket_metadata: false,
+            trailing_metadata: false,
+            editor: None,
+            global_gitignore: true,
+            git_exclude: true,
+            include_generated: false,
+            encoding_globs: vec![],
+        }
+    }
+}
+
+impl Config {
+    /// The config file `load` reads: `path`, or `.codo.toml` in the working
+    /// directory when it exists.
+    pub fn path(path: Option<&str>) -> Option<&Path> {
+        match path {
+            Some(path) => Some(Path::new(path)),
+            None if Path::new(DEFAULT_CONFIG_PATH).exists() => Some(Path::new(DEFAULT_CONFIG_PATH)),
+            None => None,
+        }
+    }
+
+    /// Loads the config at `path`, or `.codo.toml` in the working directory
+    /// when it exists, falling back to the defaults otherwise.
+    pub fn load(path: Option<&str>) -> Result<Config, String> {
+        let Some(path) = Config::path(path) else {
+            return Ok(Config::default());
+        };
+
+        let contents = fs::read_to_string(path)
+            .map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+
+        let mut config: Config = toml::from_str(&contents).map_err(|e| {
+            format!(
+                "Invalid {}: {}",
+                path.display(),
+                describe_error(&contents, &e)
+            )
+        })?;
+
+        config.markers = markers::registry(std::mem::take(&mut config.markers))
+            .map_err(|e| format!("Invalid {}: markers: {}", path.display(), e))?;
+
+        for (pattern, label) in &config.encodings {
+            let glob = Glob::new(pattern)
+                .map_err(|e| format!("Invalid {}: encodings: {}", path.display(), e))?;
+            let encoding = Encoding::for_label(label.as_bytes()).ok_or_else(|| {
+                format!(
+                    "Invalid {}: encodings: unknown encoding \"{}\"",
+                    path.display(),
+                    label
+                )
+            })?;
+            config
+                .encoding_globs
+                .push((glob.compile_matcher(), encoding));
+        }
+
+        for (extension, delimiter) in &config.comment_styles {
+            if !DELIMITERS.contains(&delimiter.as_str()) {
+                return Err(format!(
+                    "Invalid {}: comment_styles: {} has unknown delimiter \"{}\"",
+                    path.display(),
+                    extension,
+                    delimiter
+                ));
+            }
+        }
+
+        for (extension, style) in &config.todo_styles {
+            if !style.keyword.eq_ignore_ascii_case("TODO") {
+                return Err(format!(
+                    "Invalid {}: todo_styles: {} has keyword \"{}\", which isn't TODO",
+                    path.display(),
+                    extension,
+                    style.keyword
+                ));
+            }
+        }
+
+        OwnerRules::new(&config.owners)
+            .map_err(|e| format!("Invalid {}: owners: {}", path.display(), e))?;
+
+        for rules in &config.rules {
+            PathFilter::new(&rules.paths)
+                .map_err(|e| format!("Invalid {}: rules: {}", path.display(), e))?;
+        }
+
+        for (name, date) in &config.milestones {
+            NaiveDate::parse_from_str(date, "%Y-%m-%d").map_err(|_| {
+                format!(
+                    "Invalid {}: milestones: {} has invalid date \"{}\"",
+                    path.display(),
+                    name,
+                    date
+                )
+            })?;
+        }
+
+        for issue in config.issue_due_dates.keys() {
+            if config.issue_due_date(issue).is_none() {
+                return Err(format!(
+                    "Invalid {}: issue_due_dates: {} is neither a date nor a milestone",
+                    path.display(),
+                    issue
+                ));
+            }
+        }
+
+        for (name, weekday) in &config.weekday_names {
+            weekday.parse::<Weekday>().map_err(|_| {
+                format!(
+                    "Invalid {}: weekday_names: {} has invalid weekday \"{}\"",
+                    path.display(),
+                    name,
+                    weekday
+                )
+            })?;
+        }
+
+        Ok(config)
+    }
+
+    /// The marker with the keyword, in any case.
+    pub fn marker(&self, keyword: &str) -> Option<&Marker> {
+        self.markers
+            .iter()
+            .find(|marker| marker.keyword.eq_ignore_ascii_case(keyword))
+    }
+
+    /// The English weekday `name` stands for, if it's a configured name.
+    pub fn weekday_name(&self, name: &str) -> Option<&str> {
+        self.weekday_names
+            .iter()
+            .find(|(local, _)| local.eq_ignore_ascii_case(name))
+            .map(|(_, weekday)| weekday.as_str())
+    }
+
+    /// How TODOs in the file are written, from `todo_styles` for its
+    /// extension.
+    pub fn todo_style(&self, path: &Path) -> Option<&TodoStyle> {
+        let extension = path.extension()?.to_str()?;
+        self.todo_styles
+            .iter()
+            .find(|(known, _)| {
+                known
+                    .trim_start_matches('.')
+                    .eq_ignore_ascii_case(extension)
+            })
+            .map(|(_, style)| style)
+    }
+
+    /// The due date configured for `issue`, with milestones resolved.
+    pub fn issue_due_date(&self, issue: &str) -> Option<String> {
+        let due = self.issue_due_dates.get(issue)?;
+        if let Some(date) = self.milestones.get(due) {
+            return Some(date.to_owned());
+        }
+
+        NaiveDate::parse_from_str(due, "%Y-%m-%d")
+            .ok()
+            .map(|_| due.to_owned())
+    }
+}
+
+/// Describes a TOML error by the key it's at and its line, suggesting the
+/// closest expected name for unknown keys and values.
+fn describe_error(contents: &str, error: &toml::de::Error) -> String {
+    let mut message = error.message().trim_end().to_owned();
+    if let Some(suggestion) = suggestion(&message) {
+        message = format!("{}; did you mean `{}`?", message, suggestion);
+    }
+
+    let Some(span) = error.span() else {
+        return message;
+    };
+    let line = contents[..span.start].matches('\n').count() + 1;
+    match key_path(contents, span.start) {
+        Some(key) => format!("{} (line {}): {}", key, line, message),
+        None => format!("line {}: {}", line, message),
+    }
+}
+
+/// The dotted path of the key on the line at `offset`, like `http.retries`
+/// or `rules[1].paths`, from the table header above it.
+fn key_path(contents: &str, offset: usize) -> Option<String> {
+    let line_start = contents[..offset].rfind('\n').map_or(0, |i| i + 1);
+    let line_end = contents[offset..]
+        .find('\n')
+        .map_or(contents.len(), |i| offset + i);
+    let line = contents[line_start..line_end].trim();
+    let above = &contents[..line_start];
+
+    if line.starts_with('[') {
+        return table_path(above, line);
+    }
+    let (key, _) = line.split_once('=')?;
+    let key = key.trim();
+    match above
+        .lines()
+        .rev()
+        .map(str::trim)
+        .find(|header| header.starts_with('['))
+    {
+        Some(header) => {
+            let start = above.rfind(header).unwrap_or(0);
+            Some(format!("{}.{}", table_path(&above[..start], header)?, key))
+        }
+        None => Some(key.to_owned()),
+    }
+}
+
+/// The path of a `[table]` or the `[[array]]` entry `header` opens, counting
+/// the array's entries in the `above` lines.
+fn table_path(above: &str, header: &str) -> Option<String> {
+    if let Some(name) = header
+        .strip_prefix("[[")
+        .and_then(|rest| rest.split_once("]]"))
+    {
+        let index = above
+            .lines()
+            .filter(|line| line.trim().starts_with(header))
+            .count();
+        return Some(format!("{}[{}]", name.0.trim(), index));
+    }
+    let (name, _) = header.strip_prefix('[')?.split_once(']')?;
+    Some(name.trim().to_owned())
+}
+
+/// For an `unknown field` or `unknown variant` message, the expected name
+/// closest to the unknown one, if it's close enough to be a typo.
+fn suggestion(message: &str) -> Option<&str> {
+    let rest = message
+        .strip_prefix("unknown field `")
+        .or_else(|| message.strip_prefix("unknown variant `"))?;
+    let (unknown, expected) = rest.split_once('`')?;
+    expected
+        .split('`')
+        .skip(1)
+        .step_by(2)
+        .map(|name| (edit_distance(unknown, name), name))
+        .filter(|(distance, _)| *distance <= unknown.len() / 3 + 1)
+        .min_by_key(|(distance, _)| *distance)
+        .map(|(_, name)| name)
+}
+
+/// The Levenshtein distance between `a` and `b`, case-insensitively.
+fn edit_distance(a: &str, b: &str) -> usize {
+    let a: Vec<char> = a.to_lowercase().chars().collect();
+    let b: Vec<char> = b.to_lowercase().chars().collect();
+    let mut previous: Vec<usize> = (0..=b.len()).collect();
+    for (i, a_char) in a.iter().enumerate() {
+        let mut current = vec![i + 1];
+        for (j, b_char) in b.iter().enumerate() {
+            let substitution = previous[j] + usize::from(a_char != b_char);
+            current.push(substitution.min(previous[j + 1] + 1).min(current[j] + 1));
+        }
+        previous = current;
+    }
+    previous[b.len()]
+}
@@ -0,0 +1,320 @@
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+
+use grep::regex::RegexMatcher;
+use serde::Serialize;
+use serde_json::Value;
+
+use crate::config::Config;
+use crate::interactive::{FilePrompt, LineChange};
+use crate::markers;
+use crate::{
+    is_unchanged_line, parse_todo_line, relocate_updates, rewrite_line, write_replacing, Todo,
+    TodoUpdate, UpdateSummary, RELOCATE_WINDOW,
+};
+
+fn read_notebook(path: &Path) -> Result<Value, String> {
+    let contents = fs::read_to_string(path)
+        .map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+
+    serde_json::from_str(&contents)
+        .map_err(|e| format!("Failed to parse notebook {}: {}", path.display(), e))
+}
+
+fn cell_source_lines(cell: &Value) -> Vec<String> {
+    let source = match &cell["source"] {
+        Value::String(source) => source.to_owned(),
+        Value::Array(parts) => parts
+            .iter()
+            .filter_map(|part| part.as_str())
+            .collect::<Vec<&str>>()
+            .concat(),
+        _ => String::new(),
+    };
+
+    source.split('\n').map(|s| s.to_owned()).collect()
+}
+
+/// The source of the notebook cell at `index`.
+pub fn cell_source(path: &Path, index: usize) -> Result<String, String> {
+    let notebook = read_notebook(path)?;
+    Ok(cell_source_lines(&notebook["cells"][index]).join("\n"))
+}
+
+/// Where line `line` and column `column` of the source of the notebook cell
+/// at `index` are in the notebook's file, for tools that only know files.
+/// Assumes the source is an array of lines, one per line of the file, as
+/// Jupyter writes it.
+pub fn notebook_file_position(
+    path: &Path,
+    index: usize,
+    line: u64,
+    column: u64,
+) -> Option<(u64, u64)> {
+    let contents = fs::read_to_string(path).ok()?;
+    let notebook: Value = serde_json::from_str(&contents).ok()?;
+
+    // Every cell has a source, so the cell's is the key's nth occurrence.
+    let (offset, _) = contents.match_indices("\"source\":").nth(index)?;
+    let source_line = contents[..offset].matches('\n').count() as u64 + 1;
+    let Value::Array(parts) = &notebook["cells"][index]["source"] else {
+        return Some((source_line, column));
+    };
+
+    // Finds the part holding the line, and whether the line starts it.
+    let mut preceding = line.checked_sub(1)? as usize;
+    for (part_index, part) in parts.iter().enumerate() {
+        let newlines = part.as_str()?.matches('\n').count();
+        if preceding < newlines || part_index + 1 == parts.len() {
+            let file_line = source_line + 1 + part_index as u64;
+            if preceding > 0 {
+                return Some((file_line, column));
+            }
+            // Past the indentation and the opening quote.
+            let text = contents.lines().nth(file_line as usize - 1)?;
+            let quote = text.chars().position(|c| c == '"')? as u64;
+            return Some((file_line, quote + 1 + column));
+        }
+        preceding -= newlines;
+    }
+    None
+}
+
+fn is_code_cell(cell: &Value) -> bool {
+    cell["cell_type"].as_str() == Some("code")
+}
+
+/// Scans the code cells of a Jupyter notebook. Line numbers are relative to
+/// the cell, which is identified by its index in the notebook.
+pub fn scan_notebook(
+    matcher: &RegexMatcher,
+    config: &Config,
+    path: &Path,
+) -> Result<Vec<Todo>, String> {
+    let notebook = read_notebook(path)?;
+    let cells = notebook["cells"].as_array().cloned().unwrap_or_default();
+
+    let mut todos: Vec<Todo> = vec![];
+    for (cell_index, cell) in cells.iter().enumerate() {
+        if !is_code_cell(cell) {
+            continue;
+        }
+
+        for (num, line) in cell_source_lines(cell).iter().enumerate() {
+            if let Some(mut todo) = parse_todo_line(matcher, config, path, num as u64 + 1, line) {
+                todo.cell = Some(cell_index);
+                todos.push(todo);
+            }
+        }
+    }
+
+    Ok(todos)
+}
+
+/// Scans only the `<script>` blocks of single-file components, so template
+/// markup that happens to look like a comment is not picked up.
+pub fn scan_script_blocks(
+    matcher: &RegexMatcher,
+    config: &Config,
+    path: &Path,
+) -> Result<Vec<Todo>, String> {
+    let contents = fs::read_to_string(path)
+        .map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+
+    let mut todos: Vec<Todo> = vec![];
+    let mut in_script = false;
+    for (num, line) in contents.lines().enumerate() {
+        let trimmed = line.trim_start();
+        if trimmed.starts_with("<script") {
+            in_script = !trimmed.contains("</script>");
+            continue;
+        }
+
+        if trimmed.starts_with("</script>") {
+            in_script = false;
+            continue;
+        }
+
+        if in_script {
+            if let Some(todo) = parse_todo_line(matcher, config, path, num as u64 + 1, line) {
+                todos.push(todo);
+            }
+        }
+    }
+
+    Ok(todos)
+}
+
+/// Matches Markdown task items like `- [ ] TODO(@alice): note`, with the
+/// bullet and checkbox taking the place of the comment delimiter.
+fn checklist_matcher(config: &Config) -> &'static RegexMatcher {
+    static MATCHER: OnceLock<RegexMatcher> = OnceLock::new();
+    MATCHER.get_or_init(|| {
+        RegexMatcher::new(&format!(
+            r"(?m)^\s*([-*+] \[ \]) {}(?:\((.*?)\))?:? (.+?)$",
+            markers::keyword_pattern(&config.markers)
+        ))
+        .expect("valid checklist pattern")
+    })
+}
+
+/// Parses a line of Markdown, where unchecked task items are TODOs as well as
+/// comments, e.g. in code blocks.
+pub fn parse_markdown_line(
+    matcher: &RegexMatcher,
+    config: &Config,
+    path: &Path,
+    line_number: u64,
+    line: &str,
+) -> Option<Todo> {
+    parse_todo_line(checklist_matcher(config), config, path, line_number, line)
+        .or_else(|| parse_todo_line(matcher, config, path, line_number, line))
+}
+
+pub fn is_markdown(path: &Path) -> bool {
+    matches!(
+        path.extension().and_then(|e| e.to_str()),
+        Some("md" | "markdown")
+    )
+}
+
+pub fn scan_markdown(
+    matcher: &RegexMatcher,
+    config: &Config,
+    path: &Path,
+) -> Result<Vec<Todo>, String> {
+    let contents = fs::read_to_string(path)
+        .map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+
+    Ok(contents
+        .lines()
+        .enumerate()
+        .filter_map(|(num, line)| parse_markdown_line(matcher, config, path, num as u64 + 1, line))
+        .collect())
+}
+
+fn write_notebook(path: &Path, notebook: &Value) -> Result<(), String> {
+    // Jupyter writes notebooks with single space indentation.
+    let mut output: Vec<u8> = vec![];
+    let formatter = serde_json::ser::PrettyFormatter::with_indent(b" ");
+    let mut serializer = serde_json::Serializer::with_formatter(&mut output, formatter);
+    notebook
+        .serialize(&mut serializer)
+        .map_err(|e| format!("Failed to serialize notebook {}: {}", path.display(), e))?;
+    output.push(b'\n');
+
+    write_replacing(path, &output).map_err(|e| format!("Failed to write {}: {}", path.display(), e))
+}
+
+/// Rewrites notebooks like `apply_updates`, adding the changes written to
+/// `summary` and returning the notebooks that failed to be read or written.
+pub fn apply_notebook_updates(
+    updates: Vec<TodoUpdate>,
+    force: bool,
+    mut prompt: Option<&mut FilePrompt>,
+    summary: &mut UpdateSummary,
+    config: &Config,
+) -> Vec<String> {
+    let mut errors: Vec<String> = vec![];
+    let mut file_updates: BTreeMap<PathBuf, Vec<TodoUpdate>> = BTreeMap::new();
+    for update in updates.into_iter() {
+        file_updates
+            .entry(update.path.clone())
+            .or_default()
+            .push(update);
+    }
+
+    for (path, updates) in file_updates {
+        let mut notebook = match read_notebook(&path) {
+            Ok(notebook) => notebook,
+            Err(err) => {
+                errors.push(err);
+                continue;
+            }
+        };
+
+        let mut cell_updates: BTreeMap<usize, Vec<TodoUpdate>> = BTreeMap::new();
+        for update in updates {
+            cell_updates
+                .entry(update.cell.unwrap_or_default())
+                .or_default()
+                .push(update);
+        }
+        let mut updates: Vec<TodoUpdate> = cell_updates
+            .into_iter()
+            .flat_map(|(cell, updates)| {
+                let source = cell_source_lines(&notebook["cells"][cell]);
+                let lines: Vec<&str> = source.iter().map(String::as_str).collect();
+                relocate_updates(&lines, updates, RELOCATE_WINDOW)
+            })
+            .collect();
+        // Later lines first, so removing a line doesn't shift the ones left.
+        updates.sort_by_key(|update| std::cmp::Reverse((update.cell, update.line_number)));
+
+        let mut changes: Vec<LineChange> = vec![];
+        for update in updates {
+            let cell_index = update.cell.unwrap_or_default();
+            let cell = &mut notebook["cells"][cell_index];
+            if !is_code_cell(cell) {
+                continue;
+            }
+
+            let mut lines = cell_source_lines(cell);
+            let line_index = (update.line_number - 1) as usize;
+            let Some(line) = lines.get(line_index) else {
+                continue;
+            };
+
+            if !is_unchanged_line(line, &update, force) {
+                continue;
+            }
+
+            let location = update.location();
+            let new_line = rewrite_line(line, update, config);
+            if new_line.as_deref() != Some(line.as_str()) {
+                changes.push(LineChange {
+                    location,
+                    old: line.to_owned(),
+                    new: new_line.clone(),
+                });
+            }
+            match new_line {
+                Some(new_line) => lines[line_index] = new_line,
+                None => {
+                    lines.remove(line_index);
+                }
+            }
+
+            let source = lines.join("\n");
+            cell["source"] = match cell["source"] {
+                Value::String(_) => Value::String(source),
+                _ => Value::Array(
+                    source
+                        .split_inclusive('\n')
+                        .map(|part| Value::String(part.to_owned()))
+                        .collect(),
+                ),
+            };
+        }
+
+        // Changes were made from the last line up, so they're shown reversed.
+        changes.reverse();
+        if changes.is_empty() {
+            continue;
+        }
+        if let Some(prompt) = prompt.as_deref_mut() {
+            if !prompt.confirm(&path, &changes) {
+                continue;
+            }
+        }
+
+        match write_notebook(&path, &notebook) {
+            Ok(()) => summary.add_file(changes),
+            Err(err) => errors.push(err),
+        }
+    }
+
+    errors
+}
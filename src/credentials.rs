@@ -0,0 +1,329 @@
+use std::collections::BTreeMap;
+use std::fs;
+use std::io::{BufRead, IsTerminal, Write};
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+
+/// Services with API tokens, named like the tracker providers.
+pub const SERVICES: [&str; 4] = ["github", "gitlab", "jira", "linear"];
+
+/// Where a token was found.
+enum Source {
+    Env(String),
+    Keychain,
+    File(PathBuf),
+}
+
+impl Source {
+    fn describe(&self) -> String {
+        match self {
+            Source::Env(var) => format!("from ${}", var),
+            Source::Keychain => "from the OS keychain".to_owned(),
+            Source::File(path) => format!("from {}", path.display()),
+        }
+    }
+}
+
+/// The credentials file, `$CODO_CREDENTIALS` or `credentials` in codo's
+/// config directory, like `~/.config/codo/credentials`.
+fn credentials_path() -> Option<PathBuf> {
+    if let Some(path) = std::env::var_os("CODO_CREDENTIALS").filter(|path| !path.is_empty()) {
+        return Some(PathBuf::from(path));
+    }
+
+    let config_dir = std::env::var_os("XDG_CONFIG_HOME")
+        .filter(|dir| !dir.is_empty())
+        .map(PathBuf::from)
+        .or_else(|| std::env::var_os("HOME").map(|home| Path::new(&home).join(".config")))?;
+    Some(config_dir.join("codo").join("credentials"))
+}
+
+/// Tokens can't be read by other users, so files that allow it are refused.
+#[cfg(unix)]
+fn check_permissions(path: &Path, metadata: &fs::Metadata) -> Result<(), String> {
+    use std::os::unix::fs::PermissionsExt;
+
+    if metadata.permissions().mode() & 0o077 != 0 {
+        return Err(format!(
+            "{} is accessible by other users, run `chmod 600 {}`",
+            path.display(),
+            path.display()
+        ));
+    }
+    Ok(())
+}
+
+#[cfg(not(unix))]
+fn check_permissions(_path: &Path, _metadata: &fs::Metadata) -> Result<(), String> {
+    Ok(())
+}
+
+/// The tokens in the credentials file, like `github = "ghp_..."`, by
+/// service. A missing file has none.
+fn read_file(path: &Path) -> Result<BTreeMap<String, String>, String> {
+    let metadata = match fs::metadata(path) {
+        Ok(metadata) => metadata,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(BTreeMap::new()),
+        Err(e) => return Err(format!("Failed to read {}: {}", path.display(), e)),
+    };
+    check_permissions(path, &metadata)?;
+
+    let contents = fs::read_to_string(path)
+        .map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+    toml::from_str(&contents).map_err(|e| format!("Invalid {}: {}", path.display(), e))
+}
+
+fn write_file(path: &Path, tokens: &BTreeMap<String, String>) -> Result<(), String> {
+    let contents = toml::to_string(tokens)
+        .map_err(|e| format!("Failed to write {}: {}", path.display(), e))?;
+    if let Some(dir) = path.parent().filter(|dir| !dir.as_os_str().is_empty()) {
+        fs::create_dir_all(dir)
+            .map_err(|e| format!("Failed to create {}: {}", dir.display(), e))?;
+    }
+
+    let mut options = fs::OpenOptions::new();
+    options.write(true).create(true).truncate(true);
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::{OpenOptionsExt, PermissionsExt};
+        options.mode(0o600);
+        // The mode only applies to new files, so existing ones are fixed up.
+        if path.exists() {
+            fs::set_permissions(path, fs::Permissions::from_mode(0o600))
+                .map_err(|e| format!("Failed to write {}: {}", path.display(), e))?;
+        }
+    }
+
+    options
+        .open(path)
+        .and_then(|mut file| file.write_all(contents.as_bytes()))
+        .map_err(|e| format!("Failed to write {}: {}", path.display(), e))
+}
+
+fn keychain_password(service: &str) -> Option<String> {
+    let output = if cfg!(target_os = "macos") {
+        Command::new("security")
+            .args(["find-generic-password", "-s", "codo", "-a", service, "-w"])
+            .output()
+    } else {
+        Command::new("secret-tool")
+            .args(["lookup", "service", "codo", "account", service])
+            .output()
+    };
+
+    let output = output.ok().filter(|output| output.status.success())?;
+    let password = String::from_utf8(output.stdout).ok()?.trim().to_owned();
+    Some(password).filter(|password| !password.is_empty())
+}
+
+/// Stores the token through stdin, so it never shows up in `ps`: as a
+/// command of `security`'s interactive mode on macOS.
+fn keychain_store(service: &str, token: &str) -> Result<(), String> {
+    let (mut command, input) = if cfg!(target_os = "macos") {
+        let quote =
+            |value: &str| format!("\"{}\"", value.replace('\\', "\\\\").replace('"', "\\\""));
+        let mut command = Command::new("security");
+        command.arg("-i").stdout(Stdio::null());
+        let input = format!(
+            "add-generic-password -U -s codo -a {} -w {}\n",
+            quote(service),
+            quote(token)
+        );
+        (command, input)
+    } else {
+        let mut command = Command::new("secret-tool");
+        command
+            .args(["store", "--label", &format!("codo {} token", service)])
+            .args(["service", "codo", "account", service]);
+        (command, token.to_owned())
+    };
+
+    let status = command.stdin(Stdio::piped()).spawn().and_then(|mut child| {
+        if let Some(mut stdin) = child.stdin.take() {
+            stdin.write_all(input.as_bytes())?;
+        }
+        child.wait()
+    });
+
+    match status {
+        Ok(status) if status.success() => Ok(()),
+        Ok(status) => Err(format!(
+            "Failed to store the token in the OS keychain: {}",
+            status
+        )),
+        Err(e) => Err(format!(
+            "Failed to store the token in the OS keychain: {}",
+            e
+        )),
+    }
+}
+
+/// Removes the service's keychain entry, returning whether there was one.
+fn keychain_delete(service: &str) -> bool {
+    if keychain_password(service).is_none() {
+        return false;
+    }
+
+    let status = if cfg!(target_os = "macos") {
+        Command::new("security")
+            .args(["delete-generic-password", "-s", "codo", "-a", service])
+            .stdout(Stdio::null())
+            .status()
+    } else {
+        Command::new("secret-tool")
+            .args(["clear", "service", "codo", "account", service])
+            .status()
+    };
+    status.is_ok_and(|status| status.success())
+}
+
+fn env_vars(service: &str) -> [String; 2] {
+    let name = service.to_uppercase();
+    [format!("CODO_{}_TOKEN", name), format!("{}_TOKEN", name)]
+}
+
+/// The token for `service`, from `CODO_GITHUB_TOKEN` or `GITHUB_TOKEN` for
+/// `github`, then the OS keychain entry `codo`/`github`, then the
+/// credentials file.
+fn lookup(service: &str) -> Result<Option<(String, Source)>, String> {
+    for var in env_vars(service) {
+        if let Some(token) = std::env::var(&var).ok().filter(|token| !token.is_empty()) {
+            return Ok(Some((token, Source::Env(var))));
+        }
+    }
+    if let Some(token) = keychain_password(service) {
+        return Ok(Some((token, Source::Keychain)));
+    }
+
+    let Some(path) = credentials_path() else {
+        return Ok(None);
+    };
+    let token = read_file(&path)?
+        .remove(service)
+        .filter(|token| !token.is_empty());
+    Ok(token.map(|token| (token, Source::File(path))))
+}
+
+/// The API token for `service` (e.g. `github`), if one is set up.
+pub fn token(service: &str) -> Option<String> {
+    lookup(service)
+        .unwrap_or_else(|e| {
+            eprintln!("{}", e);
+            None
+        })
+        .map(|(token, _)| token)
+}
+
+fn check_service(service: &str) -> Result<(), String> {
+    if SERVICES.contains(&service) {
+        Ok(())
+    } else {
+        Err(format!(
+            "Unknown service \"{}\", expected one of {}",
+            service,
+            SERVICES.join(", ")
+        ))
+    }
+}
+
+/// Reads a token from stdin, prompting without echoing it when it's a
+/// terminal. Unknown services are rejected before anything is read.
+pub fn read_token(service: &str) -> Result<String, String> {
+    check_service(service)?;
+    let stdin = std::io::stdin();
+    let terminal = stdin.is_terminal();
+    let set_echo = |echo: &str| {
+        let _ = Command::new("stty")
+            .arg(echo)
+            .stdin(Stdio::inherit())
+            .status();
+    };
+    if terminal {
+        eprint!("Paste the {} token: ", service);
+        set_echo("-echo");
+    }
+
+    let mut token = String::new();
+    let result = stdin.lock().read_line(&mut token);
+    if terminal {
+        set_echo("echo");
+        eprintln!();
+    }
+    result
+        .map(|_| token)
+        .map_err(|e| format!("Failed to read the token: {}", e))
+}
+
+/// Stores the token for `service`, in the credentials file or the OS
+/// keychain, returning where.
+pub fn login(service: &str, token: &str, keychain: bool) -> Result<String, String> {
+    check_service(service)?;
+    let token = token.trim();
+    if token.is_empty() {
+        return Err("No token given, pipe or paste it to stdin".to_owned());
+    }
+
+    if keychain {
+        keychain_store(service, token)?;
+        return Ok(format!("Stored the {} token in the OS keychain", service));
+    }
+
+    let path = credentials_path().ok_or("No home directory, set $CODO_CREDENTIALS")?;
+    let mut tokens = read_file(&path)?;
+    tokens.insert(service.to_owned(), token.to_owned());
+    write_file(&path, &tokens)?;
+    Ok(format!(
+        "Stored the {} token in {}",
+        service,
+        path.display()
+    ))
+}
+
+/// Removes the token for `service` from the credentials file and the OS
+/// keychain, returning what was removed.
+pub fn logout(service: &str) -> Result<String, String> {
+    check_service(service)?;
+
+    let mut removed: Vec<String> = vec![];
+    if let Some(path) = credentials_path() {
+        let mut tokens = read_file(&path)?;
+        if tokens.remove(service).is_some() {
+            write_file(&path, &tokens)?;
+            removed.push(format!(
+                "Removed the {} token from {}",
+                service,
+                path.display()
+            ));
+        }
+    }
+    if keychain_delete(service) {
+        removed.push(format!(
+            "Removed the {} token from the OS keychain",
+            service
+        ));
+    }
+    if removed.is_empty() {
+        removed.push(format!("No stored {} token", service));
+    }
+
+    // Variables can't be unset for the shell, so they're only pointed out.
+    for var in env_vars(service) {
+        if std::env::var(&var).is_ok_and(|token| !token.is_empty()) {
+            removed.push(format!("${} is still set", var));
+        }
+    }
+    Ok(removed.join("\n"))
+}
+
+/// Where each service's token comes from, without showing the tokens.
+pub fn status() -> String {
+    SERVICES
+        .iter()
+        .map(|service| match lookup(service) {
+            Ok(Some((_, source))) => format!("{}: logged in {}", service, source.describe()),
+            Ok(None) => format!("{}: not logged in", service),
+            Err(e) => format!("{}: {}", service, e),
+        })
+        .collect::<Vec<String>>()
+        .join("\n")
+}
@@ -0,0 +1,192 @@
+use std::path::Path;
+use std::sync::OnceLock;
+
+use regex::Regex;
+
+/// A TODO in a doc comment or docstring, which ends up in rendered docs.
+pub struct DocTodo {
+    pub line_number: u64,
+    pub column: u64,
+    /// How the doc comment starts, like `///` or `"""`.
+    pub marker: &'static str,
+    /// The line from the TODO on.
+    pub text: String,
+    /// Whether it documents a public item or module.
+    pub public: bool,
+}
+
+/// `TODO` as a word of prose, not part of a URL, path, or name like
+/// `TODO_LIST`, `TODO-api`, or `TODO.md`.
+fn todo_pattern() -> &'static Regex {
+    static PATTERN: OnceLock<Regex> = OnceLock::new();
+    PATTERN.get_or_init(|| {
+        Regex::new(r"(?:^|[^\w/.-])(TODO)(?:$|[^\w/.-]|\.(?:\s|$))")
+            .expect("valid doc TODO pattern")
+    })
+}
+
+/// How doc comments starting with `marker` end.
+fn closing(marker: &str) -> &str {
+    match marker {
+        "\"\"\"" | "'''" => marker,
+        _ => "*/",
+    }
+}
+
+fn find_todo(line: &str, line_number: u64, marker: &'static str, public: bool) -> Option<DocTodo> {
+    let found = todo_pattern().captures(line)?.get(1)?;
+    let text = line[found.start()..].trim_end();
+    Some(DocTodo {
+        line_number,
+        column: found.start() as u64 + 1,
+        marker,
+        text: text
+            .strip_suffix(closing(marker))
+            .unwrap_or(text)
+            .trim_end()
+            .to_owned(),
+        public,
+    })
+}
+
+/// Languages with `///` and `/** */` doc comments.
+const C_LIKE_EXTENSIONS: &[&str] = &[
+    "c", "cc", "cpp", "cs", "dart", "h", "hpp", "java", "js", "jsx", "kt", "mjs", "php", "rs",
+    "scala", "swift", "ts", "tsx",
+];
+
+/// Items visible outside their module, by the keyword they start with.
+fn is_public_item(line: &str) -> bool {
+    ["pub ", "public ", "export ", "open "]
+        .iter()
+        .any(|keyword| line.starts_with(keyword))
+}
+
+/// TODOs in `///` and `/** */` comments, which document the item after them,
+/// and in `//!` and `/*! */` comments, which document the module.
+fn c_like_doc_todos(contents: &str) -> Vec<DocTodo> {
+    let mut todos: Vec<DocTodo> = vec![];
+    // TODOs in outer doc comments wait for the item they document.
+    let mut pending: Vec<DocTodo> = vec![];
+    // In a block doc comment, whether it's a module's.
+    let mut block: Option<bool> = None;
+
+    for (index, line) in contents.lines().enumerate() {
+        let line_number = index as u64 + 1;
+        let trimmed = line.trim_start();
+
+        if let Some(inner) = block {
+            let todo = find_todo(line, line_number, if inner { "/*!" } else { "/**" }, inner);
+            if inner {
+                todos.extend(todo);
+            } else {
+                pending.extend(todo);
+            }
+            if line.contains("*/") {
+                block = None;
+            }
+            continue;
+        }
+
+        if trimmed.starts_with("//!") {
+            todos.extend(find_todo(line, line_number, "//!", true));
+        } else if trimmed.starts_with("///") && !trimmed.starts_with("////") {
+            pending.extend(find_todo(line, line_number, "///", false));
+        } else if let Some(rest) = trimmed.strip_prefix("/*!") {
+            todos.extend(find_todo(line, line_number, "/*!", true));
+            block = (!rest.contains("*/")).then_some(true);
+        } else if let Some(rest) = trimmed
+            .strip_prefix("/**")
+            .filter(|rest| !rest.starts_with('/'))
+        {
+            pending.extend(find_todo(line, line_number, "/**", false));
+            block = (!rest.contains("*/")).then_some(false);
+        } else if pending.is_empty()
+            || trimmed.is_empty()
+            || trimmed.starts_with("#[")
+            || trimmed.starts_with('@')
+            || trimmed.starts_with('[')
+        {
+            // Attributes and annotations go between docs and their item.
+            continue;
+        } else {
+            let public = is_public_item(trimmed);
+            todos.extend(pending.drain(..).map(|todo| DocTodo { public, ..todo }));
+        }
+    }
+
+    todos.extend(pending);
+    todos
+}
+
+/// The name after `def`, `async def`, or `class`, if the line starts one.
+fn python_definition(line: &str) -> Option<&str> {
+    let rest = ["def ", "async def ", "class "]
+        .iter()
+        .find_map(|keyword| line.strip_prefix(keyword))?;
+    rest.split(|c: char| !c.is_alphanumeric() && c != '_')
+        .next()
+}
+
+/// TODOs in docstrings, the strings starting a module, function, or class.
+/// Those of functions and classes starting with `_` aren't public.
+fn python_doc_todos(contents: &str) -> Vec<DocTodo> {
+    let mut todos: Vec<DocTodo> = vec![];
+    // The quote of the docstring being read, and whether it's public.
+    let mut docstring: Option<(&'static str, bool)> = None;
+    // Whether the next statement can be a docstring, and whose.
+    let mut expecting: Option<bool> = Some(true);
+    // A definition whose signature continues past this line.
+    let mut signature: Option<bool> = None;
+
+    for (index, line) in contents.lines().enumerate() {
+        let line_number = index as u64 + 1;
+        if let Some((quote, public)) = docstring {
+            todos.extend(find_todo(line, line_number, quote, public));
+            if line.contains(quote) {
+                docstring = None;
+            }
+            continue;
+        }
+
+        let trimmed = line.trim();
+        if trimmed.is_empty() || trimmed.starts_with('#') {
+            continue;
+        }
+
+        let unprefixed = trimmed.trim_start_matches(['r', 'R', 'u', 'U']);
+        let quote = ["\"\"\"", "'''"]
+            .into_iter()
+            .find_map(|quote| Some((quote, unprefixed.strip_prefix(quote)?)));
+        if let (Some(public), Some((quote, rest))) = (expecting, quote) {
+            todos.extend(find_todo(line, line_number, quote, public));
+            if !rest.contains(quote) {
+                docstring = Some((quote, public));
+            }
+            expecting = None;
+            continue;
+        }
+
+        expecting = None;
+        if let Some(name) = python_definition(trimmed) {
+            signature = Some(!name.starts_with('_'));
+        }
+        if let Some(public) = signature {
+            if trimmed.ends_with(':') {
+                expecting = Some(public);
+                signature = None;
+            }
+        }
+    }
+
+    todos
+}
+
+/// TODOs in the file's doc comments or docstrings, if its language has them.
+pub fn doc_todos(path: &Path, contents: &str) -> Vec<DocTodo> {
+    match path.extension().and_then(|e| e.to_str()) {
+        Some("py" | "pyi") => python_doc_todos(contents),
+        Some(extension) if C_LIKE_EXTENSIONS.contains(&extension) => c_like_doc_todos(contents),
+        _ => vec![],
+    }
+}
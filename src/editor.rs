@@ -0,0 +1,86 @@
+use std::io::{BufRead, Write};
+use std::process::Command;
+
+use crate::config::Config;
+use crate::Todo;
+
+/// The command opening the TODO's file at its line, per `editor` in the
+/// config or else `$VISUAL` or `$EDITOR`.
+fn command(config: &Config, todo: &Todo) -> Result<Vec<String>, String> {
+    let path = todo.path.display().to_string();
+    if let Some(template) = &config.editor {
+        if template.trim().is_empty() {
+            return Err("The editor in the config is empty".to_owned());
+        }
+        return Ok(template
+            .split_whitespace()
+            .map(|arg| {
+                arg.replace("{path}", &path)
+                    .replace("{line}", &todo.line_number.to_string())
+                    .replace("{column}", &todo.column.to_string())
+            })
+            .collect());
+    }
+
+    let editor = ["VISUAL", "EDITOR"]
+        .iter()
+        .filter_map(|name| std::env::var(name).ok())
+        .find(|editor| !editor.trim().is_empty())
+        .ok_or("No editor to open TODOs in, set $EDITOR or editor in the config")?;
+
+    let mut args: Vec<String> = editor
+        .split_whitespace()
+        .map(|arg| arg.to_owned())
+        .collect();
+    args.push(format!("+{}", todo.line_number));
+    args.push(path);
+    Ok(args)
+}
+
+/// Opens the TODO in the editor, waiting until it's closed.
+pub fn open(config: &Config, todo: &Todo) -> Result<(), String> {
+    let args = command(config, todo)?;
+    let status = Command::new(&args[0])
+        .args(&args[1..])
+        .status()
+        .map_err(|e| format!("Failed to run {}: {}", args[0], e))?;
+
+    if status.success() {
+        Ok(())
+    } else {
+        Err(format!("{} exited with {}", args[0], status))
+    }
+}
+
+/// Lists the TODOs numbered and asks which one to open, if any.
+pub fn pick<'a>(todos: &[&'a Todo]) -> Option<&'a Todo> {
+    for (index, todo) in todos.iter().enumerate() {
+        println!("{:>3}  {}", index + 1, todo.as_search_result());
+    }
+
+    let stdin = std::io::stdin();
+    let mut input = stdin.lock();
+    loop {
+        print!("Open which TODO, [1-{}] or [q]uit? ", todos.len());
+        let _ = std::io::stdout().flush();
+
+        let mut answer = String::new();
+        match input.read_line(&mut answer) {
+            Ok(0) | Err(_) => {
+                println!();
+                return None;
+            }
+            Ok(_) => {}
+        }
+
+        match answer.trim() {
+            "q" => return None,
+            answer => match answer.parse::<usize>() {
+                Ok(number) if (1..=todos.len()).contains(&number) => {
+                    return Some(todos[number - 1])
+                }
+                _ => continue,
+            },
+        }
+    }
+}
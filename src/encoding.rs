@@ -0,0 +1,70 @@
+use std::path::Path;
+
+use encoding_rs::{Encoding, UTF_16BE, UTF_16LE, UTF_8};
+
+use crate::config::Config;
+use crate::globs;
+
+/// How a file's text is stored on disk, so it can be written back the same way.
+pub struct FileEncoding {
+    encoding: &'static Encoding,
+    bom: bool,
+}
+
+/// The encoding configured for `path` under `[encodings]`, if any.
+pub fn configured_encoding(path: &Path, config: &Config) -> Option<&'static Encoding> {
+    let path = globs::normalize(path);
+
+    config
+        .encoding_globs
+        .iter()
+        .find(|(glob, _)| glob.is_match(path))
+        .map(|&(_, encoding)| encoding)
+}
+
+impl FileEncoding {
+    /// Detects the encoding from a byte order mark, then the config, falling
+    /// back to UTF-8.
+    pub fn detect(bytes: &[u8], path: &Path, config: &Config) -> Self {
+        if let Some((encoding, _)) = Encoding::for_bom(bytes) {
+            return FileEncoding {
+                encoding,
+                bom: true,
+            };
+        }
+
+        FileEncoding {
+            encoding: configured_encoding(path, config).unwrap_or(UTF_8),
+            bom: false,
+        }
+    }
+
+    pub fn decode(&self, bytes: &[u8]) -> String {
+        let (text, _) = self.encoding.decode_with_bom_removal(bytes);
+        text.into_owned()
+    }
+
+    pub fn encode(&self, text: &str) -> Vec<u8> {
+        // encoding_rs only decodes UTF-16, so encode it by hand.
+        let mut bytes: Vec<u8> = vec![];
+        if self.encoding == UTF_16LE {
+            if self.bom {
+                bytes.extend([0xFF, 0xFE]);
+            }
+            bytes.extend(text.encode_utf16().flat_map(|unit| unit.to_le_bytes()));
+        } else if self.encoding == UTF_16BE {
+            if self.bom {
+                bytes.extend([0xFE, 0xFF]);
+            }
+            bytes.extend(text.encode_utf16().flat_map(|unit| unit.to_be_bytes()));
+        } else {
+            if self.bom {
+                bytes.extend([0xEF, 0xBB, 0xBF]);
+            }
+            let (encoded, _, _) = self.encoding.encode(text);
+            bytes.extend(encoded.iter());
+        }
+
+        bytes
+    }
+}
@@ -0,0 +1,323 @@
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+use chrono::{DateTime, NaiveDate};
+
+fn git_output(args: &[&str]) -> Option<String> {
+    let output = Command::new("git").args(args).output().ok()?;
+    if !output.status.success() {
+        return None;
+    }
+
+    String::from_utf8(output.stdout)
+        .ok()
+        .map(|s| s.trim().to_owned())
+}
+
+pub fn repo_root() -> Option<PathBuf> {
+    git_output(&["rev-parse", "--show-toplevel"]).map(PathBuf::from)
+}
+
+pub fn current_branch() -> Option<String> {
+    git_output(&["symbolic-ref", "--short", "HEAD"])
+}
+
+pub fn head_sha() -> Option<String> {
+    git_output(&["rev-parse", "HEAD"])
+}
+
+/// Patches of every commit since `since`, each introduced by a
+/// `commit <YYYY-MM-DD>` line, with no context lines. Renamed files only show
+/// the lines changed, whatever `diff.renames` is set to.
+pub fn log_patches(since: &str, pathspecs: &[String]) -> Option<String> {
+    let since_arg = format!("--since={}", since);
+    let mut args = vec![
+        "log",
+        &since_arg,
+        "--date=short",
+        "--format=commit %ad",
+        "--patch",
+        "--unified=0",
+        "--find-renames",
+        "--no-color",
+        "--no-ext-diff",
+        "--",
+    ];
+    args.extend(pathspecs.iter().map(|s| s.as_str()));
+
+    git_output(&args)
+}
+
+/// The date the line was last changed, per `git blame`.
+pub fn blame_date(path: &Path, line_number: u64) -> Option<NaiveDate> {
+    let range = format!("{},{}", line_number, line_number);
+    let output = git_output(&[
+        "blame",
+        "--porcelain",
+        "-L",
+        &range,
+        "--",
+        &path.to_string_lossy(),
+    ])?;
+
+    let timestamp: i64 = output
+        .lines()
+        .find_map(|line| line.strip_prefix("committer-time "))?
+        .parse()
+        .ok()?;
+
+    DateTime::from_timestamp(timestamp, 0).map(|time| time.date_naive())
+}
+
+/// The date each line of the file was last changed, per `git blame`, keyed
+/// by line number.
+pub fn blame_dates(path: &Path) -> Option<HashMap<u64, NaiveDate>> {
+    let output = git_output(&["blame", "--line-porcelain", "--", &path.to_string_lossy()])?;
+
+    let mut dates: HashMap<u64, NaiveDate> = HashMap::new();
+    let mut line_number: Option<u64> = None;
+    for line in output.lines() {
+        if let Some(timestamp) = line.strip_prefix("committer-time ") {
+            let date = timestamp
+                .parse()
+                .ok()
+                .and_then(|timestamp| DateTime::from_timestamp(timestamp, 0));
+            if let (Some(line_number), Some(date)) = (line_number, date) {
+                dates.insert(line_number, date.date_naive());
+            }
+            continue;
+        }
+
+        // Each line starts with a header of its commit, original, and final line number.
+        let mut fields = line.split(' ');
+        let is_header = fields
+            .next()
+            .is_some_and(|sha| sha.len() == 40 && sha.chars().all(|c| c.is_ascii_hexdigit()));
+        if is_header {
+            line_number = fields.nth(1).and_then(|n| n.parse().ok());
+        }
+    }
+
+    Some(dates)
+}
+
+/// Lines added or changed since `base`, including uncommitted changes, keyed
+/// by canonical path.
+pub fn added_lines(base: &str) -> Option<HashMap<PathBuf, HashSet<u64>>> {
+    let root = repo_root()?;
+    let diff = git_output(&[
+        "diff",
+        "--unified=0",
+        "--no-color",
+        "--no-ext-diff",
+        base,
+        "--",
+    ])?;
+
+    let mut added: HashMap<PathBuf, HashSet<u64>> = HashMap::new();
+    let mut path: Option<PathBuf> = None;
+    for line in diff.lines() {
+        if let Some(file) = line.strip_prefix("+++ ") {
+            path = file
+                .strip_prefix("b/")
+                .and_then(|file| root.join(file).canonicalize().ok());
+        } else if let (Some(hunk), Some(path)) = (line.strip_prefix("@@ "), &path) {
+            // `@@ -12,3 +14,5 @@`, where a missing count means one line.
+            let Some(range) = hunk.split(' ').find_map(|r| r.strip_prefix('+')) else {
+                continue;
+            };
+            let (start, count) = range.split_once(',').unwrap_or((range, "1"));
+            let (Ok(start), Ok(count)) = (start.parse::<u64>(), count.parse::<u64>()) else {
+                continue;
+            };
+            added
+                .entry(path.to_owned())
+                .or_default()
+                .extend(start..start + count);
+        }
+    }
+
+    Some(added)
+}
+
+/// The lines at `reference` containing any of the keywords in any case, as
+/// path, line number, and text, found by `git grep`.
+pub fn grep_lines(
+    reference: &str,
+    keywords: &[&str],
+    pathspecs: &[String],
+) -> Option<Vec<(PathBuf, u64, String)>> {
+    let mut args = vec!["grep", "-z", "-n", "-I", "-i", "-F", "--no-color"];
+    for keyword in keywords {
+        args.extend(["-e", keyword]);
+    }
+    args.extend([reference, "--"]);
+    args.extend(pathspecs.iter().map(|s| s.as_str()));
+
+    // `git grep` exits with 1 when nothing matches.
+    let output = Command::new("git").args(&args).output().ok()?;
+    if !output.status.success() && output.status.code() != Some(1) {
+        return None;
+    }
+
+    let prefix = format!("{}:", reference);
+    Some(
+        String::from_utf8_lossy(&output.stdout)
+            .lines()
+            .filter_map(|line| {
+                let mut fields = line.strip_prefix(&prefix)?.splitn(3, '\0');
+                let path = PathBuf::from(fields.next()?);
+                let line_number = fields.next()?.parse().ok()?;
+                Some((path, line_number, fields.next()?.to_owned()))
+            })
+            .collect(),
+    )
+}
+
+pub struct Author {
+    pub name: String,
+    pub email: String,
+}
+
+/// The configured `user.name` and `user.email`, who changes not yet
+/// committed are by.
+pub fn user_identity() -> Option<Author> {
+    Some(Author {
+        name: git_output(&["config", "user.name"])?,
+        email: git_output(&["config", "user.email"]).unwrap_or_default(),
+    })
+}
+
+/// Who last changed the line, per `git blame`, or the current user if the
+/// change isn't committed yet.
+pub fn blame_author(path: &Path, line_number: u64) -> Option<Author> {
+    let range = format!("{},{}", line_number, line_number);
+    let output = git_output(&[
+        "blame",
+        "--porcelain",
+        "-L",
+        &range,
+        "--",
+        &path.to_string_lossy(),
+    ])?;
+
+    let field = |name: &str| {
+        output
+            .lines()
+            .find_map(|line| line.strip_prefix(name))
+            .map(|value| value.to_owned())
+    };
+    let email = field("author-mail ")?
+        .trim_matches(|c| c == '<' || c == '>')
+        .to_owned();
+    if email == "not.committed.yet" {
+        return user_identity();
+    }
+
+    Some(Author {
+        name: field("author ")?,
+        email,
+    })
+}
+
+/// The files git tracks under `pathspec`, relative to the working directory,
+/// and with `untracked`, the untracked files git doesn't ignore.
+pub fn ls_files(pathspec: &str, untracked: bool) -> Result<Vec<PathBuf>, String> {
+    let mut command = Command::new("git");
+    command.args(["ls-files", "-z", "--cached"]);
+    if untracked {
+        command.args(["--others", "--exclude-standard"]);
+    }
+    let output = command
+        .args(["--", pathspec])
+        .output()
+        .map_err(|e| format!("Failed to run git: {}", e))?;
+    if !output.status.success() {
+        return Err(format!(
+            "git ls-files failed: {}",
+            String::from_utf8_lossy(&output.stderr).trim()
+        ));
+    }
+
+    Ok(String::from_utf8_lossy(&output.stdout)
+        .split('\0')
+        .filter(|path| !path.is_empty())
+        .map(PathBuf::from)
+        .collect())
+}
+
+/// Everyone who authored a commit in the last `days` days, with names and
+/// emails mapped through `.mailmap`.
+pub fn recent_authors(days: u32) -> Option<Vec<Author>> {
+    let since = format!("--since={}.days", days);
+    let output = git_output(&["log", &since, "--format=%aN%x00%aE"])?;
+
+    let unique: HashSet<&str> = output.lines().collect();
+    Some(
+        unique
+            .into_iter()
+            .filter_map(|line| {
+                let (name, email) = line.split_once('\0')?;
+                Some(Author {
+                    name: name.to_owned(),
+                    email: email.to_owned(),
+                })
+            })
+            .collect(),
+    )
+}
+
+/// The browsable https URL of the origin remote, e.g.
+/// `git@github.com:org/repo.git` becomes `https://github.com/org/repo`.
+pub fn remote_web_url() -> Option<String> {
+    let url = git_output(&["remote", "get-url", "origin"])?;
+    let url = url.strip_suffix(".git").unwrap_or(&url);
+
+    if let Some(rest) = url.strip_prefix("git@") {
+        let (host, repo) = rest.split_once(':')?;
+        Some(format!("https://{}/{}", host, repo))
+    } else if let Some(rest) = url.strip_prefix("ssh://git@") {
+        Some(format!("https://{}", rest))
+    } else if url.starts_with("https://") || url.starts_with("http://") {
+        Some(url.to_owned())
+    } else {
+        None
+    }
+}
+
+pub struct PermalinkBase {
+    url: String,
+    sha: String,
+    root: PathBuf,
+}
+
+impl PermalinkBase {
+    /// Links to `repo_url` when given, otherwise to the origin remote, at the
+    /// commit checked out.
+    pub fn detect(repo_url: Option<&str>) -> Option<Self> {
+        let url = match repo_url {
+            Some(url) => url.trim_end_matches('/').to_owned(),
+            None => remote_web_url()?,
+        };
+
+        Some(PermalinkBase {
+            url,
+            sha: head_sha()?,
+            root: repo_root()?.canonicalize().ok()?,
+        })
+    }
+
+    pub fn link(&self, path: &Path, line_number: u64) -> Option<String> {
+        let absolute = path.canonicalize().ok()?;
+        let relative = absolute.strip_prefix(&self.root).ok()?;
+
+        Some(format!(
+            "{}/blob/{}/{}#L{}",
+            self.url,
+            self.sha,
+            relative.to_string_lossy(),
+            line_number
+        ))
+    }
+}
@@ -0,0 +1,117 @@
+use std::collections::BTreeMap;
+use std::path::Path;
+
+use globset::{GlobBuilder, GlobSet, GlobSetBuilder};
+use ignore::types::{Types, TypesBuilder};
+
+/// Builds a glob matching like gitignore patterns, where `*` stays within a
+/// directory and `**` crosses them.
+pub fn build_glob_set<'a>(patterns: impl Iterator<Item = &'a str>) -> Result<GlobSet, String> {
+    let mut builder = GlobSetBuilder::new();
+    for pattern in patterns {
+        let glob = GlobBuilder::new(pattern)
+            .literal_separator(true)
+            .build()
+            .map_err(|e| format!("Invalid glob \"{}\": {}", pattern, e))?;
+        builder.add(glob);
+    }
+
+    builder.build().map_err(|e| format!("{}", e))
+}
+
+/// Paths are matched without a leading `./`, so `src/**` matches `./src/a.rs`.
+pub fn normalize(path: &Path) -> &Path {
+    path.strip_prefix("./").unwrap_or(path)
+}
+
+/// Selects paths matching any of the included globs and none of the excluded
+/// (`!`-prefixed) ones. With only exclusions, everything else is included.
+pub struct PathFilter {
+    include: GlobSet,
+    exclude: GlobSet,
+}
+
+impl PathFilter {
+    pub fn new(patterns: &[String]) -> Result<Self, String> {
+        let include = build_glob_set(
+            patterns
+                .iter()
+                .filter(|p| !p.starts_with('!'))
+                .map(|p| p.as_str()),
+        )?;
+        let exclude = build_glob_set(patterns.iter().filter_map(|p| p.strip_prefix('!')))?;
+
+        Ok(PathFilter { include, exclude })
+    }
+
+    pub fn is_match(&self, path: &Path) -> bool {
+        let path = normalize(path);
+        (self.include.is_empty() || self.include.is_match(path)) && !self.exclude.is_match(path)
+    }
+}
+
+/// Selects files of the `--type` names, from ripgrep's built-in extension
+/// map plus any `--type-add` definitions like `proto:*.proto`.
+pub struct FileTypes {
+    types: Types,
+}
+
+impl FileTypes {
+    pub fn new(selected: &[String], definitions: &[String]) -> Result<Self, String> {
+        let mut builder = TypesBuilder::new();
+        builder.add_defaults();
+        for definition in definitions {
+            builder
+                .add_def(definition)
+                .map_err(|e| format!("Invalid --type-add \"{}\": {}", definition, e))?;
+        }
+
+        for name in selected {
+            if !builder.definitions().iter().any(|def| def.name() == name) {
+                return Err(format!(
+                    "Unknown --type \"{}\", define it with --type-add \"{}:*.ext\"",
+                    name, name
+                ));
+            }
+            builder.select(name);
+        }
+
+        let types = builder.build().map_err(|e| format!("{}", e))?;
+        Ok(FileTypes { types })
+    }
+
+    pub fn is_match(&self, path: &Path) -> bool {
+        !self.types.matched(path, false).is_ignore()
+    }
+}
+
+/// Default assignees of paths, from `[owners]` in the config. When several
+/// globs match, the longest, most specific one wins.
+pub struct OwnerRules {
+    globs: GlobSet,
+    owners: Vec<(String, String)>,
+}
+
+impl OwnerRules {
+    pub fn new(owners: &BTreeMap<String, String>) -> Result<Self, String> {
+        let globs = build_glob_set(owners.keys().map(|pattern| pattern.as_str()))?;
+        let owners = owners
+            .iter()
+            .map(|(pattern, owner)| {
+                let owner = owner.strip_prefix('@').unwrap_or(owner);
+                (pattern.to_owned(), owner.to_owned())
+            })
+            .collect();
+
+        Ok(OwnerRules { globs, owners })
+    }
+
+    pub fn owner(&self, path: &Path) -> Option<&str> {
+        self.globs
+            .matches(normalize(path))
+            .into_iter()
+            .map(|index| &self.owners[index])
+            .max_by_key(|(pattern, _)| pattern.len())
+            .map(|(_, owner)| owner.as_str())
+    }
+}
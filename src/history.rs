@@ -0,0 +1,271 @@
+use std::collections::{BTreeMap, HashMap};
+use std::path::PathBuf;
+
+use chrono::{Duration, NaiveDate};
+use grep::regex::RegexMatcher;
+
+use crate::config::Config;
+use crate::globs::{FileTypes, PathFilter};
+use crate::{containers, git, parse_todo_line, todo_matches, Todo, TodoFilters};
+
+#[derive(Default)]
+pub struct DailyChange {
+    pub added: usize,
+    pub removed: usize,
+}
+
+/// Parses periods like `90d`, `12w`, `3m`, or `1y`, where a month is 30 days
+/// and a year 365.
+pub fn parse_period(period: &str) -> Option<Duration> {
+    let (split, unit) = period.char_indices().last()?;
+    let count: i64 = period[..split].parse().ok()?;
+
+    let days = match unit {
+        'd' => count,
+        'w' => count.checked_mul(7)?,
+        'm' => count.checked_mul(30)?,
+        'y' => count.checked_mul(365)?,
+        _ => return None,
+    };
+    Duration::try_days(days)
+}
+
+/// The TODOs one commit added and removed, by their text.
+#[derive(Default)]
+struct CommitChanges {
+    added: Vec<String>,
+    removed: Vec<String>,
+}
+
+impl CommitChanges {
+    /// Adds the commit's changes to `change`, leaving out TODOs that were
+    /// removed in one place and added unchanged in another, as when moving
+    /// code between files.
+    fn count_into(mut self, change: &mut DailyChange) {
+        let mut added = 0;
+        for todo in self.added {
+            match self.removed.iter().position(|removed| *removed == todo) {
+                Some(index) => {
+                    self.removed.swap_remove(index);
+                }
+                None => added += 1,
+            }
+        }
+
+        change.added += added;
+        change.removed += self.removed.len();
+    }
+}
+
+/// Counts the TODOs matching `filters` added and removed per day by the
+/// commits since `since`. Paths are relative to the repository root. TODOs
+/// in renamed files, or moved unchanged to another file, count as neither.
+pub fn todo_changes(
+    matcher: &RegexMatcher,
+    config: &Config,
+    since: NaiveDate,
+    pathspecs: &[String],
+    path_filter: Option<&PathFilter>,
+    file_types: Option<&FileTypes>,
+    filters: &TodoFilters,
+) -> Result<BTreeMap<NaiveDate, DailyChange>, String> {
+    let log = git::log_patches(&since.to_string(), pathspecs)
+        .ok_or_else(|| "Failed to read git history".to_owned())?;
+
+    let mut changes: BTreeMap<NaiveDate, DailyChange> = BTreeMap::new();
+    let mut date: Option<NaiveDate> = None;
+    let mut commit = CommitChanges::default();
+    let mut path = PathBuf::new();
+
+    for line in log.lines() {
+        if let Some(commit_date) = line.strip_prefix("commit ") {
+            if let Some(date) = date {
+                std::mem::take(&mut commit).count_into(changes.entry(date).or_default());
+            }
+            date = NaiveDate::parse_from_str(commit_date, "%Y-%m-%d").ok();
+            continue;
+        }
+
+        if let Some(old_path) = line.strip_prefix("--- a/") {
+            path = PathBuf::from(old_path);
+            continue;
+        }
+
+        if let Some(new_path) = line.strip_prefix("+++ b/") {
+            path = PathBuf::from(new_path);
+            continue;
+        }
+
+        if line.starts_with("--- ") || line.starts_with("+++ ") {
+            continue;
+        }
+
+        if date.is_none() {
+            continue;
+        }
+
+        let (content, is_addition) = if let Some(added) = line.strip_prefix('+') {
+            (added, true)
+        } else if let Some(removed) = line.strip_prefix('-') {
+            (removed, false)
+        } else {
+            continue;
+        };
+
+        if path_filter.is_some_and(|path_filter| !path_filter.is_match(&path))
+            || file_types.is_some_and(|file_types| !file_types.is_match(&path))
+        {
+            continue;
+        }
+
+        let todo = if containers::is_markdown(&path) {
+            containers::parse_markdown_line(matcher, config, &path, 0, content)
+        } else {
+            parse_todo_line(matcher, config, &path, 0, content)
+        };
+        let is_match = todo
+            .map(|todo| todo_matches(&todo, filters))
+            .unwrap_or(false);
+        if !is_match {
+            continue;
+        }
+
+        let text = content.trim().to_owned();
+        if is_addition {
+            commit.added.push(text);
+        } else {
+            commit.removed.push(text);
+        }
+    }
+
+    if let Some(date) = date {
+        commit.count_into(changes.entry(date).or_default());
+    }
+
+    Ok(changes)
+}
+
+const CHART_WIDTH: usize = 40;
+
+/// Renders the weekly backlog size since `since`, reconstructed backwards
+/// from the `current` count, along with a projection of when it hits zero.
+pub fn burndown_chart(
+    current: usize,
+    changes: &BTreeMap<NaiveDate, DailyChange>,
+    since: NaiveDate,
+    today: NaiveDate,
+) -> String {
+    let net_after = |date: NaiveDate| -> i64 {
+        changes
+            .range(date.succ_opt().unwrap_or(date)..)
+            .map(|(_, change)| change.added as i64 - change.removed as i64)
+            .sum()
+    };
+
+    let mut points: Vec<(NaiveDate, i64)> = vec![];
+    let mut date = since;
+    while date < today {
+        points.push((date, current as i64 - net_after(date)));
+        date += Duration::weeks(1);
+    }
+    points.push((today, current as i64));
+
+    let max = points.iter().map(|(_, count)| *count).max().unwrap_or(0);
+    let rows: Vec<String> = points
+        .iter()
+        .map(|(date, count)| {
+            let width = if max > 0 {
+                (*count).max(0) as usize * CHART_WIDTH / max as usize
+            } else {
+                0
+            };
+            format!("{} {} {}", date, "█".repeat(width), count)
+        })
+        .collect();
+
+    let added: usize = changes.values().map(|change| change.added).sum();
+    let removed: usize = changes.values().map(|change| change.removed).sum();
+    let days = (today - since).num_days().max(1);
+    let rate = (added as f64 - removed as f64) / days as f64;
+
+    let projection = if rate < 0.0 {
+        let days_left = (current as f64 / -rate).ceil() as i64;
+        format!(
+            "At this rate the backlog reaches zero around {}.",
+            today + Duration::days(days_left)
+        )
+    } else if rate > 0.0 {
+        format!("The backlog is growing by {:.2} TODOs a day.", rate)
+    } else {
+        "The backlog is holding steady.".to_owned()
+    };
+
+    format!(
+        "{}\n\nAdded {}, removed {} since {} ({:+.2} a day).\n{}",
+        rows.join("\n"),
+        added,
+        removed,
+        since,
+        rate,
+        projection
+    )
+}
+
+const AGE_BUCKETS: [(&str, i64); 5] = [
+    ("<1w", 7),
+    ("1w-1m", 30),
+    ("1-3m", 90),
+    ("3-12m", 365),
+    (">1y", i64::MAX),
+];
+
+/// Renders how many TODOs were last touched in each age bucket, per
+/// `git blame`. TODOs outside of git history are counted as unknown.
+pub fn age_histogram(todos: &[Todo], today: NaiveDate) -> String {
+    let mut blames: HashMap<PathBuf, Option<HashMap<u64, NaiveDate>>> = HashMap::new();
+    let mut counts = [0usize; AGE_BUCKETS.len()];
+    let mut unknown = 0;
+
+    for todo in todos {
+        // Notebook line numbers are relative to the cell, not the file.
+        let date = if todo.cell.is_some() {
+            None
+        } else {
+            blames
+                .entry(todo.path.clone())
+                .or_insert_with(|| git::blame_dates(&todo.path))
+                .as_ref()
+                .and_then(|dates| dates.get(&todo.line_number))
+        };
+
+        match date {
+            Some(date) => {
+                let age = (today - *date).num_days();
+                let bucket = AGE_BUCKETS
+                    .iter()
+                    .position(|(_, max_days)| age < *max_days)
+                    .unwrap_or(AGE_BUCKETS.len() - 1);
+                counts[bucket] += 1;
+            }
+            None => unknown += 1,
+        }
+    }
+
+    let mut rows: Vec<(&str, usize)> = AGE_BUCKETS
+        .iter()
+        .zip(counts)
+        .map(|((label, _), count)| (*label, count))
+        .collect();
+    if unknown > 0 {
+        rows.push(("unknown", unknown));
+    }
+
+    let max = rows.iter().map(|(_, count)| *count).max().unwrap_or(0);
+    rows.iter()
+        .map(|(label, count)| {
+            let width = (count * CHART_WIDTH).checked_div(max).unwrap_or(0);
+            format!("{:<7} {} {}", label, "█".repeat(width), count)
+        })
+        .collect::<Vec<String>>()
+        .join("\n")
+}
@@ -0,0 +1,296 @@
+use std::cell::Cell;
+use std::io::Write;
+use std::process::{Command, Stdio};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use serde::Deserialize;
+use serde_json::Value;
+
+use crate::VERSION;
+
+const MAX_BACKOFF: Duration = Duration::from_secs(30);
+
+/// Settings shared by every integration that talks to an issue tracker.
+#[derive(Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct HttpConfig {
+    /// How many times a request failing with a network error, 429, or 5xx is
+    /// retried before giving up.
+    pub retries: u32,
+
+    /// At most this many requests are made per minute, if set.
+    pub requests_per_minute: Option<u32>,
+
+    /// Proxy URL, e.g. `http://proxy:3128`. Without it curl's own
+    /// `HTTPS_PROXY` and `NO_PROXY` handling applies.
+    pub proxy: Option<String>,
+
+    pub timeout_secs: u64,
+}
+
+impl Default for HttpConfig {
+    fn default() -> Self {
+        HttpConfig {
+            retries: 3,
+            requests_per_minute: None,
+            proxy: None,
+            timeout_secs: 30,
+        }
+    }
+}
+
+pub struct Response {
+    pub status: u16,
+    pub body: String,
+    retry_after: Option<Duration>,
+}
+
+impl Response {
+    pub fn is_success(&self) -> bool {
+        (200..300).contains(&self.status)
+    }
+
+    pub fn json(&self) -> Result<Value, String> {
+        serde_json::from_str(&self.body).map_err(|e| format!("Invalid JSON response: {}", e))
+    }
+
+    fn is_transient(&self) -> bool {
+        self.status == 429 || self.status >= 500
+    }
+}
+
+/// A request that got no response.
+struct Failure {
+    message: String,
+    /// Whether curl reached the server, so the request may have been sent.
+    connected: bool,
+}
+
+/// curl's exit codes for failing to resolve the proxy or host, or to
+/// connect, before any of the request was sent.
+const NOT_CONNECTED_EXIT_CODES: [i32; 3] = [5, 6, 7];
+
+/// How a token is sent, which differs between trackers.
+pub enum Auth {
+    /// `Authorization: Bearer <token>`, for GitHub and GitLab tokens.
+    Bearer(String),
+
+    /// HTTP Basic auth with a `user:password` token, like Jira Cloud's
+    /// `email:api_token`.
+    Basic(String),
+
+    /// `Authorization: <token>`, for Linear's personal API keys.
+    Raw(String),
+}
+
+/// Makes requests through `curl`, retrying transient failures with
+/// exponential backoff and spacing requests to respect the rate limit.
+pub struct HttpClient<'a> {
+    config: &'a HttpConfig,
+    auth: Option<Auth>,
+    last_request: Cell<Option<Instant>>,
+}
+
+/// Quotes a value for curl's config file syntax.
+fn curl_quote(value: &str) -> String {
+    let escaped = value
+        .replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace('\n', "\\n")
+        .replace('\r', "\\r")
+        .replace('\t', "\\t");
+    format!("\"{}\"", escaped)
+}
+
+/// Splits curl's `--dump-header -` output into the final response's status,
+/// headers, and body. Redirects and `100 Continue` add header blocks first.
+fn parse_curl_output(output: &str) -> Option<Response> {
+    let mut rest = output;
+    let mut status = None;
+    let mut retry_after = None;
+
+    while rest.starts_with("HTTP/") {
+        let (head, body) = rest
+            .split_once("\r\n\r\n")
+            .or_else(|| rest.split_once("\n\n"))
+            .unwrap_or((rest, ""));
+
+        let mut lines = head.lines();
+        status = lines
+            .next()
+            .and_then(|line| line.split_whitespace().nth(1))
+            .and_then(|code| code.parse::<u16>().ok());
+        retry_after = lines
+            .filter_map(|line| line.split_once(':'))
+            .find(|(name, _)| name.trim().eq_ignore_ascii_case("retry-after"))
+            .and_then(|(_, value)| value.trim().parse::<u64>().ok())
+            .map(Duration::from_secs);
+
+        rest = body;
+    }
+
+    Some(Response {
+        status: status?,
+        body: rest.to_owned(),
+        retry_after,
+    })
+}
+
+impl<'a> HttpClient<'a> {
+    pub fn new(config: &'a HttpConfig, auth: Option<Auth>) -> Self {
+        HttpClient {
+            config,
+            auth,
+            last_request: Cell::new(None),
+        }
+    }
+
+    pub fn get(&self, url: &str) -> Result<Response, String> {
+        self.request("GET", url, None)
+    }
+
+    pub fn post(&self, url: &str, body: &Value) -> Result<Response, String> {
+        self.request("POST", url, Some(body))
+    }
+
+    pub fn patch(&self, url: &str, body: &Value) -> Result<Response, String> {
+        self.request("PATCH", url, Some(body))
+    }
+
+    pub fn request(
+        &self,
+        method: &str,
+        url: &str,
+        body: Option<&Value>,
+    ) -> Result<Response, String> {
+        // A POST may have been committed even when its response was lost,
+        // so it's only retried when the server surely didn't act on it.
+        let idempotent = method != "POST";
+        let mut attempt = 0;
+        loop {
+            let result = self.send(method, url, body);
+            let (retryable, retry_after) = match &result {
+                Ok(response) if response.status == 429 => (true, response.retry_after),
+                Ok(response) if response.is_transient() => (idempotent, response.retry_after),
+                Ok(_) => (false, None),
+                Err(failure) => (idempotent || !failure.connected, None),
+            };
+
+            if !retryable || attempt >= self.config.retries {
+                return result.map_err(|failure| failure.message);
+            }
+
+            let backoff = 2u32
+                .checked_pow(attempt)
+                .and_then(|factor| Duration::from_millis(500).checked_mul(factor))
+                .unwrap_or(MAX_BACKOFF);
+            thread::sleep(retry_after.unwrap_or(backoff).min(MAX_BACKOFF));
+            attempt += 1;
+        }
+    }
+
+    fn wait_for_rate_limit(&self) {
+        let Some(per_minute) = self.config.requests_per_minute.filter(|n| *n > 0) else {
+            return;
+        };
+
+        let interval = Duration::from_secs(60) / per_minute;
+        if let Some(last_request) = self.last_request.get() {
+            let elapsed = last_request.elapsed();
+            if elapsed < interval {
+                thread::sleep(interval - elapsed);
+            }
+        }
+
+        self.last_request.set(Some(Instant::now()));
+    }
+
+    fn send(&self, method: &str, url: &str, body: Option<&Value>) -> Result<Response, Failure> {
+        self.wait_for_rate_limit();
+
+        // Options go through stdin so the token never shows up in `ps`.
+        let mut options = vec![
+            format!("url = {}", curl_quote(url)),
+            format!("request = {}", curl_quote(method)),
+            format!("max-time = {}", self.config.timeout_secs),
+            format!("user-agent = {}", curl_quote(&format!("codo/{}", VERSION))),
+            format!("header = {}", curl_quote("Accept: application/json")),
+        ];
+        match &self.auth {
+            Some(Auth::Bearer(token)) => options.push(format!(
+                "header = {}",
+                curl_quote(&format!("Authorization: Bearer {}", token))
+            )),
+            Some(Auth::Basic(credentials)) => {
+                options.push("basic".to_owned());
+                options.push(format!("user = {}", curl_quote(credentials)));
+            }
+            Some(Auth::Raw(token)) => options.push(format!(
+                "header = {}",
+                curl_quote(&format!("Authorization: {}", token))
+            )),
+            None => {}
+        }
+        if let Some(proxy) = &self.config.proxy {
+            options.push(format!("proxy = {}", curl_quote(proxy)));
+        }
+        if let Some(body) = body {
+            options.push(format!(
+                "header = {}",
+                curl_quote("Content-Type: application/json")
+            ));
+            options.push(format!("data-binary = {}", curl_quote(&body.to_string())));
+        }
+
+        // Until curl has its options, nothing was sent.
+        let not_run = |e: std::io::Error| Failure {
+            message: format!("Failed to run curl: {}", e),
+            connected: false,
+        };
+        let mut child = Command::new("curl")
+            .args([
+                "--silent",
+                "--show-error",
+                "--location",
+                "--dump-header",
+                "-",
+            ])
+            .args(["--config", "-"])
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .spawn()
+            .map_err(not_run)?;
+
+        if let Some(mut stdin) = child.stdin.take() {
+            stdin
+                .write_all(options.join("\n").as_bytes())
+                .map_err(not_run)?;
+        }
+
+        let output = child.wait_with_output().map_err(|e| Failure {
+            message: format!("Failed to run curl: {}", e),
+            connected: true,
+        })?;
+        if !output.status.success() {
+            return Err(Failure {
+                message: format!(
+                    "{} {} failed: {}",
+                    method,
+                    url,
+                    String::from_utf8_lossy(&output.stderr).trim()
+                ),
+                connected: !output
+                    .status
+                    .code()
+                    .is_some_and(|code| NOT_CONNECTED_EXIT_CODES.contains(&code)),
+            });
+        }
+
+        parse_curl_output(&String::from_utf8_lossy(&output.stdout)).ok_or_else(|| Failure {
+            message: format!("{} {} failed: malformed response", method, url),
+            connected: true,
+        })
+    }
+}
@@ -0,0 +1,189 @@
+use std::path::{Component, Path, PathBuf};
+
+use ignore::gitignore::{Gitignore, GitignoreBuilder};
+use ignore::Match;
+
+use crate::{ScanOptions, CODOIGNORE_FILENAME};
+
+/// The rule in an ignore file that matched a path.
+struct IgnoreRule {
+    file: PathBuf,
+    pattern: String,
+    whitelist: bool,
+}
+
+/// Resolves `.` and `..` without touching the file system, so paths are
+/// shown the way they were given.
+fn normalize(path: &Path) -> PathBuf {
+    let mut normalized = PathBuf::new();
+    for component in path.components() {
+        match component {
+            Component::CurDir => {}
+            Component::ParentDir => {
+                normalized.pop();
+            }
+            component => normalized.push(component),
+        }
+    }
+    normalized
+}
+
+fn display(path: &Path, cwd: &Path) -> String {
+    path.strip_prefix(cwd).unwrap_or(path).display().to_string()
+}
+
+fn matched(gitignore: &Gitignore, path: &Path, is_dir: bool) -> Option<IgnoreRule> {
+    match gitignore.matched(path, is_dir) {
+        Match::None => None,
+        Match::Ignore(glob) | Match::Whitelist(glob) => Some(IgnoreRule {
+            file: glob
+                .from()
+                .map(|from| from.to_path_buf())
+                .unwrap_or_default(),
+            pattern: glob.original().to_owned(),
+            whitelist: glob.is_whitelist(),
+        }),
+    }
+}
+
+/// The rule deciding whether the walk skips `path`, in the order the walk
+/// applies them: `.codoignore`, `.ignore`, and in git repositories
+/// `.gitignore`, `.git/info/exclude`, and the global gitignore. Deeper
+/// ignore files take precedence over ones further up.
+fn rule_for(path: &Path, is_dir: bool, options: &ScanOptions) -> Option<IgnoreRule> {
+    let ancestors: Vec<&Path> = path.ancestors().skip(1).collect();
+    for name in [CODOIGNORE_FILENAME, ".ignore"] {
+        for dir in &ancestors {
+            let (gitignore, _) = Gitignore::new(dir.join(name));
+            if let Some(rule) = matched(&gitignore, path, is_dir) {
+                return Some(rule);
+            }
+        }
+    }
+
+    let repo = ancestors.iter().position(|dir| dir.join(".git").exists())?;
+    for dir in &ancestors[..=repo] {
+        let (gitignore, _) = Gitignore::new(dir.join(".gitignore"));
+        if let Some(rule) = matched(&gitignore, path, is_dir) {
+            return Some(rule);
+        }
+    }
+
+    let repo = ancestors[repo];
+    if options.git_exclude {
+        let mut builder = GitignoreBuilder::new(repo);
+        builder.add(repo.join(".git/info/exclude"));
+        if let Some(rule) = builder.build().ok().and_then(|g| matched(&g, path, is_dir)) {
+            return Some(rule);
+        }
+    }
+    if options.git_global {
+        let (gitignore, _) = GitignoreBuilder::new(repo).build_global();
+        return matched(&gitignore, path, is_dir);
+    }
+    None
+}
+
+/// Why the scan does or doesn't include `path`, for `--debug-ignores`.
+pub fn explain(path: &str, options: &ScanOptions) -> String {
+    let cwd = std::env::current_dir().unwrap_or_default();
+    let target = normalize(&cwd.join(path));
+    if !target.exists() {
+        return format!("{} doesn't exist", path);
+    }
+
+    let root = options
+        .roots
+        .iter()
+        .map(|root| normalize(&cwd.join(&root.path)))
+        .filter(|root| target.starts_with(root))
+        .max_by_key(|root| root.components().count());
+    let Some(root) = root else {
+        return format!("{} is skipped: it's outside every --path", path);
+    };
+    if target == root {
+        return format!("{} is scanned: it's given with --path", path);
+    }
+
+    // Checked from the top down like the walk, which doesn't enter ignored
+    // directories.
+    let mut current = root.clone();
+    let mut unignored: Option<(String, IgnoreRule)> = None;
+    for component in target.strip_prefix(&root).unwrap_or(&target).components() {
+        current.push(component);
+        let shown = display(&current, &cwd);
+        match rule_for(&current, current.is_dir(), options) {
+            Some(rule) if rule.whitelist => unignored = Some((shown, rule)),
+            Some(rule) => {
+                return format!(
+                    "{} is skipped: {} matches `{}` in {}",
+                    path,
+                    shown,
+                    rule.pattern,
+                    display(&rule.file, &cwd)
+                )
+            }
+            None if component.as_os_str().to_string_lossy().starts_with('.') => {
+                return format!("{} is skipped: {} is hidden", path, shown)
+            }
+            None => {}
+        }
+    }
+
+    if let Some(path_filter) = &options.path_filter {
+        if target.is_file() && !path_filter.is_match(Path::new(path)) {
+            return format!("{} is skipped: it doesn't match --path-glob", path);
+        }
+    }
+    if let Some(file_types) = &options.file_types {
+        if target.is_file() && !file_types.is_match(Path::new(path)) {
+            return format!("{} is skipped: it isn't of any --type", path);
+        }
+    }
+
+    match unignored {
+        Some((shown, rule)) => format!(
+            "{} is scanned: {} matches `{}` in {}",
+            path,
+            shown,
+            rule.pattern,
+            display(&rule.file, &cwd)
+        ),
+        None => format!("{} is scanned", path),
+    }
+}
+
+/// The `.codoignore` and `.ignore` files among `paths`, deepest first, so
+/// files listed by git can be left out like the walk does.
+pub fn custom_ignores(paths: &[PathBuf]) -> Vec<Gitignore> {
+    let mut ignore_files: Vec<&PathBuf> = paths
+        .iter()
+        .filter(|path| {
+            path.file_name()
+                .is_some_and(|name| name == CODOIGNORE_FILENAME || name == ".ignore")
+        })
+        .collect();
+    // `.codoignore` files take precedence over `.ignore` files next to them.
+    ignore_files.sort_by_key(|path| {
+        (
+            std::cmp::Reverse(path.components().count()),
+            path.file_name()
+                .is_some_and(|name| name != CODOIGNORE_FILENAME),
+        )
+    });
+
+    ignore_files
+        .into_iter()
+        .map(|path| Gitignore::new(path).0)
+        .collect()
+}
+
+/// Whether the deepest of `ignores` with a rule for `path` excludes it.
+pub fn is_custom_ignored(ignores: &[Gitignore], path: &Path) -> bool {
+    ignores
+        .iter()
+        .filter(|gitignore| path.starts_with(gitignore.path()))
+        .map(|gitignore| gitignore.matched_path_or_any_parents(path, false))
+        .find(|matched| !matched.is_none())
+        .is_some_and(|matched| matched.is_ignore())
+}
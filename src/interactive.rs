@@ -0,0 +1,89 @@
+use std::io::{BufRead, Write};
+use std::path::Path;
+
+/// A line a code mod rewrites, or deletes when `new` is `None`. Wrapped TODOs
+/// change as a whole, so `old` and `new` can both span lines.
+pub struct LineChange {
+    pub location: String,
+    pub old: String,
+    pub new: Option<String>,
+}
+
+impl LineChange {
+    /// The location, then the line before and after, like a diff.
+    pub fn as_diff(&self) -> String {
+        let mut lines = vec![self.location.clone()];
+        for line in self.old.lines() {
+            lines.push(format!("- {}", line.trim_start()));
+        }
+        for line in self.new.iter().flat_map(|new| new.lines()) {
+            lines.push(format!("+ {}", line.trim_start()));
+        }
+        lines.join("\n")
+    }
+}
+
+/// Asks before each file `mod --interactive` rewrites, like
+/// `git add --patch`.
+#[derive(Default)]
+pub struct FilePrompt {
+    apply_all: bool,
+    quit: bool,
+    /// Files left as they were.
+    pub skipped: usize,
+}
+
+impl FilePrompt {
+    /// Shows the changes to `path` and whether they should be written.
+    pub fn confirm(&mut self, path: &Path, changes: &[LineChange]) -> bool {
+        let confirmed = self.ask(path, changes);
+        if !confirmed {
+            self.skipped += 1;
+        }
+        confirmed
+    }
+
+    fn ask(&mut self, path: &Path, changes: &[LineChange]) -> bool {
+        if self.quit {
+            return false;
+        }
+        if self.apply_all || changes.is_empty() {
+            return true;
+        }
+
+        for change in changes {
+            println!("\n{}", change.as_diff());
+        }
+
+        let stdin = std::io::stdin();
+        let mut input = stdin.lock();
+        loop {
+            print!("Apply to {}, [y]es, [n]o, [q]uit, [a]ll? ", path.display());
+            let _ = std::io::stdout().flush();
+
+            let mut answer = String::new();
+            match input.read_line(&mut answer) {
+                Ok(0) | Err(_) => {
+                    println!();
+                    self.quit = true;
+                    return false;
+                }
+                Ok(_) => {}
+            }
+
+            match answer.trim() {
+                "y" => return true,
+                "n" => return false,
+                "q" => {
+                    self.quit = true;
+                    return false;
+                }
+                "a" => {
+                    self.apply_all = true;
+                    return true;
+                }
+                _ => continue,
+            }
+        }
+    }
+}
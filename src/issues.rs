@@ -0,0 +1,159 @@
+use std::collections::BTreeMap;
+
+use serde_json::{json, Value};
+
+use crate::{count_noun, Grouping, Todo};
+
+pub enum IssuesFormat {
+    Text,
+    Json,
+    Markdown,
+}
+
+impl IssuesFormat {
+    pub fn from_str(s: &str) -> Option<Self> {
+        match s {
+            "text" => Some(IssuesFormat::Text),
+            "json" => Some(IssuesFormat::Json),
+            "markdown" => Some(IssuesFormat::Markdown),
+            _ => None,
+        }
+    }
+}
+
+/// The lines of one file, or notebook cell, citing an issue.
+pub struct Lines {
+    pub path: String,
+    pub cell: Option<usize>,
+    pub line_numbers: Vec<u64>,
+}
+
+impl Lines {
+    /// `path:3, 10`, or `path[1]:3, 10` in notebooks.
+    fn as_text(&self) -> String {
+        let lines: Vec<String> = self.line_numbers.iter().map(u64::to_string).collect();
+        match self.cell {
+            Some(cell) => format!("{}[{}]:{}", self.path, cell, lines.join(", ")),
+            None => format!("{}:{}", self.path, lines.join(", ")),
+        }
+    }
+}
+
+/// The line numbers citing an issue per file and notebook cell.
+type LinesByFile = BTreeMap<(String, Option<usize>), Vec<u64>>;
+
+/// An issue and the TODOs citing it.
+pub struct CitedIssue {
+    pub issue: String,
+    pub count: usize,
+    pub locations: Vec<Lines>,
+}
+
+/// The issues cited by `todos`, the most cited first, with their TODOs'
+/// locations collapsed per file.
+pub fn cited_issues(todos: &[Todo]) -> Vec<CitedIssue> {
+    let mut by_issue: BTreeMap<String, LinesByFile> = BTreeMap::new();
+    for todo in todos {
+        let Some(issue) = &todo.metadata.issue else {
+            continue;
+        };
+        by_issue
+            .entry(issue.as_display_string())
+            .or_default()
+            .entry((todo.path.display().to_string(), todo.cell))
+            .or_default()
+            .push(todo.line_number);
+    }
+
+    let mut issues: Vec<CitedIssue> = by_issue
+        .into_iter()
+        .map(|(issue, files)| CitedIssue {
+            issue,
+            count: files.values().map(Vec::len).sum(),
+            locations: files
+                .into_iter()
+                .map(|((path, cell), mut line_numbers)| {
+                    line_numbers.sort();
+                    Lines {
+                        path,
+                        cell,
+                        line_numbers,
+                    }
+                })
+                .collect(),
+        })
+        .collect();
+    issues.sort_by(|a, b| {
+        b.count
+            .cmp(&a.count)
+            .then_with(|| Grouping::Issue.compare_keys(&a.issue, &b.issue))
+    });
+    issues
+}
+
+pub fn as_text(issues: &[CitedIssue]) -> String {
+    issues
+        .iter()
+        .map(|cited| {
+            let locations: Vec<String> = cited
+                .locations
+                .iter()
+                .map(|lines| format!("  {}", lines.as_text()))
+                .collect();
+            format!(
+                "{}: {}\n{}",
+                cited.issue,
+                count_noun(cited.count, "TODO"),
+                locations.join("\n")
+            )
+        })
+        .collect::<Vec<String>>()
+        .join("\n")
+}
+
+pub fn as_markdown(issues: &[CitedIssue]) -> String {
+    let rows: Vec<String> = issues
+        .iter()
+        .map(|cited| {
+            let locations: Vec<String> = cited
+                .locations
+                .iter()
+                .map(|lines| format!("`{}`", lines.as_text()))
+                .collect();
+            format!(
+                "| {} | {} | {} |",
+                cited.issue,
+                cited.count,
+                locations.join("<br>")
+            )
+        })
+        .collect();
+
+    format!(
+        "| Issue | TODOs | Locations |\n| --- | --- | --- |\n{}",
+        rows.join("\n")
+    )
+}
+
+pub fn as_json(issues: &[CitedIssue]) -> Value {
+    Value::Array(
+        issues
+            .iter()
+            .map(|cited| {
+                json!({
+                    "issue": cited.issue,
+                    "count": cited.count,
+                    "locations": cited
+                        .locations
+                        .iter()
+                        .map(|lines| json!({
+                            "path": lines.path,
+                            "cell": lines.cell,
+                            "lines": lines.line_numbers,
+                        }))
+                        .collect::<Vec<Value>>(),
+                })
+            })
+            .collect(),
+    )
+}
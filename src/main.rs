@@ -1,24 +1,87 @@
-use std::borrow::BorrowMut;
-use std::collections::HashMap;
-use std::fs::File;
-use std::io::{BufRead, BufReader, Write};
-use std::path::PathBuf;
-
-use chrono::{Local, NaiveDate};
-use grep::matcher::{Captures, Matcher};
-use grep::regex::RegexMatcher;
+use std::cmp::{Ordering, Reverse};
+use std::collections::hash_map::RandomState;
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::fs::{self, File};
+use std::hash::BuildHasher;
+use std::io::{BufRead, BufReader, Read, Write};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering as AtomicOrdering};
+use std::sync::{Mutex, OnceLock};
+use std::time::Instant;
+
+use chrono::{Datelike, Duration, Local, NaiveDate, Weekday};
+use grep::matcher::{Captures, LineTerminator, Matcher};
+use grep::regex::{RegexMatcher, RegexMatcherBuilder};
 use grep::searcher::sinks::UTF8;
-use grep::searcher::Searcher;
+use grep::searcher::{BinaryDetection, Searcher, SearcherBuilder};
 use regex::Regex;
 
+use config::{
+    Config, LongLines, MetaOrder, PreserveOrder, ScopedRules, TodoStyle, DEFAULT_CONFIG_PATH,
+};
+use encoding::{configured_encoding, FileEncoding};
+use globs::{FileTypes, OwnerRules, PathFilter};
+use issues::IssuesFormat;
+use markers::Severity;
+use schema::SchemaFormat;
+use serde::Deserialize;
+
+mod agenda;
+mod aggregate;
+mod apply;
+mod archive;
+mod batch;
+mod budget;
+mod calendar;
+mod complete;
+mod config;
+mod containers;
+mod credentials;
+mod docs;
+mod editor;
+mod encoding;
+mod git;
+mod globs;
+mod history;
+// Shared by the issue tracker integrations.
+mod http;
+mod ignores;
+mod interactive;
+mod issues;
+mod markers;
+mod metrics;
+mod patch;
+mod review;
+mod roster;
+mod rules;
+mod schema;
+mod serve;
+mod snapshot;
+mod stats;
+mod summary;
+mod sync;
+mod tracker;
+mod tree;
+mod watch;
+
 struct Todo {
+    /// The label of the `--path` the TODO was found under.
+    root: Option<String>,
     raw: String,
     delimiter: String,
+    /// The keyword of the TODO's marker as the config writes it, like `TODO`.
+    marker: String,
     path: PathBuf,
     line_number: u64,
+    column: u64,
+    cell: Option<usize>,
     note: String,
     meta: Option<String>,
     metadata: TodoMetadata,
+    /// The due date of the cited issue from the config, if any.
+    inherited_due: Option<String>,
+    /// The file has a generated code marker near the top.
+    generated: bool,
 }
 
 fn get_printable_note(delimiter: &String, note: &String) -> String {
@@ -33,55 +96,273 @@ fn get_printable_note(delimiter: &String, note: &String) -> String {
     }
 }
 
+const DELIMITERS: [&str; 3] = ["//", "/*", "#"];
+
+/// The comment delimiters a TODO can be written with in files with the given
+/// extension.
+fn supported_delimiters(extension: &str) -> &'static [&'static str] {
+    match extension {
+        "c" | "h" | "cc" | "cpp" | "cxx" | "hpp" | "hh" | "cs" | "java" | "kt" | "kts" | "go"
+        | "rs" | "js" | "jsx" | "mjs" | "cjs" | "ts" | "tsx" | "swift" | "scala" | "dart"
+        | "scss" | "less" | "vue" | "svelte" => &["//", "/*"],
+        "css" => &["/*"],
+        "php" => &["//", "/*", "#"],
+        "py" | "rb" | "sh" | "bash" | "zsh" | "yml" | "yaml" | "toml" | "pl" | "r" | "ipynb" => {
+            &["#"]
+        }
+        _ => &[],
+    }
+}
+
+/// The note rewritten for another delimiter, or `None` for block comments
+/// spanning several lines, which can't be converted line by line.
+fn change_note_delimiter(delimiter: &String, note: &String, to: &str) -> Option<String> {
+    if delimiter == "/*" && !note.ends_with("*/") {
+        return None;
+    }
+
+    let note = get_printable_note(delimiter, note).trim_end().to_owned();
+    if to == "/*" {
+        Some(format!("{} */", note))
+    } else {
+        Some(note)
+    }
+}
+
+fn format_location(path: &Path, cell: Option<usize>, line_number: u64) -> String {
+    match cell {
+        Some(cell) => format!("{}[{}]:{}", path.display(), cell, line_number),
+        None => format!("{}:{}", path.display(), line_number),
+    }
+}
+
 impl Todo {
+    /// The TODO's own due date, or otherwise the one it inherits from its
+    /// issue.
+    fn due(&self) -> &Option<String> {
+        if self.metadata.due.is_some() {
+            &self.metadata.due
+        } else {
+            &self.inherited_due
+        }
+    }
+
+    fn location(&self) -> String {
+        format_location(&self.path, self.cell, self.line_number)
+    }
+
+    /// Everything the parser extracted, with the path relative to `base`.
+    fn as_parse_description(&self, base: &Path) -> String {
+        let path = self.path.strip_prefix(base).unwrap_or(&self.path);
+
+        format!(
+            "{}:{} delimiter={:?} meta={:?} issue={:?} blocked_by={:?} assignee={:?} due={:?} priority={:?} every={:?} snooze={:?} id={:?} extra={:?} note={:?}",
+            format_location(path, self.cell, self.line_number),
+            self.column,
+            self.delimiter,
+            self.meta,
+            self.metadata.issue.as_ref().map(|i| i.as_string()),
+            self.metadata.blocked_by.as_ref().map(|i| i.as_string()),
+            self.metadata.assignee(),
+            self.metadata.due,
+            self.metadata.priority,
+            self.metadata.every,
+            self.metadata.snooze,
+            self.metadata.id,
+            self.metadata.extra,
+            self.note
+        )
+    }
+
+    fn as_json(&self) -> Value {
+        let mut json = serde_json::json!({
+            "path": self.path.to_str(),
+            "line": self.line_number,
+            "cell": self.cell,
+            "note": get_printable_note(&self.delimiter, &self.note),
+            "issue": self.metadata.issue.as_ref().map(|f| f.as_string()),
+            "blocked_by": self.metadata.blocked_by.as_ref().map(|f| f.as_string()),
+            "assignee": self.metadata.assignee(),
+            "assignees": self.metadata.assignees,
+            "due": self.metadata.due,
+            "priority": self.metadata.priority,
+            "every": self.metadata.every,
+            "snooze": self.metadata.snooze,
+            "id": self.metadata.id,
+            "extra": self.metadata.extra,
+            "root": self.root,
+        });
+        if self.marker != markers::TODO {
+            json["marker"] = Value::String(self.marker.clone());
+        }
+        if self.generated {
+            json["generated"] = Value::Bool(true);
+        }
+        json
+    }
+
+    /// Whether the TODO has parentheses with nothing but whitespace and
+    /// commas in them, like `TODO():`.
+    fn has_empty_metadata(&self) -> bool {
+        self.meta
+            .as_deref()
+            .is_some_and(|meta| meta.split(',').all(|token| token.trim().is_empty()))
+    }
+
+    /// Where the TODO is in its file, which for notebook cells is the line of
+    /// the notebook's JSON.
+    fn file_position(&self) -> (u64, u64) {
+        self.cell
+            .and_then(|cell| {
+                containers::notebook_file_position(&self.path, cell, self.line_number, self.column)
+            })
+            .unwrap_or((self.line_number, self.column))
+    }
+
+    /// Matches ripgrep's `--vimgrep` output, `path:line:column:text`, with the
+    /// note as the text. Notebook TODOs are located in the file, which vim's
+    /// `errorformat` can parse.
+    fn as_vimgrep_result(&self) -> String {
+        let (line, column) = self.file_position();
+        format!(
+            "{}:{}:{}:{}",
+            self.path.display(),
+            line,
+            column,
+            get_printable_note(&self.delimiter, &self.note).trim_end()
+        )
+    }
+
     fn as_search_result(&self) -> String {
+        match &self.root {
+            Some(root) => format!("{}: {}", root, self.as_unlabeled_search_result()),
+            None => self.as_unlabeled_search_result(),
+        }
+    }
+
+    fn as_unlabeled_search_result(&self) -> String {
+        format!("{} {}", self.location(), self.summary())
+    }
+
+    /// The metadata and note, as shown after the location in search results.
+    fn summary(&self) -> String {
         let note: String = get_printable_note(&self.delimiter, &self.note);
 
         match self.meta.to_owned() {
             Some(meta) => {
-                let metadata = TodoMetadata::from_string(meta.clone());
+                let metadata = self.metadata.clone();
 
                 let mut info: Vec<String> = vec![];
                 if let Some(issue) = metadata.issue {
-                    info.push(format!("{}", issue.as_string()))
+                    info.push(issue.as_display_string())
                 }
 
-                if let Some(assignee) = metadata.assignee {
-                    info.push(format!("@{}", assignee))
+                if let Some(blocker) = metadata.blocked_by {
+                    info.push(format!("blocked-by:{}", blocker.as_display_string()))
                 }
 
+                info.extend(metadata.assignees.iter().map(|a| format!("@{}", a)));
+
                 if let Some(due) = metadata.due {
                     info.push(format!("due:{}", due))
+                } else if let Some(invalid_due) = metadata.invalid_due {
+                    info.push(invalid_due)
+                }
+
+                if let Some(priority) = metadata.priority {
+                    info.push(format!("p{}", priority))
+                }
+
+                if let Some(every) = metadata.every {
+                    info.push(format!("every:{}", every))
+                }
+
+                if let Some(snooze) = metadata.snooze {
+                    info.push(format!("snooze:{}", snooze))
+                }
+
+                if let Some(id) = metadata.id {
+                    info.push(format!("id:{}", id))
                 }
 
+                info.extend(metadata.extra);
+
                 let meta_part = if info.is_empty() {
                     meta
                 } else {
                     info.join(", ")
                 };
 
-                format!(
-                    "{}:{} [{}] {}",
-                    self.path.display(),
-                    self.line_number,
-                    meta_part,
-                    note
-                )
-            }
-            None => {
-                format!("{}:{} {}", self.path.display(), self.line_number, note)
+                format!("[{}] {}", meta_part, note)
             }
+            None => note,
         }
     }
 }
 
 #[derive(Clone)]
 struct TodoMetadata {
-    assignee: Option<String>,
+    /// Everyone the TODO is assigned to, like `alice` and `bob` in
+    /// `TODO(@alice, @bob)`, in the order written.
+    assignees: Vec<String>,
     issue: Option<Issue>,
+    /// Another issue the TODO waits on, like `ABC-99` in
+    /// `blocked-by:ABC-99`.
+    blocked_by: Option<Issue>,
     due: Option<String>,
+    /// A due date token that isn't a date, like `2025-02-30` or `due:frday`,
+    /// as written. The TODO has no due date then.
+    invalid_due: Option<String>,
+    /// From `p0`, the highest, to `p9`.
+    priority: Option<u8>,
+    every: Option<String>,
+    /// Keeps the TODO out of overdue and due soon lists until this date.
+    snooze: Option<String>,
+    /// A stable identifier like `td_8f3a`, which survives moves and edits.
+    id: Option<String>,
+    /// Tokens that aren't recognized, kept so rewriting doesn't drop them.
+    extra: Vec<String>,
+    /// The tokens in the order written, so edits can keep them as they were.
+    tokens: Vec<MetaToken>,
+}
+
+/// A metadata token as written, with what it was recognized as.
+#[derive(Clone)]
+struct MetaToken {
+    kind: MetaKind,
+    text: String,
+    /// The token written canonically, like `2025-01-05` for
+    /// `due:2025-01-05`, to tell whether its value changed since.
+    canonical: String,
+}
+
+#[derive(Clone, Copy, PartialEq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+enum MetaKind {
+    Issue,
+    #[serde(rename = "blocked-by")]
+    BlockedBy,
+    Assignee,
+    Due,
+    Priority,
+    Every,
+    Snooze,
+    Id,
+    Extra,
 }
 
+const DEFAULT_META_ORDER: [MetaKind; 9] = [
+    MetaKind::Issue,
+    MetaKind::BlockedBy,
+    MetaKind::Assignee,
+    MetaKind::Due,
+    MetaKind::Priority,
+    MetaKind::Every,
+    MetaKind::Snooze,
+    MetaKind::Id,
+    MetaKind::Extra,
+];
+
 enum IssueFormat {
     Numbered,
     ProjectKey,
@@ -97,10 +378,30 @@ impl IssueFormat {
     }
 }
 
+#[derive(Clone, Copy, PartialEq)]
+enum IssueProvider {
+    GitHub,
+    GitLab,
+    Jira,
+    Linear,
+    Other,
+}
+
 #[derive(Clone)]
 enum Issue {
     Numbered(String),
-    ProjectKey { project_key: String, number: String },
+    ProjectKey {
+        project_key: String,
+        number: String,
+    },
+    /// A link to the issue, written back exactly as found.
+    Url {
+        url: String,
+        provider: IssueProvider,
+        /// Short form like `org/repo#42` or `PROJ-123`, if the provider is
+        /// recognized.
+        reference: Option<String>,
+    },
 }
 
 impl Issue {
@@ -111,13 +412,126 @@ impl Issue {
                 project_key,
                 number,
             } => format!("{}-{}", project_key, number),
+            Issue::Url { url, .. } => url.to_owned(),
         }
     }
+
+    /// How the issue is shown in reports, with URLs shortened where possible.
+    fn as_display_string(&self) -> String {
+        match self {
+            Issue::Url {
+                reference: Some(reference),
+                ..
+            } => reference.to_owned(),
+            _ => self.as_string(),
+        }
+    }
+
+    fn is_numbered(&self) -> bool {
+        match self {
+            Issue::Numbered(_) => true,
+            Issue::Url { provider, .. } => {
+                matches!(provider, IssueProvider::GitHub | IssueProvider::GitLab)
+            }
+            Issue::ProjectKey { .. } => false,
+        }
+    }
+
+    /// The issue's number and what it's numbered within, like `PROJ-` for
+    /// `PROJ-12` or `org/repo#` for `org/repo#42`.
+    fn numbered_within(&self) -> Option<(String, u64)> {
+        let reference = self.as_display_string();
+        let split = reference.rfind(['#', '-'])? + 1;
+        let number = reference[split..].parse().ok()?;
+        Some((reference[..split].to_owned(), number))
+    }
+
+    /// How the issue's number compares to the other's, if they're numbered
+    /// within the same project or repository.
+    fn compare_number(&self, other: &Issue) -> Option<Ordering> {
+        let (within, number) = self.numbered_within()?;
+        let (other_within, other_number) = other.numbered_within()?;
+        (within == other_within).then(|| number.cmp(&other_number))
+    }
+
+    fn project_key(&self) -> Option<String> {
+        match self {
+            Issue::ProjectKey { project_key, .. } => Some(project_key.to_owned()),
+            Issue::Url {
+                provider: IssueProvider::Jira | IssueProvider::Linear,
+                reference: Some(reference),
+                ..
+            } => reference.split_once('-').map(|(key, _)| key.to_owned()),
+            _ => None,
+        }
+    }
+}
+
+/// Recognizes issue links of common trackers, e.g.
+/// `https://github.com/org/repo/issues/42` shortened to `org/repo#42`.
+fn parse_issue_url(url: &str) -> Option<Issue> {
+    let patterns = [
+        (
+            IssueProvider::GitHub,
+            r"^https?://github\.com/([^/]+/[^/]+)/(?:issues|pull)/([[:digit:]]+)/?$",
+        ),
+        (
+            IssueProvider::GitLab,
+            r"^https?://gitlab\.com/(.+?)/-/(?:issues|merge_requests)/([[:digit:]]+)/?$",
+        ),
+        (
+            IssueProvider::Jira,
+            r"^https?://[^/]+/browse/([A-Z][A-Z_0-9]*-[[:digit:]]+)/?$",
+        ),
+        (
+            IssueProvider::Linear,
+            r"^https?://linear\.app/[^/]+/issue/([A-Z][A-Z_0-9]*-[[:digit:]]+)(?:/[^/]*)?$",
+        ),
+    ];
+
+    if !(url.starts_with("https://") || url.starts_with("http://")) || url.contains(' ') {
+        return None;
+    }
+
+    for (provider, pattern) in patterns {
+        let captures = Regex::new(pattern).ok()?.captures(url);
+        if let Some(captures) = captures {
+            let reference = match (captures.get(1), captures.get(2)) {
+                (Some(repo), Some(number)) => format!("{}#{}", repo.as_str(), number.as_str()),
+                (Some(key), None) => key.as_str().to_owned(),
+                _ => continue,
+            };
+
+            return Some(Issue::Url {
+                url: url.to_owned(),
+                provider,
+                reference: Some(reference),
+            });
+        }
+    }
+
+    Some(Issue::Url {
+        url: url.to_owned(),
+        provider: IssueProvider::Other,
+        reference: None,
+    })
+}
+
+/// `p0` to `p9`, in either case.
+fn parse_priority(str: &str) -> Option<u8> {
+    match str.as_bytes() {
+        [b'p' | b'P', digit @ b'0'..=b'9'] => Some(digit - b'0'),
+        _ => None,
+    }
 }
 
 fn parse_issue(str: &str) -> Option<Issue> {
+    if str.contains("://") {
+        return parse_issue_url(str);
+    }
+
     if let Ok(numbered) = Regex::new(r"^#[[:digit:]]+$") {
-        if numbered.is_match(&str) {
+        if numbered.is_match(str) {
             return Some(Issue::Numbered(str.to_owned()));
         }
     }
@@ -135,61 +549,638 @@ fn parse_issue(str: &str) -> Option<Issue> {
     None
 }
 
+/// Issues the parser doesn't recognize because of a lowercase project key or
+/// stray spaces, like `abc-123` or `# 42`.
+fn parse_malformed_issue(token: &str) -> Option<Issue> {
+    if token.contains("://") {
+        return None;
+    }
+
+    let compact: String = token.chars().filter(|c| !c.is_whitespace()).collect();
+    let normalized = match compact.split_once('-') {
+        Some((key, number)) => format!("{}-{}", key.to_uppercase(), number),
+        None => compact,
+    };
+
+    if normalized == token {
+        None
+    } else {
+        parse_issue(&normalized)
+    }
+}
+
+/// Issues as `--issue` gives them, written like TODOs' issues are shown, so
+/// `proj-12` matches `PROJ-12` and a GitHub link matches `org/repo#42`.
+fn normalize_issues(issues: &[String]) -> Vec<String> {
+    issues
+        .iter()
+        .map(|issue| {
+            parse_issue(issue)
+                .or_else(|| parse_malformed_issue(issue))
+                .map_or(issue.to_owned(), |issue| issue.as_display_string())
+        })
+        .collect()
+}
+
+/// The issue given to a flag like `--issue-gt`, which must have a number.
+fn parse_issue_bound(flag: &str, value: &str) -> Issue {
+    parse_issue(value)
+        .or_else(|| parse_malformed_issue(value))
+        .filter(|issue| issue.numbered_within().is_some())
+        .unwrap_or_else(|| cli_error(format!("{}={} isn't a numbered issue", flag, value)))
+}
+
+/// Whether the unrecognized token is metadata on its own, which means it
+/// repeats a kind the TODO has already, like the same assignee again or a
+/// second date.
+fn is_duplicate_token(token: &str, config: &Config) -> bool {
+    TodoMetadata::from_string(token.to_owned(), config)
+        .tokens
+        .first()
+        .is_some_and(|token| token.kind != MetaKind::Extra)
+}
+
+fn is_valid_assignee(name: &str) -> bool {
+    !name.is_empty() && !name.starts_with('@') && !name.contains(char::is_whitespace)
+}
+
+/// Finds a project key issue in a branch name, e.g. `feature/ABC-123-foo`.
+/// Keys must be uppercase, and ones that are really encodings or versions,
+/// like `fix/UTF-8` or `release/V2-10`, aren't taken for issues.
+fn issue_from_branch_name(branch: &str) -> Option<Issue> {
+    let key = Regex::new(r"(?:^|[^[:alnum:]])([A-Z][A-Z_0-9]+)-([[:digit:]]+)").ok()?;
+    let not_issue = Regex::new(r"^(?:UTF|UCS|ISO|CP|V[0-9]+)$").ok()?;
+    key.captures_iter(branch)
+        .map(|captures| captures.extract())
+        .find(|(_, [project_key, _])| !not_issue.is_match(project_key))
+        .and_then(|(_, [project_key, number])| parse_issue(&format!("{}-{}", project_key, number)))
+}
+
+/// Reads a due date written as ISO 8601 or in one of the configured
+/// `date_formats`, which are normalized to ISO.
+fn parse_due_str(part: &str, config: &Config) -> Option<String> {
+    let iso_format = Regex::new(r"^[0-9]{4}-[0-9]{2}-[0-9]{2}$").unwrap();
+    if iso_format.is_match(part) {
+        return parse_due_date(part.to_owned()).map(|_| part.to_owned());
+    }
+
+    config
+        .date_formats
+        .iter()
+        .find_map(|format| NaiveDate::parse_from_str(part, format).ok())
+        .map(|date| date.format("%Y-%m-%d").to_string())
+}
+
+/// Whether the token looks like an ISO date, valid or not, like `2025-02-30`
+/// or `2025-2-3`.
+fn is_date_shaped(part: &str) -> bool {
+    Regex::new(r"^[0-9]{4}-[0-9]{1,2}-[0-9]{1,2}$")
+        .unwrap()
+        .is_match(part)
+}
+
+/// Resolves `fri` to the coming Friday (today on Fridays) and `next-fri` to
+/// the Friday a week after that. Since they're resolved against today on
+/// every run, they're never overdue until `format` writes the date in.
+fn parse_weekday_due(value: &str, today: NaiveDate, config: &Config) -> Option<String> {
+    let (weeks, name) = match value.strip_prefix("next-") {
+        Some(name) => (1, name),
+        None => (0, value),
+    };
+    let weekday: Weekday = config.weekday_name(name).unwrap_or(name).parse().ok()?;
+
+    let days_ahead =
+        (weekday.num_days_from_monday() + 7 - today.weekday().num_days_from_monday()) % 7;
+    let date = today + Duration::days(days_ahead as i64 + 7 * weeks);
+    Some(date.format("%Y-%m-%d").to_string())
+}
+
 impl TodoMetadata {
     fn empty() -> Self {
         TodoMetadata {
-            assignee: None,
+            assignees: vec![],
             issue: None,
+            blocked_by: None,
             due: None,
+            invalid_due: None,
+            priority: None,
+            every: None,
+            snooze: None,
+            id: None,
+            extra: vec![],
+            tokens: vec![],
         }
     }
 
-    fn from_string(str: String) -> Self {
-        let date_format = Regex::new(r"[0-9]{4}-[0-9]{2}-[0-9]{2}").unwrap();
-
-        let mut assignee: Option<String> = None;
+    fn from_string(str: String, config: &Config) -> Self {
+        let mut assignees: Vec<String> = vec![];
         let mut issue: Option<Issue> = None;
+        let mut blocked_by: Option<Issue> = None;
         let mut due: Option<String> = None;
+        let mut invalid_due: Option<String> = None;
+        let mut priority: Option<u8> = None;
+        let mut every: Option<String> = None;
+        let mut snooze: Option<String> = None;
+        let mut id: Option<String> = None;
+        let mut extra: Vec<String> = vec![];
+        let mut order: Vec<(MetaKind, &str)> = vec![];
 
         let parts: Vec<&str> = str.trim().split(',').map(|s| s.trim()).collect();
         for part in parts {
-            if part.starts_with('@') && assignee == None {
-                assignee = Some(part[1..].to_string());
+            if part.is_empty() {
                 continue;
             }
 
-            issue = issue.or_else(|| parse_issue(part));
-
-            if date_format.is_match(part) && due == None {
-                due = Some(part.to_string())
+            if let Some(name) = part.strip_prefix('@') {
+                if is_valid_assignee(name) && !assignees.iter().any(|a| a == name) {
+                    assignees.push(name.to_string());
+                    order.push((MetaKind::Assignee, part));
+                    continue;
+                }
+            } else if let Some(period) = part.strip_prefix("every:") {
+                if every.is_none() {
+                    every = Some(period.to_string());
+                    order.push((MetaKind::Every, part));
+                    continue;
+                }
+            } else if let Some(value) = part.strip_prefix("snooze:") {
+                if let Some(parsed) = parse_due_str(value, config).filter(|_| snooze.is_none()) {
+                    snooze = Some(parsed);
+                    order.push((MetaKind::Snooze, part));
+                    continue;
+                }
+            } else if let Some(value) = part.strip_prefix("due:") {
+                let parsed = parse_due_str(value, config)
+                    .or_else(|| parse_weekday_due(value, Local::now().date_naive(), config));
+                if due.is_none() && invalid_due.is_none() {
+                    match parsed {
+                        Some(parsed) => due = Some(parsed),
+                        None => invalid_due = Some(part.to_owned()),
+                    }
+                    order.push((MetaKind::Due, part));
+                    continue;
+                }
+            } else if let Some(value) = part.strip_prefix("blocked-by:") {
+                if let Some(parsed) = parse_issue(value).filter(|_| blocked_by.is_none()) {
+                    blocked_by = Some(parsed);
+                    order.push((MetaKind::BlockedBy, part));
+                    continue;
+                }
+            } else if let Some(value) = part.strip_prefix("id:") {
+                if id.is_none() && !value.is_empty() {
+                    id = Some(value.to_string());
+                    order.push((MetaKind::Id, part));
+                    continue;
+                }
+            } else if let Some(parsed) = parse_priority(part).filter(|_| priority.is_none()) {
+                priority = Some(parsed);
+                order.push((MetaKind::Priority, part));
+                continue;
+            } else if let Some(parsed) = parse_issue(part).filter(|_| issue.is_none()) {
+                issue = Some(parsed);
+                order.push((MetaKind::Issue, part));
+                continue;
+            } else if let Some(parsed) = parse_due_str(part, config).filter(|_| due.is_none()) {
+                due = Some(parsed);
+                order.push((MetaKind::Due, part));
+                continue;
+            } else if is_date_shaped(part) && due.is_none() && invalid_due.is_none() {
+                invalid_due = Some(part.to_owned());
+                order.push((MetaKind::Due, part));
+                continue;
             }
+
+            order.push((MetaKind::Extra, part));
+            extra.push(part.to_string());
         }
 
-        TodoMetadata {
-            assignee,
+        let mut metadata = TodoMetadata {
+            assignees,
             issue,
+            blocked_by,
             due,
+            invalid_due,
+            priority,
+            every,
+            snooze,
+            id,
+            extra,
+            tokens: vec![],
+        };
+        metadata.tokens = order
+            .into_iter()
+            .map(|(kind, text)| MetaToken {
+                kind,
+                text: text.to_owned(),
+                canonical: match kind {
+                    MetaKind::Assignee => text.to_owned(),
+                    kind => metadata.token_text(kind).unwrap_or(text.to_owned()),
+                },
+            })
+            .collect();
+        metadata
+    }
+
+    /// The first assignee, for reports and checks that take one owner.
+    fn assignee(&self) -> Option<&String> {
+        self.assignees.first()
+    }
+
+    /// How a recognized kind of metadata is written, if present. Several
+    /// assignees are written as one, like `@alice, @bob`.
+    fn token_text(&self, kind: MetaKind) -> Option<String> {
+        match kind {
+            MetaKind::Issue => self.issue.as_ref().map(|issue| issue.as_string()),
+            MetaKind::BlockedBy => self
+                .blocked_by
+                .as_ref()
+                .map(|issue| format!("blocked-by:{}", issue.as_string())),
+            MetaKind::Assignee => (!self.assignees.is_empty()).then(|| {
+                self.assignees
+                    .iter()
+                    .map(|a| format!("@{}", a))
+                    .collect::<Vec<String>>()
+                    .join(", ")
+            }),
+            MetaKind::Due => self.due.clone().or(self.invalid_due.clone()),
+            MetaKind::Priority => self.priority.map(|p| format!("p{}", p)),
+            MetaKind::Every => self.every.as_ref().map(|every| format!("every:{}", every)),
+            MetaKind::Snooze => self.snooze.as_ref().map(|date| format!("snooze:{}", date)),
+            MetaKind::Id => self.id.as_ref().map(|id| format!("id:{}", id)),
+            MetaKind::Extra => None,
+        }
+    }
+
+    /// The tokens in the order written, and values without a token where
+    /// `preferred` puts them among those. With `keep_text`, tokens whose
+    /// values didn't change are kept exactly as written, otherwise all are
+    /// written canonically.
+    fn written_parts(&self, keep_text: bool, preferred: &[MetaKind]) -> Vec<String> {
+        let mut parts: Vec<(MetaKind, String)> = vec![];
+        let mut extra = self.extra.clone();
+        for token in &self.tokens {
+            if token.kind == MetaKind::Extra {
+                if let Some(index) = extra.iter().position(|e| *e == token.text) {
+                    parts.push((MetaKind::Extra, extra.remove(index)));
+                }
+            } else if token.kind == MetaKind::Assignee {
+                if !parts.iter().any(|(kind, _)| *kind == MetaKind::Assignee) {
+                    parts.extend(
+                        self.assignee_parts(keep_text)
+                            .into_iter()
+                            .map(|text| (MetaKind::Assignee, text)),
+                    );
+                }
+            } else if !parts.iter().any(|(kind, _)| *kind == token.kind) {
+                if let Some(text) = self.token_text(token.kind) {
+                    let unchanged = keep_text && text == token.canonical;
+                    parts.push((
+                        token.kind,
+                        if unchanged { token.text.clone() } else { text },
+                    ));
+                }
+            }
+        }
+
+        let rank = |kind: MetaKind| {
+            preferred
+                .iter()
+                .chain(DEFAULT_META_ORDER.iter())
+                .position(|k| *k == kind)
+        };
+        for kind in DEFAULT_META_ORDER {
+            if parts.iter().any(|(k, _)| *k == kind) {
+                continue;
+            }
+            if let Some(text) = self.token_text(kind) {
+                let index = parts
+                    .iter()
+                    .position(|(k, _)| rank(*k) > rank(kind))
+                    .unwrap_or(parts.len());
+                parts.insert(index, (kind, text));
+            }
+        }
+        parts.extend(extra.into_iter().map(|e| (MetaKind::Extra, e)));
+
+        parts.into_iter().map(|(_, text)| text).collect()
+    }
+
+    /// Each assignee's token, as written if `keep_text` and it was.
+    fn assignee_parts(&self, keep_text: bool) -> Vec<String> {
+        self.assignees
+            .iter()
+            .map(|assignee| {
+                let text = format!("@{}", assignee);
+                self.tokens
+                    .iter()
+                    .find(|token| {
+                        keep_text && token.kind == MetaKind::Assignee && token.canonical == text
+                    })
+                    .map_or(text.clone(), |token| token.text.clone())
+            })
+            .collect()
+    }
+
+    fn has(&self, kind: MetaKind) -> bool {
+        match kind {
+            MetaKind::Issue => self.issue.is_some(),
+            MetaKind::BlockedBy => self.blocked_by.is_some(),
+            MetaKind::Assignee => !self.assignees.is_empty(),
+            MetaKind::Due => self.due.is_some() || self.invalid_due.is_some(),
+            MetaKind::Priority => self.priority.is_some(),
+            MetaKind::Every => self.every.is_some(),
+            MetaKind::Snooze => self.snooze.is_some(),
+            MetaKind::Id => self.id.is_some(),
+            MetaKind::Extra => !self.extra.is_empty(),
+        }
+    }
+
+    /// The kinds of metadata present, in the order they should be written.
+    /// Kinds missing from the preferred order go last, in the default order.
+    fn ordered_kinds(&self, preferred: &[MetaKind]) -> Vec<MetaKind> {
+        let mut kinds: Vec<MetaKind> = vec![];
+        for kind in preferred.iter().chain(DEFAULT_META_ORDER.iter()) {
+            if self.has(*kind) && !kinds.contains(kind) {
+                kinds.push(*kind);
+            }
         }
+
+        kinds
     }
 }
 
-use clap::{Parser, Subcommand};
+use clap::{Args, Parser, Subcommand};
 use serde_json::Value;
 
 #[derive(Parser)]
 #[command(author, version, about, long_about = None)]
 struct Cli {
-    #[arg(long)]
+    /// Files or directories to scan, optionally labeled as `label=path`. May
+    /// be repeated, and given after a subcommand to narrow it, like
+    /// `codo mod assign-unassigned --assignee dana --path src/billing`.
+    #[arg(long, global = true)]
     path: Option<Vec<String>>,
 
+    /// Follows symbolic links while scanning, skipping link cycles.
+    #[arg(long, global = true)]
+    follow_links: bool,
+
+    /// Only scan the `--path` entries with these labels, given as `label=path`.
+    #[arg(long, global = true)]
+    root: Option<Vec<String>>,
+
+    /// Only include files matching the glob, or not matching it when prefixed
+    /// with `!`. May be repeated.
+    #[arg(long, global = true)]
+    path_glob: Option<Vec<String>>,
+
+    /// Only include files of this type, like `rust` or `py`, from ripgrep's
+    /// built-in extension map. May be repeated.
+    #[arg(long = "type", global = true)]
+    file_type: Option<Vec<String>>,
+
+    /// Defines a file type for `--type`, like `proto:*.proto`. May be
+    /// repeated.
+    #[arg(long, global = true)]
+    type_add: Vec<String>,
+
+    /// Rewrites TODOs whose lines changed since the scan, which edits
+    /// otherwise skip and report.
+    #[arg(long, global = true)]
+    force: bool,
+
+    /// Lets code mods and `format` edit files marked `@generated` or
+    /// `DO NOT EDIT` near the top, which are otherwise left alone.
+    #[arg(long, global = true)]
+    include_generated: bool,
+
+    /// Also scans the files inside `.zip`, `.tar`, and `.tar.gz` archives,
+    /// reported as `archive.zip!/inner/path.py`. Archives are read-only, so
+    /// this can't be combined with commands that edit files.
+    #[arg(long, global = true)]
+    scan_archives: bool,
+
+    /// Scans the files git tracks, listed by `git ls-files`, instead of
+    /// walking directories. Of the ignore files, only `.codoignore` and
+    /// `.ignore` apply.
+    #[arg(long, global = true)]
+    vcs_files: bool,
+
+    /// With --vcs-files, also scans untracked files git doesn't ignore.
+    #[arg(long, global = true, requires = "vcs_files")]
+    vcs_untracked: bool,
+
+    /// Doesn't apply the global gitignore, set by git's `core.excludesFile`.
+    #[arg(long, global = true)]
+    no_ignore_global: bool,
+
+    /// Doesn't apply the repository's `.git/info/exclude`.
+    #[arg(long, global = true)]
+    no_ignore_exclude: bool,
+
+    /// Explains whether the scan includes the file or directory, and which
+    /// ignore file rule decides it, instead of running the command.
+    #[arg(long, global = true, value_name = "PATH")]
+    debug_ignores: Option<String>,
+
+    /// The config file to read instead of `.codo.toml` in the working
+    /// directory.
+    #[arg(long, global = true)]
+    config: Option<String>,
+
+    /// Prints timings and scan counters to stderr, to help tune excludes.
+    #[arg(long, global = true)]
+    stats: bool,
+
     #[command(subcommand)]
     command: Option<Commands>,
 }
 
+enum StatFormat {
+    Text,
+    Json,
+    Prometheus,
+    Csv,
+    Tsv,
+}
+
+impl StatFormat {
+    fn from_str(s: &str) -> Option<Self> {
+        match s {
+            "text" => Some(StatFormat::Text),
+            "json" => Some(StatFormat::Json),
+            "prometheus" => Some(StatFormat::Prometheus),
+            "csv" => Some(StatFormat::Csv),
+            "tsv" => Some(StatFormat::Tsv),
+            _ => None,
+        }
+    }
+}
+
+fn escape_label_value(value: &str) -> String {
+    value
+        .replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace('\n', "\\n")
+}
+
+/// TODO counts in the Prometheus text exposition format, labeled by the
+/// grouping if given, otherwise by assignee and whether they're overdue.
+/// TODOs with several assignees count for each.
+fn prometheus_metrics(
+    todos: &[Todo],
+    grouping: Option<(String, Grouping)>,
+    config: &Config,
+) -> String {
+    let mut counts: BTreeMap<String, usize> = BTreeMap::new();
+    for todo in todos {
+        let labels: Vec<String> = match &grouping {
+            Some((name, grouping)) => grouping
+                .keys(todo, config)
+                .iter()
+                .map(|key| format!("{}=\"{}\"", name, escape_label_value(key)))
+                .collect(),
+            None => {
+                let assignees = match todo.metadata.assignees.as_slice() {
+                    [] => vec![String::new()],
+                    assignees => assignees.to_vec(),
+                };
+                assignees
+                    .iter()
+                    .map(|assignee| {
+                        format!(
+                            "assignee=\"{}\",overdue=\"{}\"",
+                            escape_label_value(assignee),
+                            is_overdue(todo)
+                        )
+                    })
+                    .collect()
+            }
+        };
+        for labels in labels {
+            *counts.entry(labels).or_default() += 1;
+        }
+    }
+
+    let mut output = String::from(
+        "# HELP codo_todos_total Number of TODO comments.\n# TYPE codo_todos_total gauge\n",
+    );
+    for (labels, count) in counts {
+        output.push_str(&format!("codo_todos_total{{{}}} {}\n", labels, count));
+    }
+
+    output
+}
+
+/// Languages by file extension, for `--group-by language`.
+const LANGUAGES: [(&str, &str); 56] = [
+    ("bash", "Shell"),
+    ("c", "C"),
+    ("cc", "C++"),
+    ("cjs", "JavaScript"),
+    ("clj", "Clojure"),
+    ("cpp", "C++"),
+    ("cs", "C#"),
+    ("css", "CSS"),
+    ("cxx", "C++"),
+    ("dart", "Dart"),
+    ("erl", "Erlang"),
+    ("ex", "Elixir"),
+    ("exs", "Elixir"),
+    ("fs", "F#"),
+    ("go", "Go"),
+    ("groovy", "Groovy"),
+    ("h", "C"),
+    ("hh", "C++"),
+    ("hpp", "C++"),
+    ("hs", "Haskell"),
+    ("html", "HTML"),
+    ("ipynb", "Jupyter Notebook"),
+    ("java", "Java"),
+    ("js", "JavaScript"),
+    ("jsx", "JavaScript"),
+    ("kt", "Kotlin"),
+    ("kts", "Kotlin"),
+    ("less", "CSS"),
+    ("lua", "Lua"),
+    ("m", "Objective-C"),
+    ("md", "Markdown"),
+    ("mjs", "JavaScript"),
+    ("ml", "OCaml"),
+    ("php", "PHP"),
+    ("pl", "Perl"),
+    ("proto", "Protocol Buffers"),
+    ("ps1", "PowerShell"),
+    ("py", "Python"),
+    ("r", "R"),
+    ("rb", "Ruby"),
+    ("rs", "Rust"),
+    ("sass", "CSS"),
+    ("scala", "Scala"),
+    ("scss", "CSS"),
+    ("sh", "Shell"),
+    ("sql", "SQL"),
+    ("svelte", "Svelte"),
+    ("swift", "Swift"),
+    ("tf", "Terraform"),
+    ("toml", "TOML"),
+    ("ts", "TypeScript"),
+    ("tsx", "TypeScript"),
+    ("vue", "Vue"),
+    ("yaml", "YAML"),
+    ("yml", "YAML"),
+    ("zsh", "Shell"),
+];
+
+/// The language of the file by its extension, per `[languages]` in the
+/// config or else the built-in list.
+fn language(path: &Path, config: &Config) -> Option<String> {
+    let extension = path.extension()?.to_str()?.to_lowercase();
+    let configured = config.languages.iter().find(|(known, _)| {
+        known
+            .trim_start_matches('.')
+            .eq_ignore_ascii_case(&extension)
+    });
+    if let Some((_, language)) = configured {
+        return Some(language.to_owned());
+    }
+
+    LANGUAGES
+        .iter()
+        .find(|(known, _)| *known == extension)
+        .map(|(_, language)| language.to_string())
+}
+
 enum Grouping {
     Assignee,
     Due,
     Issue,
+    Root,
+    Language,
+    /// The directory a TODO is in, cut to this many levels.
+    Path(usize),
+}
+
+/// The first `depth` directories of the path's parent, like `src/api` for
+/// `src/api/users/handler.rs` at depth 2, or `.` for files at the top.
+fn path_group(path: &Path, depth: usize) -> String {
+    let directories: Vec<String> = path
+        .parent()
+        .map(|parent| {
+            parent
+                .components()
+                .filter(|component| !matches!(component, std::path::Component::CurDir))
+                .take(depth)
+                .map(|component| component.as_os_str().to_string_lossy().into_owned())
+                .collect()
+        })
+        .unwrap_or_default();
+
+    if directories.is_empty() {
+        ".".to_owned()
+    } else {
+        directories.join("/")
+    }
 }
 
 impl Grouping {
@@ -198,72 +1189,286 @@ impl Grouping {
             "assignee" => Some(Grouping::Assignee),
             "due" => Some(Grouping::Due),
             "issue" => Some(Grouping::Issue),
+            "root" => Some(Grouping::Root),
+            "language" => Some(Grouping::Language),
+            "path" => Some(Grouping::Path(1)),
+            _ => None,
+        }
+    }
+
+    /// The groups the TODO is in: one, or with several assignees each of
+    /// theirs.
+    fn keys(&self, todo: &Todo, config: &Config) -> Vec<String> {
+        match self {
+            Grouping::Assignee if !todo.metadata.assignees.is_empty() => {
+                todo.metadata.assignees.clone()
+            }
+            grouping => vec![grouping.key(todo, config)],
+        }
+    }
+
+    fn key(&self, todo: &Todo, config: &Config) -> String {
+        match self {
+            Grouping::Assignee => todo
+                .metadata
+                .assignee()
+                .cloned()
+                .unwrap_or("<unassigned>".to_string()),
+            Grouping::Due if todo.metadata.invalid_due.is_some() => "<invalid>".to_owned(),
+            Grouping::Due => todo.due().to_owned().unwrap_or("<someday>".to_string()),
+            Grouping::Issue => todo
+                .metadata
+                .issue
+                .as_ref()
+                .map(|i| i.as_display_string())
+                .unwrap_or("<untracked>".to_string()),
+            Grouping::Root => todo.root.to_owned().unwrap_or("<unlabeled>".to_string()),
+            Grouping::Language => language(&todo.path, config).unwrap_or("<other>".to_string()),
+            Grouping::Path(depth) => path_group(&todo.path, *depth),
+        }
+    }
+
+    /// Orders group keys alphabetically, or chronologically for due dates,
+    /// with placeholders like `<someday>` last.
+    fn compare_keys(&self, a: &str, b: &str) -> Ordering {
+        let is_placeholder = |key: &str| key.starts_with('<') && key.ends_with('>');
+        let date = |key: &str| NaiveDate::parse_from_str(key, "%Y-%m-%d").ok();
+
+        is_placeholder(a)
+            .cmp(&is_placeholder(b))
+            .then_with(|| match self {
+                Grouping::Due => date(a).cmp(&date(b)),
+                _ => Ordering::Equal,
+            })
+            .then_with(|| a.cmp(b))
+    }
+}
+
+enum GroupSort {
+    Key,
+    Count,
+}
+
+impl GroupSort {
+    fn from_str(s: &str) -> Option<Self> {
+        match s {
+            "key" => Some(GroupSort::Key),
+            "count" => Some(GroupSort::Count),
             _ => None,
         }
     }
 }
 
+#[derive(Clone)]
 struct TodoFilters {
     assignee: Option<Vec<String>>,
     unassigned: bool,
 
     issue: Option<Vec<String>>,
+    project_key: Option<Vec<String>>,
+    issue_gt: Option<Issue>,
+    issue_lt: Option<Issue>,
     untracked: bool,
+    blocked_by: Option<Vec<String>>,
 
     due: Option<Vec<String>>,
     overdue: bool,
     someday: bool,
+    due_within: Option<Duration>,
 }
 
-#[derive(Subcommand)]
-enum Commands {
-    List {
-        #[arg(long)]
-        assignee: Option<Vec<String>>,
+impl TodoFilters {
+    fn filters_dates(&self) -> bool {
+        self.due.is_some() || self.overdue || self.someday || self.due_within.is_some()
+    }
+}
 
-        #[arg(long)]
-        unassigned: bool,
+/// The filters shared by the commands that pick TODOs to list or count.
+#[derive(Args, Default)]
+struct FilterArgs {
+    #[arg(long)]
+    assignee: Option<Vec<String>>,
 
-        #[arg(long)]
-        issue: Option<Vec<String>>,
+    #[arg(long)]
+    unassigned: bool,
 
-        #[arg(long)]
-        untracked: bool,
+    /// Only TODOs citing the issue, written like `#12`, `PROJ-12`, or as
+    /// the issue's URL. Case and spacing don't matter, and links to
+    /// GitHub, GitLab, Jira, and Linear match their short forms.
+    #[arg(long)]
+    issue: Option<Vec<String>>,
 
-        #[arg(long)]
-        due: Option<Vec<String>>,
+    /// Only TODOs citing an issue of the project, like `PROJ` for
+    /// `PROJ-12`.
+    #[arg(long)]
+    project_key: Option<Vec<String>>,
 
-        #[arg(long)]
-        overdue: bool,
+    /// Only TODOs citing an issue numbered after this one, in the same
+    /// project or repository, like `PROJ-100` or `#100`.
+    #[arg(long, value_name = "ISSUE")]
+    issue_gt: Option<String>,
 
-        #[arg(long)]
-        someday: bool,
-    },
-    Stat {
-        #[arg(long)]
-        assignee: Option<Vec<String>>,
+    /// Only TODOs citing an issue numbered before this one, in the same
+    /// project or repository.
+    #[arg(long, value_name = "ISSUE")]
+    issue_lt: Option<String>,
 
-        #[arg(long)]
-        unassigned: bool,
+    #[arg(long)]
+    untracked: bool,
 
-        #[arg(long)]
-        issue: Option<Vec<String>>,
+    #[arg(long)]
+    due: Option<Vec<String>>,
 
-        #[arg(long)]
-        untracked: bool,
+    #[arg(long)]
+    overdue: bool,
+
+    #[arg(long)]
+    someday: bool,
+
+    /// Only TODOs due within the period, like `14d`, including overdue
+    /// ones.
+    #[arg(long)]
+    due_within: Option<String>,
+}
+
+impl FilterArgs {
+    fn into_filters(self, blocked_by: Option<Vec<String>>) -> TodoFilters {
+        TodoFilters {
+            assignee: self.assignee,
+            unassigned: self.unassigned,
+            issue: self.issue.map(|issues| normalize_issues(&issues)),
+            project_key: self
+                .project_key
+                .map(|keys| keys.iter().map(|key| key.to_uppercase()).collect()),
+            issue_gt: self
+                .issue_gt
+                .map(|issue| parse_issue_bound("--issue-gt", &issue)),
+            issue_lt: self
+                .issue_lt
+                .map(|issue| parse_issue_bound("--issue-lt", &issue)),
+            untracked: self.untracked,
+            blocked_by: blocked_by.map(|issues| normalize_issues(&issues)),
+            due: self.due,
+            overdue: self.overdue,
+            someday: self.someday,
+            due_within: parse_due_within(self.due_within),
+        }
+    }
+}
+
+#[derive(Subcommand)]
+enum Commands {
+    List {
+        #[command(flatten)]
+        filters: FilterArgs,
 
+        /// Only TODOs blocked by the issue, as `blocked-by:ABC-99`, written
+        /// like for `--issue`.
         #[arg(long)]
-        due: Option<Vec<String>>,
+        blocked_by: Option<Vec<String>>,
 
         #[arg(long)]
-        overdue: bool,
+        vimgrep: bool,
+
+        /// Prints only the paths of files with matching TODOs, once each.
+        #[arg(long, short = 'l', conflicts_with = "vimgrep")]
+        files_with_matches: bool,
+
+        /// Prints TODOs as a directory tree, with counts for each directory
+        /// and file.
+        #[arg(long, conflicts_with_all = ["vimgrep", "files_with_matches"])]
+        tree: bool,
 
+        /// Prints each location as a link to the line at the commit checked
+        /// out.
+        #[arg(long, conflicts_with_all = ["vimgrep", "files_with_matches", "tree"])]
+        permalinks: bool,
+
+        /// Keeps running, rescanning changed files and reprinting the list.
         #[arg(long)]
-        someday: bool,
+        watch: bool,
+
+        /// Opens the first match at its line in the editor, set by `editor`
+        /// in the config or `$EDITOR`, instead of printing the list.
+        #[arg(long, conflicts_with_all = ["files_with_matches", "tree", "permalinks", "watch"])]
+        open: bool,
+
+        /// Asks which of the matches to open.
+        #[arg(long, requires = "open")]
+        pick: bool,
+
+        /// `text` (the default), or `ndjson` for one JSON object per TODO and
+        /// line, as read by `stat --json-lines`.
+        #[arg(long, conflicts_with_all = ["vimgrep", "files_with_matches", "tree", "permalinks", "open"])]
+        format: Option<String>,
+
+        /// Prints each distinct note once, with how many TODOs have it and
+        /// where, ignoring case, spacing, and punctuation at its ends.
+        #[arg(long, conflicts_with_all = ["vimgrep", "files_with_matches", "tree", "permalinks", "open", "format"])]
+        summary: bool,
+    },
+    Stat {
+        #[command(flatten)]
+        filters: FilterArgs,
 
         #[arg(long)]
         group_by: Option<String>,
+
+        /// How many directories deep `--group-by=path` groups TODOs, e.g. 2
+        /// for `src/api` and `src/web`. Defaults to 1.
+        #[arg(long, requires = "group_by", value_parser = clap::value_parser!(u16).range(1..))]
+        depth: Option<u16>,
+
+        /// Orders groups by `count` (the default) or by `key`, which puts due
+        /// dates in chronological order.
+        #[arg(long, requires = "group_by")]
+        sort: Option<String>,
+
+        /// Also lists groups without TODOs, at 0: for `assignee`, those given
+        /// with `--assignee`, or else `active_members` or the `[assignees]`
+        /// in the config; for `due`, the dates of the config's milestones;
+        /// and for `root`, the labels of the `--path` entries.
+        #[arg(long, requires = "group_by")]
+        include_empty: bool,
+
+        /// Counts TODOs with several assignees as a share for each, like 0.5
+        /// for two, instead of a whole TODO for each.
+        #[arg(long, requires = "group_by")]
+        split_count: bool,
+
+        #[arg(long, conflicts_with = "group_by")]
+        burndown: bool,
+
+        #[arg(long, requires = "burndown")]
+        since: Option<String>,
+
+        #[arg(long, conflicts_with_all = ["group_by", "burndown"])]
+        by_age: bool,
+
+        /// Prints, per assignee, how many TODOs they have, how many and what
+        /// share of them are overdue, and by how many days on average.
+        #[arg(long, conflicts_with_all = ["group_by", "burndown", "by_age"])]
+        metrics: bool,
+
+        /// Lists assignees who aren't on the roster in the file, one name
+        /// per line, and roster members without TODOs, exiting non-zero if
+        /// there are assignees missing from it. Without a file, the roster is
+        /// `active_members` in the config.
+        #[arg(long, value_name = "FILE", num_args = 0..=1, require_equals = true, conflicts_with_all = ["group_by", "burndown", "by_age", "metrics", "format"])]
+        assignees_missing_from: Option<Option<String>>,
+
+        /// `text`, `json` (with `--metrics`), `prometheus` for the Prometheus
+        /// exposition format, or `csv` or `tsv` (with `--group-by`) for a
+        /// header and a row per group with its count and overdue count.
+        #[arg(long, conflicts_with_all = ["burndown", "by_age"])]
+        format: Option<String>,
+
+        /// Counts the TODOs saved by `list --format ndjson` in the file, or
+        /// stdin if `-`, instead of scanning.
+        #[arg(long, value_name = "FILE", conflicts_with_all = ["burndown", "by_age"])]
+        json_lines: Option<String>,
     },
+    #[command(alias = "lint")]
     Validate {
         #[arg(long)]
         require_assignees: bool,
@@ -274,31 +1479,302 @@ enum Commands {
         #[arg(long)]
         require_due_dates: bool,
 
+        /// Requires TODOs of this priority or higher, e.g. `p1` for `p0` and
+        /// `p1`, to have a due date and an assignee.
+        #[arg(long)]
+        require_due_for_priority: Option<String>,
+
+        #[arg(long)]
+        forbid_bare: bool,
+
+        #[arg(long)]
+        max_todos_per_file: Option<usize>,
+
+        /// Reports TODOs snoozed further ahead than the period, like `30d`.
+        #[arg(long)]
+        max_snooze: Option<String>,
+
         #[arg(long)]
         allowed_assignees: Option<Vec<String>>,
 
+        /// Reports TODOs assigned to more than one person.
+        #[arg(long)]
+        single_assignee: bool,
+
+        /// Reports metadata tokens that aren't recognized, like `@@alice`.
+        #[arg(long)]
+        forbid_unknown_metadata: bool,
+
         #[arg(long)]
         issue_format: Option<String>,
 
         #[arg(long)]
         issue_project_keys: Option<Vec<String>>,
+
+        /// Requires issues only for TODOs in files matching the glob.
+        #[arg(long)]
+        require_issues_for: Option<Vec<String>>,
+
+        /// Also reports comments mentioning TODO or FIXME that aren't
+        /// well-formed enough to be scanned, e.g. `//TODO fix`.
+        #[arg(long)]
+        pedantic: bool,
+
+        /// Reports `DONE:` and `RESOLVED:` comments left behind after the
+        /// work was finished.
+        #[arg(long)]
+        forbid_done: bool,
+
+        /// Requires TODOs with notes shorter than this many characters to
+        /// have another comment line right before or after them.
+        #[arg(long)]
+        require_context_comment: Option<usize>,
+
+        /// Only checks TODOs on lines added since the git ref, e.g.
+        /// `origin/main`, including uncommitted changes.
+        #[arg(long)]
+        new_since: Option<String>,
+
+        /// Requires new TODOs to be assigned to whoever added them, by git
+        /// author name, email, or their `aliases` in the config.
+        #[arg(long, requires = "new_since")]
+        require_author_assignee: bool,
+
+        /// Only reports errors the TODOs didn't already have at the git ref,
+        /// e.g. `main`, recognizing TODOs by their `id:` or unchanged text
+        /// rather than their line, so moved TODOs don't count as new. Per
+        /// file limits still count every TODO.
+        #[arg(long, value_name = "REF", conflicts_with = "new_since")]
+        compare_ref: Option<String>,
+
+        /// Reports TODOs assigned to someone who left the team, that is not
+        /// in `active_members` in the config, or if that's not set, who
+        /// hasn't committed in the last 180 days.
+        #[arg(long)]
+        forbid_assignee_left: bool,
+
+        /// Reports TODOs in doc comments and docstrings, which end up in
+        /// rendered documentation. With `=public`, only in the docs of
+        /// public items and modules.
+        #[arg(long, value_name = "SCOPE", num_args = 0..=1, require_equals = true, default_missing_value = "all")]
+        forbid_doc_todos: Option<String>,
+
+        /// Writes the fixes for invalid formats, metadata orders, and issue
+        /// cases to the file, or stdout if it's `-`, as a patch for `git
+        /// apply`, without changing any other file. Notebooks are left out.
+        #[arg(long, value_name = "FILE")]
+        emit_patch: Option<String>,
+
+        /// Keeps running, reading one `{"path": ..., "content": ...}` JSON
+        /// request per line from stdin and writing its TODOs' errors as one
+        /// JSON line, for editor plugins.
+        #[arg(long, conflicts_with_all = ["pedantic", "forbid_done", "require_context_comment", "new_since", "require_author_assignee", "compare_ref", "forbid_assignee_left", "max_todos_per_file", "forbid_doc_todos", "emit_patch"])]
+        stdin_json: bool,
+
+        /// Prints only how many errors each rule reported, for CI logs.
+        #[arg(long, conflicts_with = "stdin_json")]
+        quiet: bool,
+
+        /// Lists at most this many TODOs and files with errors, followed by
+        /// how many more there are.
+        #[arg(long, value_name = "N", conflicts_with_all = ["stdin_json", "quiet"])]
+        max_violations: Option<usize>,
+    },
+    Format {
+        /// Lists the TODOs that would change without rewriting them, exiting
+        /// non-zero if there are any.
+        #[arg(long)]
+        check: bool,
+
+        /// Gives TODOs without an `id:` token a new unique one.
+        #[arg(long, conflicts_with = "check")]
+        assign_ids: bool,
+
+        /// Prints each line changed, before and after.
+        #[arg(long, conflicts_with = "check")]
+        verbose: bool,
+
+        /// Checks that formatting the formatted TODOs changes nothing more,
+        /// listing those that would change again on the next run, without
+        /// rewriting anything.
+        #[arg(long, conflicts_with_all = ["check", "assign_ids", "verbose"])]
+        verify: bool,
     },
-    Format,
     Export {
         #[command(subcommand)]
         medium: ExportMedium,
     },
+    Digest {
+        #[arg(long)]
+        assignee: Option<String>,
+
+        #[arg(long)]
+        all_assignees: bool,
+
+        #[arg(long)]
+        format: Option<String>,
+
+        #[arg(long)]
+        out_dir: Option<String>,
+    },
     Mod {
+        /// Shows the changes to each file and asks whether to write them.
+        #[arg(long, global = true)]
+        interactive: bool,
+
+        /// Prints each line changed, before and after.
+        #[arg(long, global = true)]
+        verbose: bool,
+
         #[command(subcommand)]
         code_mod: CodeMod,
     },
+    Serve {
+        #[arg(long, default_value_t = 8080)]
+        port: u16,
+    },
+    /// Lists TODOs with a due date by date.
+    Due {
+        #[arg(long)]
+        assignee: Option<Vec<String>>,
+
+        /// Prints a calendar of each month, marking days with the number of
+        /// TODOs due.
+        #[arg(long)]
+        calendar: bool,
+
+        /// The first month of the calendar as YYYY-MM, by default this one.
+        #[arg(long, requires = "calendar")]
+        from: Option<String>,
+
+        /// How many months the calendar shows, up to 1200.
+        #[arg(long, default_value_t = 3, requires = "calendar", value_parser = clap::value_parser!(u32).range(1..=1200))]
+        months: u32,
+    },
+    /// Lists the TODOs of each assignee across several repositories, the
+    /// earliest due first.
+    Agenda {
+        /// A file listing a repository directory, or a saved `list --format
+        /// ndjson` run, per line, optionally labeled as `label=path`.
+        #[arg(long)]
+        repos: String,
+
+        #[arg(long)]
+        assignee: Option<Vec<String>>,
+    },
+    /// Compares TODO counts with the directory budgets in the config.
+    Budget,
+    /// Lists the issues TODOs cite, the most cited first, with where they're
+    /// cited.
+    Issues {
+        /// text, json, or markdown.
+        #[arg(long)]
+        format: Option<String>,
+    },
+    /// Checks cited issues with the tracker in the config, listing TODOs
+    /// whose issue is closed.
+    Sync {
+        /// Files an issue for each TODO without one, and cites it.
+        #[arg(long)]
+        create_issues: bool,
+
+        /// Closes the issues cited by DONE and RESOLVED markers.
+        #[arg(long)]
+        close_done: bool,
+
+        /// Adds a `status:closed` token, or the tracker's `closed_marker`, to
+        /// the metadata of TODOs citing closed issues.
+        #[arg(long)]
+        mark_closed: bool,
+
+        /// Removes the `blocked-by:` token of TODOs whose blocker is closed.
+        #[arg(long)]
+        unblock: bool,
+    },
+    /// Steps through matching TODOs one by one to snooze, reassign, delete,
+    /// or edit each.
+    Review {
+        #[command(flatten)]
+        filters: FilterArgs,
+    },
+    /// Explains a `validate` rule, or lists them all.
+    Explain { rule: Option<String> },
+    /// Stores, checks, and removes the API tokens of issue trackers.
+    Auth {
+        #[command(subcommand)]
+        action: AuthAction,
+    },
+    /// Validates the config file without scanning.
+    Config {
+        #[command(subcommand)]
+        action: ConfigAction,
+    },
+    /// Prints the schema of the JSON output.
+    Schema {
+        #[arg(long, default_value = "json-schema")]
+        format: String,
+    },
+    /// Suggests existing values for `--assignee`, `--issue`, or `--due`, for
+    /// shell completion scripts.
+    #[command(name = "_complete", hide = true)]
+    Complete {
+        #[arg(allow_hyphen_values = true)]
+        flag: String,
+
+        #[arg(default_value = "")]
+        prefix: String,
+    },
+    /// Checks the parser against the test corpus and its golden parse results.
+    #[command(hide = true)]
+    Selftest {
+        #[arg(long, default_value = "tests")]
+        dir: String,
+
+        #[arg(long)]
+        bless: bool,
+    },
 }
 
 const VERSION: &str = env!("CARGO_PKG_VERSION");
 
 #[derive(Subcommand)]
 enum ExportMedium {
-    Json,
+    Json {
+        /// Includes this many lines before and after each TODO as `context`.
+        #[arg(long)]
+        context: Option<usize>,
+
+        /// Includes a link to each TODO's line at the commit checked out as
+        /// `permalink`.
+        #[arg(long)]
+        permalinks: bool,
+    },
+}
+
+#[derive(Subcommand)]
+enum AuthAction {
+    /// Reads the service's token from stdin and stores it in the credentials
+    /// file, readable only by you.
+    Login {
+        /// github, gitlab, jira, or linear.
+        service: String,
+
+        /// Stores the token in the OS keychain instead.
+        #[arg(long)]
+        keychain: bool,
+    },
+    /// Shows where each service's token comes from, without showing it.
+    Status,
+    /// Removes the service's token from the credentials file and the OS
+    /// keychain.
+    Logout { service: String },
+}
+
+#[derive(Subcommand)]
+enum ConfigAction {
+    /// Fails on unknown keys and invalid values, like `codo` would before
+    /// any other command.
+    Check,
 }
 
 #[derive(Subcommand)]
@@ -306,8 +1782,22 @@ enum CodeMod {
     RemoveIssue {
         #[arg(long)]
         issue: String,
+
+        /// `strip-metadata` removes just the issue, `delete` the whole
+        /// comment, and `convert-to-note` keeps the comment as a plain note.
+        #[arg(long, default_value = "strip-metadata")]
+        strategy: String,
     },
     RemoveAllIssues,
+    /// Uppercases project keys and removes spaces in issues, e.g. `abc-123`
+    /// becomes `ABC-123`, so they're recognized as issues.
+    NormalizeIssueCase,
+    /// Drops metadata repeating a kind the TODO already has, like the second
+    /// assignee in `@alice, @alice` or a second date, keeping the first.
+    DedupeMetadata,
+    /// Drops parentheses holding no metadata, like `TODO():` or `TODO(, ):`,
+    /// leaving the bare `TODO:`.
+    PruneEmptyMeta,
     RenameIssue {
         #[arg(long)]
         from: String,
@@ -316,8 +1806,11 @@ enum CodeMod {
         to: String,
     },
     AddIssueForAllUntracked {
-        #[arg(long)]
-        issue: String,
+        #[arg(long, required_unless_present = "from_branch")]
+        issue: Option<String>,
+
+        #[arg(long, conflicts_with = "issue")]
+        from_branch: bool,
     },
 
     RemoveAssignee {
@@ -336,6 +1829,9 @@ enum CodeMod {
         #[arg(long)]
         assignee: String,
     },
+    /// Assigns unassigned TODOs to the owner of their path, per `[owners]`
+    /// in the config.
+    AssignByRules,
 
     AssignIssue {
         #[arg(long)]
@@ -357,6 +1853,32 @@ enum CodeMod {
         #[arg(long)]
         date: String,
     },
+    /// Keeps the TODOs citing the issue out of overdue and due soon lists
+    /// until the date.
+    Snooze {
+        #[arg(long)]
+        issue: String,
+
+        #[arg(long)]
+        until: String,
+    },
+
+    /// Converts TODOs to the comment delimiter given, or configured per file
+    /// extension under `[comment_styles]`, where the language supports it.
+    ChangeDelimiter {
+        #[arg(long)]
+        to: Option<String>,
+    },
+
+    /// Deletes `DONE:` and `RESOLVED:` comments left behind after the work
+    /// was finished.
+    RemoveDone,
+
+    /// Applies edits read as JSON objects, one per TODO, from stdin.
+    Apply {
+        #[arg(long, required = true)]
+        stdin: bool,
+    },
 }
 
 fn filter_by_match(
@@ -371,61 +1893,215 @@ fn filter_by_match(
             include_unset
         }
     } else if include_unset {
-        value == None
+        value.is_none()
     } else {
         true
     }
 }
 
+/// Like `filter_by_match` for several values, matching when any is selected.
+fn filter_by_any_match(
+    values: &[String],
+    selection: Option<&[String]>,
+    include_unset: bool,
+) -> bool {
+    match selection {
+        Some(list) if !values.is_empty() => values.iter().any(|value| list.contains(value)),
+        Some(_) => include_unset,
+        None => !include_unset || values.is_empty(),
+    }
+}
+
 fn parse_due_date(date_str: String) -> Option<NaiveDate> {
     NaiveDate::parse_from_str(&date_str, "%Y-%m-%d").ok()
 }
 
-fn filter_todo_list(list: Vec<Todo>, filters: TodoFilters) -> Vec<Todo> {
-    list.into_iter()
-        .filter(|todo| {
-            filter_by_match(
-                todo.metadata.assignee.to_owned(),
-                filters.assignee.to_owned(),
-                filters.unassigned,
-            ) && filter_by_match(
-                todo.metadata.issue.as_ref().map(|i| i.as_string()),
-                filters.issue.to_owned(),
-                filters.untracked,
-            ) && filter_by_match(
-                todo.metadata.due.to_owned(),
-                filters.due.to_owned(),
-                filters.someday,
-            ) && if filters.overdue {
-                if let Some(due) = todo.metadata.due.to_owned() {
-                    if let Some(date) = parse_due_date(due) {
-                        date < Local::now().date_naive()
-                    } else {
-                        false
-                    }
-                } else {
-                    false
-                }
-            } else {
-                true
-            }
-        })
-        .collect()
+/// Whether the TODO's due date has passed, and no `snooze:` date still ahead
+/// keeps it out of the overdue lists.
+fn is_overdue(todo: &Todo) -> bool {
+    todo.due()
+        .to_owned()
+        .and_then(parse_due_date)
+        .is_some_and(|date| date < Local::now().date_naive())
+        && !is_snoozed(&todo.metadata)
+}
+
+enum ListStyle {
+    SearchResults,
+    Permalinks(git::PermalinkBase),
+    Vimgrep,
+    FilesWithMatches,
+    Tree,
+    Ndjson,
+    Summary,
 }
 
-fn make_metadata_str(metadata: TodoMetadata) -> Option<String> {
-    let mut parts: Vec<String> = vec![];
-    if let Some(issue) = metadata.issue {
-        parts.push(format!("{}", issue.as_string()))
+fn list_output(todos: &[&Todo], style: &ListStyle) -> String {
+    match style {
+        ListStyle::SearchResults => todos
+            .iter()
+            .map(|t| t.as_search_result())
+            .collect::<Vec<String>>()
+            .join("\n"),
+        ListStyle::Permalinks(base) => todos
+            .iter()
+            .map(|t| match base.link(&t.path, t.file_position().0) {
+                Some(link) => format!("{} {}", link, t.summary()),
+                None => t.as_search_result(),
+            })
+            .collect::<Vec<String>>()
+            .join("\n"),
+        ListStyle::Vimgrep => todos
+            .iter()
+            .map(|t| t.as_vimgrep_result())
+            .collect::<Vec<String>>()
+            .join("\n"),
+        ListStyle::FilesWithMatches => {
+            let mut seen: HashSet<&PathBuf> = HashSet::new();
+            todos
+                .iter()
+                .filter(|todo| seen.insert(&todo.path))
+                .map(|todo| todo.path.display().to_string())
+                .collect::<Vec<String>>()
+                .join("\n")
+        }
+        ListStyle::Tree => tree::render(todos),
+        ListStyle::Summary => summary::render(todos),
+        ListStyle::Ndjson => todos
+            .iter()
+            .map(|t| t.as_json().to_string())
+            .collect::<Vec<String>>()
+            .join("\n"),
     }
+}
+
+fn detect_permalinks(config: &Config) -> git::PermalinkBase {
+    git::PermalinkBase::detect(config.repo_url.as_deref()).unwrap_or_else(|| {
+        cli_error(
+            "Permalinks need a git repository with a commit, and an origin remote or repo_url in the config"
+                .to_owned(),
+        )
+    })
+}
+
+fn parse_due_within(period: Option<String>) -> Option<Duration> {
+    period.map(|period| {
+        history::parse_period(&period)
+            .unwrap_or_else(|| cli_error(format!("--due-within={} not supported", period)))
+    })
+}
+
+/// Periods reaching past the last date there is count every due date as
+/// within them.
+fn is_due_within(due: &Option<String>, within: Duration) -> bool {
+    let until = Local::now().date_naive().checked_add_signed(within);
+    due.to_owned()
+        .and_then(parse_due_date)
+        .is_some_and(|date| until.is_none_or(|until| date <= until))
+}
+
+/// Whether a `snooze:` date still ahead keeps the TODO out of the overdue
+/// and due soon lists.
+fn is_snoozed(metadata: &TodoMetadata) -> bool {
+    metadata
+        .snooze
+        .to_owned()
+        .and_then(parse_due_date)
+        .is_some_and(|date| date > Local::now().date_naive())
+}
+
+fn todo_matches(todo: &Todo, filters: &TodoFilters) -> bool {
+    filter_by_any_match(
+        &todo.metadata.assignees,
+        filters.assignee.as_deref(),
+        filters.unassigned,
+    ) && filter_by_match(
+        todo.metadata.issue.as_ref().map(|i| i.as_display_string()),
+        filters.issue.to_owned(),
+        filters.untracked,
+    ) && filters.project_key.as_ref().is_none_or(|keys| {
+        todo.metadata
+            .issue
+            .as_ref()
+            .and_then(|issue| issue.project_key())
+            .is_some_and(|key| keys.contains(&key))
+    }) && filters.issue_gt.as_ref().is_none_or(|bound| {
+        todo.metadata
+            .issue
+            .as_ref()
+            .is_some_and(|issue| issue.compare_number(bound) == Some(Ordering::Greater))
+    }) && filters.issue_lt.as_ref().is_none_or(|bound| {
+        todo.metadata
+            .issue
+            .as_ref()
+            .is_some_and(|issue| issue.compare_number(bound) == Some(Ordering::Less))
+    }) && filters.blocked_by.as_ref().is_none_or(|issues| {
+        todo.metadata
+            .blocked_by
+            .as_ref()
+            .is_some_and(|blocker| issues.contains(&blocker.as_display_string()))
+    }) && filter_by_match(
+        todo.due().to_owned(),
+        filters.due.to_owned(),
+        filters.someday,
+    ) && (!filters.overdue || is_overdue(todo))
+        && filters
+            .due_within
+            .is_none_or(|within| is_due_within(todo.due(), within) && !is_snoozed(&todo.metadata))
+        && !(filters.filters_dates() && todo.metadata.invalid_due.is_some())
+}
 
-    if let Some(assignee) = metadata.assignee {
-        parts.push(format!("@{}", assignee))
+/// Warns about the TODOs that only the date filters left out, because their
+/// due dates are invalid: they match no date filter, rather than being taken
+/// as due someday. Only once, since `serve` and `list --watch` filter again on
+/// every request and rescan.
+fn warn_invalid_dues<'a>(todos: impl IntoIterator<Item = &'a Todo>, filters: &TodoFilters) {
+    static WARNED: AtomicBool = AtomicBool::new(false);
+    if !filters.filters_dates() || WARNED.load(AtomicOrdering::Relaxed) {
+        return;
     }
 
-    if let Some(due) = metadata.due {
-        parts.push(format!("{}", due))
+    let undated = TodoFilters {
+        due: None,
+        overdue: false,
+        someday: false,
+        due_within: None,
+        ..filters.clone()
+    };
+    let invalid_count = todos
+        .into_iter()
+        .filter(|todo| todo.metadata.invalid_due.is_some() && todo_matches(todo, &undated))
+        .count();
+    if invalid_count > 0 {
+        WARNED.store(true, AtomicOrdering::Relaxed);
+        eprintln!(
+            "Skipped {} with invalid due dates, see `codo lint`",
+            count_noun(invalid_count, "TODO")
+        );
     }
+}
+
+fn filter_todo_list(list: Vec<Todo>, filters: &TodoFilters) -> Vec<Todo> {
+    warn_invalid_dues(&list, filters);
+    list.into_iter()
+        .filter(|todo| todo_matches(todo, filters))
+        .collect()
+}
+
+fn make_metadata_str(metadata: TodoMetadata, meta_order: &MetaOrder) -> Option<String> {
+    let parts: Vec<String> = match meta_order {
+        MetaOrder::Preserve(_) => metadata.written_parts(false, &DEFAULT_META_ORDER),
+        MetaOrder::Fixed(order) => {
+            let mut parts = vec![];
+            for kind in metadata.ordered_kinds(order) {
+                match kind {
+                    MetaKind::Extra => parts.extend(metadata.extra.iter().cloned()),
+                    kind => parts.extend(metadata.token_text(kind)),
+                }
+            }
+            parts
+        }
+    };
 
     if parts.is_empty() {
         None
@@ -434,313 +2110,2466 @@ fn make_metadata_str(metadata: TodoMetadata) -> Option<String> {
     }
 }
 
-fn format_todo_update(delimiter: &String, note: &String, metadata: TodoMetadata) -> String {
-    if let Some(meta) = make_metadata_str(metadata) {
-        format!("{} TODO({}): {}", delimiter, meta, note)
+/// Like `make_metadata_str`, but keeps the tokens that didn't change as
+/// written, so code mods only touch what they mean to.
+fn make_edited_metadata_str(metadata: TodoMetadata, meta_order: &MetaOrder) -> Option<String> {
+    let preferred: &[MetaKind] = match meta_order {
+        MetaOrder::Preserve(_) => &DEFAULT_META_ORDER,
+        MetaOrder::Fixed(order) => order,
+    };
+    let parts = metadata.written_parts(true, preferred);
+    if parts.is_empty() {
+        None
     } else {
-        format!("{} TODO: {}", delimiter, note)
+        Some(parts.join(", "))
     }
 }
 
-fn apply_updates(updates: Vec<TodoUpdate>) {
-    let mut file_updates: HashMap<PathBuf, HashMap<u64, TodoUpdate>> = HashMap::new();
-    for update in updates.into_iter() {
-        file_updates
-            .entry(update.path.clone())
-            .or_default()
-            .insert(update.line_number - 1, update);
-    }
-
-    for (path, line_updates) in file_updates.borrow_mut() {
-        if let Ok(handle) = File::open(path.clone()) {
-            let mut output_lines: Vec<String> = vec![];
+/// The marker's keyword, metadata, and colon as `style` writes them, or as
+/// `TODO(<meta>):` by default. Styles only change how TODO is written.
+fn format_head(meta: Option<String>, marker: &str, style: Option<&TodoStyle>) -> String {
+    let keyword = match style {
+        Some(style) if marker == markers::TODO => style.keyword.as_str(),
+        _ => marker,
+    };
+    let colon = if style.is_none_or(|style| style.colon) {
+        ":"
+    } else {
+        ""
+    };
 
-            let reader = BufReader::new(handle);
-            for (num, line_result) in reader.lines().enumerate() {
-                if let Ok(line) = line_result {
-                    let new_line = if let Some(update) = line_updates.remove(&(num as u64)) {
-                        let leading_whitespace = line.split(&update.delimiter).nth(0).unwrap_or("");
+    match meta {
+        Some(meta) => format!("{}({}){}", keyword, meta, colon),
+        None => format!("{}{}", keyword, colon),
+    }
+}
 
-                        format!(
-                            "{}{}",
-                            leading_whitespace,
-                            format_todo_update(&update.delimiter, &update.note, update.metadata)
-                        )
-                    } else {
-                        line
-                    };
+fn format_todo(
+    delimiter: &String,
+    marker: &str,
+    note: &String,
+    metadata: TodoMetadata,
+    meta_order: &MetaOrder,
+    style: Option<&TodoStyle>,
+) -> String {
+    let head = format_head(make_metadata_str(metadata, meta_order), marker, style);
+    format!("{} {} {}", delimiter, head, note)
+}
 
-                    output_lines.push(new_line);
-                }
-            }
+fn format_todo_update(
+    delimiter: &String,
+    marker: &str,
+    note: &String,
+    metadata: TodoMetadata,
+    path: &Path,
+    config: &Config,
+) -> String {
+    format_todo(
+        delimiter,
+        marker,
+        note,
+        metadata,
+        &config.meta_order,
+        config.todo_style(path),
+    )
+}
 
-            if let Ok(mut new_file) = File::create(path) {
-                let _ = new_file.write_all(output_lines.join("\n").as_bytes());
-            }
+/// The TODO formatted, and formatted again after reading that back, if the
+/// second run would change it further. Formatting is meant to be idempotent,
+/// so this is a bug in the parser or formatter.
+fn format_instability(matcher: &RegexMatcher, todo: &Todo, config: &Config) -> Option<String> {
+    let format = |todo: &Todo| {
+        format_todo_update(
+            &todo.delimiter,
+            &todo.marker,
+            &todo.note,
+            todo.metadata.to_owned(),
+            &todo.path,
+            config,
+        )
+    };
+
+    let once = format(todo);
+    let reparsed = if containers::is_markdown(&todo.path) {
+        containers::parse_markdown_line(matcher, config, &todo.path, todo.line_number, &once)
+    } else {
+        parse_todo_line(matcher, config, &todo.path, todo.line_number, &once)
+    };
+    match reparsed {
+        Some(reparsed) => {
+            let twice = format(&reparsed);
+            (twice != once).then(|| format!("{}\n 1st {}\n 2nd {}", todo.location(), once, twice))
         }
+        None => Some(format!(
+            "{}\n 1st {}\n (no longer read as a TODO)",
+            todo.location(),
+            once
+        )),
     }
-
-    ()
 }
 
-struct TodoUpdate {
-    path: PathBuf,
-    line_number: u64,
-    delimiter: String,
-    note: String,
+fn format_todo_edit(
+    delimiter: &String,
+    marker: &str,
+    note: &String,
     metadata: TodoMetadata,
+    path: &Path,
+    config: &Config,
+) -> String {
+    let head = format_head(
+        make_edited_metadata_str(metadata, &config.meta_order),
+        marker,
+        config.todo_style(path),
+    );
+    format!("{} {} {}", delimiter, head, note)
 }
 
-struct ValidationErrorEntry {
-    todo: Todo,
-    errors: Vec<String>,
+/// A new ID like `td_8f3a` not in `taken`, which it's added to. IDs get
+/// longer when short ones keep colliding.
+fn generate_id(taken: &mut HashSet<String>) -> String {
+    let state = RandomState::new();
+    for attempt in 0usize.. {
+        let hex = format!("{:016x}", state.hash_one(attempt));
+        let id = format!("td_{}", &hex[..(4 + attempt / 8).min(hex.len())]);
+        if taken.insert(id.clone()) {
+            return id;
+        }
+    }
+
+    unreachable!()
 }
 
-impl ValidationErrorEntry {
-    fn as_cli_result(&self) -> String {
-        let error_list = self
-            .errors
-            .to_owned()
-            .into_iter()
-            .map(|s| format!("\t- {}", s))
-            .collect::<Vec<String>>()
-            .join("\n");
+/// How many lines from where it was scanned a TODO is looked for, when edits
+/// since the scan moved it.
+const RELOCATE_WINDOW: u64 = 10;
+
+/// Moves updates whose line changed since the scan to the closest line within
+/// `window` still holding their TODO as scanned, e.g. after another tool
+/// added lines above it. Updates finding their TODO on neither stay put, to
+/// be skipped.
+fn relocate_updates(lines: &[&str], mut updates: Vec<TodoUpdate>, window: u64) -> Vec<TodoUpdate> {
+    let mut located: Vec<(&mut u64, &str)> = updates
+        .iter_mut()
+        .map(|update| (&mut update.line_number, update.raw.as_str()))
+        .collect();
+    relocate_lines(lines, &mut located, window);
+    updates
+}
 
-        format!("{}\n{}", self.todo.as_search_result(), error_list)
+/// Moves each `(line number, raw)` pair whose line no longer ends with `raw`
+/// to the closest line within `window` that does and isn't taken, or leaves
+/// it put if there's none.
+fn relocate_lines(lines: &[&str], located: &mut [(&mut u64, &str)], window: u64) {
+    let holds = |line_number: u64, raw: &str| {
+        line_number >= 1
+            && lines
+                .get(line_number as usize - 1)
+                .is_some_and(|line| line.ends_with(raw))
+    };
+
+    let mut taken: HashSet<u64> = located
+        .iter()
+        .filter(|(line_number, raw)| holds(**line_number, raw))
+        .map(|(line_number, _)| **line_number)
+        .collect();
+    for (line_number, raw) in located.iter_mut() {
+        if holds(**line_number, raw) {
+            continue;
+        }
+        let found = (1..=window)
+            .flat_map(|distance| {
+                [
+                    line_number.checked_sub(distance),
+                    Some(**line_number + distance),
+                ]
+            })
+            .flatten()
+            .find(|&candidate| !taken.contains(&candidate) && holds(candidate, raw));
+        if let Some(found) = found {
+            taken.insert(found);
+            **line_number = found;
+        }
     }
 }
 
-struct ValidationRules {
-    require_assignees: bool,
-    require_issues: bool,
-    require_due_dates: bool,
+fn is_unchanged_line(line: &str, update: &TodoUpdate, force: bool) -> bool {
+    let unchanged = line.ends_with(&update.raw);
+    if !unchanged && !force {
+        report_stale_update(update);
+    }
 
-    allowed_assignees: Option<Vec<String>>,
-    issue_format: Option<IssueFormat>,
-    issue_project_keys: Option<Vec<String>>,
+    unchanged || force
 }
 
-fn get_validation_errors(todo: &Todo, validation_rules: &ValidationRules) -> Vec<String> {
-    let mut errors = vec![];
+fn report_stale_update(update: &TodoUpdate) {
+    eprintln!(
+        "Skipped {}, the line changed since it was scanned (re-run or use --force)",
+        update.location()
+    );
+}
 
-    let formatted = format_todo_update(&todo.delimiter, &todo.note, todo.metadata.to_owned());
-    if todo.raw != formatted {
-        errors.push("Invalid format");
+/// What the lines continuing a wrapped TODO start with: the TODO's own
+/// indentation and delimiter, then the text indented two more spaces, so they
+/// can't be mistaken for the comments after it.
+fn continuation_prefix(prefix: &str, delimiter: &str) -> String {
+    let indent: String = prefix
+        .chars()
+        .map(|c| if c == '\t' { '\t' } else { ' ' })
+        .collect();
+    // Block comments and Markdown task items continue without a delimiter.
+    match delimiter {
+        "//" | "#" => format!("{}{}   ", indent, delimiter),
+        _ => format!("{}{}", indent, " ".repeat(delimiter.chars().count() + 3)),
     }
+}
 
-    if validation_rules.require_assignees && todo.metadata.assignee.is_none() {
-        errors.push("Missing assignee");
+/// The text of the lines after a TODO that continue its note, like
+///
+/// ```text
+/// // TODO(@alice): Drop the legacy
+/// //   tax table
+/// ```
+///
+/// `prefix` is what comes before the TODO on its line, and `todo` the TODO.
+fn continuation_texts<'a>(
+    prefix: &str,
+    delimiter: &str,
+    todo: &str,
+    following: &[&'a str],
+) -> Vec<&'a str> {
+    let closed = |text: &str| delimiter == "/*" && text.ends_with("*/");
+    if closed(todo.trim_end()) {
+        return vec![];
     }
 
-    if let Some(allowed) = &validation_rules.allowed_assignees {
-        if let Some(assignee) = &todo.metadata.assignee {
-            if !allowed.contains(&assignee) {
-                errors.push("Invalid assignee");
+    let continuation = continuation_prefix(prefix, delimiter);
+    let mut texts: Vec<&str> = vec![];
+    for line in following {
+        match line.strip_prefix(&continuation) {
+            Some(text) if text.starts_with(|c: char| !c.is_whitespace()) => {
+                texts.push(text.trim_end());
+                if closed(text.trim_end()) {
+                    break;
+                }
             }
+            _ => break,
         }
     }
+    texts
+}
 
-    if validation_rules.require_issues && todo.metadata.issue.is_none() {
-        errors.push("Missing issue");
+/// Joins the lines continuing each TODO into its note, as if it were written
+/// on one line.
+fn join_continuations(todos: &mut [Todo], lines: &[&str]) {
+    for todo in todos.iter_mut().filter(|todo| todo.cell.is_none()) {
+        let index = todo.line_number as usize;
+        let Some(prefix) = lines
+            .get(index - 1)
+            .and_then(|line| line.strip_suffix(&todo.raw))
+        else {
+            continue;
+        };
+        let texts = continuation_texts(prefix, &todo.delimiter, &todo.raw, &lines[index..]);
+        if !texts.is_empty() {
+            todo.raw = format!("{} {}", todo.raw, texts.join(" "));
+            todo.note = format!("{} {}", todo.note, texts.join(" "));
+        }
     }
+}
 
-    if let Some(issue) = todo.metadata.issue.to_owned() {
-        if let Some(format) = &validation_rules.issue_format {
-            let valid_format = match (format, issue.to_owned()) {
-                (IssueFormat::Numbered, Issue::Numbered(_)) => true,
-                (IssueFormat::ProjectKey, Issue::ProjectKey { .. }) => true,
-                _ => false,
-            };
-
-            if !valid_format {
-                errors.push("Invalid issue format");
-            }
+/// Breaks a formatted TODO longer than `max` characters between the words of
+/// its note, continuing on comment lines at the same indentation.
+fn wrap_todo_line(prefix: &str, delimiter: &str, todo: &str, note: &str, max: usize) -> String {
+    let continuation = continuation_prefix(prefix, delimiter);
+
+    let head = todo.strip_suffix(note).unwrap_or(todo);
+    let mut lines: Vec<String> = vec![];
+    let mut current = format!("{}{}", prefix, head);
+    let mut is_empty = true;
+    for word in note.split_whitespace() {
+        if !is_empty && current.chars().count() + 1 + word.chars().count() > max {
+            lines.push(current);
+            current = continuation.clone();
+            is_empty = true;
         }
 
-        if let Some(project_keys) = &validation_rules.issue_project_keys {
-            if let Issue::ProjectKey { project_key, .. } = issue {
-                if !project_keys.contains(&project_key) {
-                    errors.push("Invalid project key");
-                }
-            }
+        if !is_empty {
+            current.push(' ');
         }
+        current.push_str(word);
+        is_empty = false;
     }
+    lines.push(current);
 
-    if validation_rules.require_due_dates && todo.metadata.due.is_none() {
-        errors.push("Missing due date");
+    lines.join("\n")
+}
+
+/// The line with the update applied, or `None` when it should be removed.
+fn rewrite_line(line: &str, update: TodoUpdate, config: &Config) -> Option<String> {
+    // The update's delimiter may differ from the line's, so prefer the raw TODO.
+    let leading_whitespace = line
+        .strip_suffix(&update.raw)
+        .or_else(|| line.split(&update.delimiter).next())
+        .unwrap_or("");
+
+    // Removing the start of a block comment spanning lines would break the
+    // code, so those only lose the TODO.
+    let unterminated_block = update.delimiter == "/*" && !update.note.ends_with("*/");
+    let action = match update.action {
+        UpdateAction::Delete if unterminated_block => UpdateAction::ConvertToNote,
+        action => action,
+    };
+
+    match action {
+        UpdateAction::Rewrite | UpdateAction::Edit => {
+            let location = update.location();
+            let todo = match action {
+                UpdateAction::Edit => format_todo_edit(
+                    &update.delimiter,
+                    &update.marker,
+                    &update.note,
+                    update.metadata,
+                    &update.path,
+                    config,
+                ),
+                _ => format_todo_update(
+                    &update.delimiter,
+                    &update.marker,
+                    &update.note,
+                    update.metadata,
+                    &update.path,
+                    config,
+                ),
+            };
+            let line = format!("{}{}", leading_whitespace, todo);
+
+            match config.max_line_length {
+                Some(max) if line.chars().count() > max => match config.long_lines {
+                    LongLines::Wrap => Some(wrap_todo_line(
+                        leading_whitespace,
+                        &update.delimiter,
+                        &todo,
+                        &update.note,
+                        max,
+                    )),
+                    LongLines::Warn => {
+                        eprintln!("{} is longer than {} characters", location, max);
+                        Some(line)
+                    }
+                },
+                _ => Some(line),
+            }
+        }
+        UpdateAction::Delete if leading_whitespace.trim().is_empty() => None,
+        UpdateAction::Delete => Some(leading_whitespace.trim_end().to_owned()),
+        UpdateAction::ConvertToNote => Some(format!(
+            "{}{} {}",
+            leading_whitespace, update.delimiter, update.note
+        )),
     }
+}
 
-    errors.into_iter().map(|s| s.to_owned()).collect()
+/// The lines `apply_updates` changed.
+#[derive(Default)]
+struct UpdateSummary {
+    files: usize,
+    changes: Vec<interactive::LineChange>,
+}
+
+impl UpdateSummary {
+    fn add_file(&mut self, changes: Vec<interactive::LineChange>) {
+        self.files += 1;
+        self.changes.extend(changes);
+    }
+
+    /// How many lines and files changed, after each change if `verbose`.
+    fn as_cli_result(&self, verbose: bool) -> String {
+        let mut parts: Vec<String> = vec![];
+        if verbose {
+            parts.extend(self.changes.iter().map(|change| change.as_diff()));
+        }
+        parts.push(format!(
+            "Changed {} in {}.",
+            count_noun(self.changes.len(), "line"),
+            count_noun(self.files, "file")
+        ));
+        parts.join("\n\n")
+    }
+}
+
+fn count_noun(count: usize, noun: &str) -> String {
+    if count == 1 {
+        format!("{} {}", count, noun)
+    } else {
+        format!("{} {}s", count, noun)
+    }
+}
+
+fn apply_updates(
+    updates: Vec<TodoUpdate>,
+    force: bool,
+    config: &Config,
+) -> Result<UpdateSummary, String> {
+    apply_updates_confirmed(updates, force, None, config)
+}
+
+/// A file with updates applied, not written yet.
+struct RewrittenFile {
+    encoding: FileEncoding,
+    /// The lines before, each with its line ending.
+    old_lines: Vec<String>,
+    contents: String,
+    /// The index of each line changed, and the lines replacing it, with
+    /// endings. Deleted lines are replaced by none.
+    edits: Vec<(usize, Vec<String>)>,
+    changes: Vec<interactive::LineChange>,
+    /// The updates skipped, as their line changed since the scan.
+    stale: Vec<TodoUpdate>,
+}
+
+/// Applies the updates to the file's lines, keyed by index. Another tool
+/// may be writing the file as it's read, so if some lines changed since the
+/// scan, the file is read again once, looking for their TODOs anywhere in
+/// it, before they're skipped.
+fn rewrite_file(
+    path: &Path,
+    updates: Vec<TodoUpdate>,
+    force: bool,
+    config: &Config,
+) -> Result<RewrittenFile, String> {
+    let rewritten = read_and_rewrite(path, updates.clone(), force, Some(RELOCATE_WINDOW), config)?;
+    if rewritten.stale.is_empty() {
+        return Ok(rewritten);
+    }
+
+    let rescanned = read_and_rewrite(path, updates, force, None, config)?;
+    for update in &rescanned.stale {
+        report_stale_update(update);
+    }
+    Ok(rescanned)
+}
+
+/// Reads the file and applies the updates, relocating them within `window`
+/// lines, or the whole file if `None`.
+fn read_and_rewrite(
+    path: &Path,
+    updates: Vec<TodoUpdate>,
+    force: bool,
+    window: Option<u64>,
+    config: &Config,
+) -> Result<RewrittenFile, String> {
+    let bytes = fs::read(path).map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+
+    let encoding = FileEncoding::detect(&bytes, path, config);
+    let old_contents = encoding.decode(&bytes);
+
+    let lines: Vec<&str> = old_contents
+        .split_inclusive('\n')
+        .map(|segment| segment.trim_end_matches(['\r', '\n']))
+        .collect();
+    let window = window.unwrap_or(lines.len() as u64);
+    let mut line_updates: HashMap<u64, TodoUpdate> = relocate_updates(&lines, updates, window)
+        .into_iter()
+        .map(|update| (update.line_number - 1, update))
+        .collect();
+
+    // Lines keep their endings, so CRLF files and the final newline
+    // survive the rewrite.
+    let newline = if old_contents.contains("\r\n") {
+        "\r\n"
+    } else {
+        "\n"
+    };
+    let mut contents = String::with_capacity(old_contents.len());
+    let mut old_lines: Vec<String> = vec![];
+    let mut edits: Vec<(usize, Vec<String>)> = vec![];
+    let mut changes: Vec<interactive::LineChange> = vec![];
+    let mut stale: Vec<TodoUpdate> = vec![];
+    // The lines continuing a rewritten TODO, which it's written anew with.
+    let mut continued = 0..0;
+    for (num, segment) in old_contents.split_inclusive('\n').enumerate() {
+        old_lines.push(segment.to_owned());
+        if continued.contains(&num) {
+            edits.push((num, vec![]));
+            continue;
+        }
+
+        let line = segment.trim_end_matches(['\r', '\n']);
+        let ending = &segment[line.len()..];
+        let update = line_updates.remove(&(num as u64));
+
+        // Wrapped TODOs are read, and so rewritten, as if on one line.
+        let texts = update
+            .as_ref()
+            .and_then(|update| {
+                let start = line
+                    .char_indices()
+                    .map(|(index, _)| index)
+                    .find(|&index| update.raw.starts_with(&line[index..]))?;
+                let (prefix, todo) = line.split_at(start);
+                Some(continuation_texts(
+                    prefix,
+                    &update.delimiter,
+                    todo,
+                    &lines[num + 1..],
+                ))
+            })
+            .unwrap_or_default();
+        let joined = if texts.is_empty() {
+            line.to_owned()
+        } else {
+            format!("{} {}", line, texts.join(" "))
+        };
+        let old = lines[num..=num + texts.len()].join("\n");
+
+        let new_line = match update {
+            Some(update) if !force && !joined.ends_with(&update.raw) => {
+                stale.push(update);
+                Some(line.to_owned())
+            }
+            Some(update) => {
+                let location = update.location();
+                let new_line = rewrite_line(&joined, update, config);
+                if new_line.as_deref() == Some(old.as_str()) {
+                    Some(line.to_owned())
+                } else {
+                    changes.push(interactive::LineChange {
+                        location,
+                        old,
+                        new: new_line.clone(),
+                    });
+                    let replacement = new_line
+                        .iter()
+                        .map(|new_line| format!("{}{}", new_line.replace('\n', newline), ending))
+                        .collect();
+                    edits.push((num, replacement));
+                    continued = num + 1..num + 1 + texts.len();
+                    new_line
+                }
+            }
+            None => Some(line.to_owned()),
+        };
+
+        if let Some(new_line) = new_line {
+            contents.push_str(&new_line.replace('\n', newline));
+            contents.push_str(ending);
+        }
+    }
+
+    Ok(RewrittenFile {
+        encoding,
+        old_lines,
+        contents,
+        edits,
+        changes,
+        stale,
+    })
+}
+
+/// Groups updates by file, in path order.
+fn updates_by_file(updates: Vec<TodoUpdate>) -> BTreeMap<PathBuf, Vec<TodoUpdate>> {
+    let mut file_updates: BTreeMap<PathBuf, Vec<TodoUpdate>> = BTreeMap::new();
+    for update in updates.into_iter() {
+        file_updates
+            .entry(update.path.clone())
+            .or_default()
+            .push(update);
+    }
+    file_updates
+}
+
+/// Like `apply_updates`, but asks `prompt`, if given, before writing each
+/// file. Files that fail to be read or written are reported after the rest
+/// are written.
+fn apply_updates_confirmed(
+    updates: Vec<TodoUpdate>,
+    force: bool,
+    mut prompt: Option<&mut interactive::FilePrompt>,
+    config: &Config,
+) -> Result<UpdateSummary, String> {
+    // Generated files would be overwritten by the next build.
+    let mut generated: BTreeMap<PathBuf, bool> = BTreeMap::new();
+    let updates: Vec<TodoUpdate> = updates
+        .into_iter()
+        .filter(|update| {
+            config.include_generated
+                || !*generated.entry(update.path.clone()).or_insert_with(|| {
+                    let is_generated = is_generated_file(&update.path);
+                    if is_generated {
+                        eprintln!(
+                            "Skipped {}, it's generated (use --include-generated)",
+                            update.path.display()
+                        );
+                    }
+                    is_generated
+                })
+        })
+        .collect();
+
+    let (notebook_updates, updates): (Vec<TodoUpdate>, Vec<TodoUpdate>) = updates
+        .into_iter()
+        .partition(|update| update.cell.is_some());
+
+    let mut summary = UpdateSummary::default();
+    let mut errors: Vec<String> = containers::apply_notebook_updates(
+        notebook_updates,
+        force,
+        prompt.as_deref_mut(),
+        &mut summary,
+        config,
+    );
+
+    // Sorted, so interactive prompts go through files in order.
+    for (path, line_updates) in updates_by_file(updates) {
+        let rewritten = match rewrite_file(&path, line_updates, force, config) {
+            Ok(rewritten) => rewritten,
+            Err(e) => {
+                errors.push(e);
+                continue;
+            }
+        };
+
+        if rewritten.changes.is_empty() {
+            continue;
+        }
+        if let Some(prompt) = prompt.as_deref_mut() {
+            if !prompt.confirm(&path, &rewritten.changes) {
+                continue;
+            }
+        }
+
+        let written = write_replacing(&path, &rewritten.encoding.encode(&rewritten.contents));
+        match written {
+            Ok(()) => summary.add_file(rewritten.changes),
+            Err(e) => errors.push(format!("Failed to write {}: {}", path.display(), e)),
+        }
+    }
+
+    if errors.is_empty() {
+        Ok(summary)
+    } else {
+        Err(errors.join("\n"))
+    }
+}
+
+/// Replaces the file's contents through a temporary file in the same
+/// directory, so a crash or another writer never sees it half written. The
+/// file keeps its permissions.
+fn write_replacing(path: &Path, contents: &[u8]) -> std::io::Result<()> {
+    let dir = match path.parent() {
+        Some(dir) if !dir.as_os_str().is_empty() => dir,
+        _ => Path::new("."),
+    };
+    let mut temp_file = tempfile::NamedTempFile::new_in(dir)?;
+    temp_file.write_all(contents)?;
+    if let Ok(metadata) = fs::metadata(path) {
+        temp_file
+            .as_file()
+            .set_permissions(metadata.permissions())?;
+    }
+    temp_file.persist(path).map_err(|e| e.error)?;
+    Ok(())
+}
+
+/// The update fixing the TODO's format, metadata order, and a malformed
+/// issue, if any of them need fixing.
+fn fix_update(todo: &Todo, config: &Config) -> Option<TodoUpdate> {
+    let mut metadata = todo.metadata.clone();
+    if metadata.issue.is_none() {
+        if let Some(index) = metadata
+            .extra
+            .iter()
+            .position(|token| parse_malformed_issue(token).is_some())
+        {
+            metadata.issue = parse_malformed_issue(&metadata.extra.remove(index));
+        }
+    }
+
+    let formatted = format_todo_update(
+        &todo.delimiter,
+        &todo.marker,
+        &todo.note,
+        metadata.clone(),
+        &todo.path,
+        config,
+    );
+    (formatted != todo.raw).then(|| TodoUpdate {
+        metadata,
+        note: todo.note.clone(),
+        path: todo.path.clone(),
+        line_number: todo.line_number,
+        cell: todo.cell,
+        delimiter: todo.delimiter.clone(),
+        marker: todo.marker.clone(),
+        raw: todo.raw.clone(),
+        action: UpdateAction::Rewrite,
+    })
+}
+
+/// A unified diff of the updates, which `git apply` applies, without
+/// touching any file. Notebooks are left out, as their TODOs are in JSON.
+fn updates_patch(updates: Vec<TodoUpdate>, config: &Config) -> Result<String, String> {
+    let updates: Vec<TodoUpdate> = updates
+        .into_iter()
+        .filter(|update| update.cell.is_none())
+        .collect();
+
+    let mut diffs: Vec<String> = vec![];
+    for (path, line_updates) in updates_by_file(updates) {
+        let rewritten = rewrite_file(&path, line_updates, false, config)?;
+        if !rewritten.edits.is_empty() {
+            diffs.push(patch::unified_diff(
+                &path,
+                &rewritten.old_lines,
+                &rewritten.edits,
+            ));
+        }
+    }
+
+    Ok(diffs.concat())
+}
+
+enum DigestFormat {
+    Markdown,
+    Slack,
+    Text,
+}
+
+impl DigestFormat {
+    fn from_str(s: &str) -> Option<Self> {
+        match s {
+            "markdown" => Some(DigestFormat::Markdown),
+            "slack" => Some(DigestFormat::Slack),
+            "text" => Some(DigestFormat::Text),
+            _ => None,
+        }
+    }
+
+    fn extension(&self) -> &str {
+        match self {
+            DigestFormat::Markdown => "md",
+            DigestFormat::Slack | DigestFormat::Text => "txt",
+        }
+    }
+
+    /// `@assignee`, linked to their profile or mentioned where the format
+    /// and config allow.
+    fn mention(&self, assignee: &str, config: &Config) -> String {
+        let profile = config.assignees.get(assignee);
+        let url = profile.and_then(|profile| profile.url.as_ref());
+        let slack = profile.and_then(|profile| profile.slack.as_ref());
+
+        match (self, url, slack) {
+            (DigestFormat::Markdown, Some(url), _) => format!("[@{}]({})", assignee, url),
+            (DigestFormat::Slack, _, Some(slack)) => format!("<@{}>", slack),
+            (DigestFormat::Slack, Some(url), None) => {
+                format!("<{}|@{}>", url, escape_slack(assignee))
+            }
+            _ => format!("@{}", assignee),
+        }
+    }
+}
+
+/// Escapes the characters Slack reads as markup, so notes can't mention
+/// people or link anywhere.
+fn escape_slack(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+}
+
+/// The path of an assignee's digest under `dir`. Assignees come from the
+/// scanned code, so names that could point outside `dir` are rejected.
+fn digest_path(dir: &Path, assignee: &str, extension: &str) -> Result<PathBuf, String> {
+    let unsafe_name = assignee.contains(['/', '\\', ':']) || assignee.contains("..");
+    let path = dir.join(format!("{}.{}", assignee, extension));
+    if unsafe_name || path.parent() != Some(dir) {
+        return Err(format!(
+            "Can't write a digest for \"{}\", the assignee isn't a safe file name",
+            assignee
+        ));
+    }
+
+    Ok(path)
+}
+
+fn make_digest(
+    assignee: &str,
+    todos: &[&Todo],
+    format: &DigestFormat,
+    permalinks: &Option<git::PermalinkBase>,
+    config: &Config,
+) -> String {
+    let mut todos = todos.to_vec();
+    todos.sort_by_key(|todo| (todo.due().is_none(), todo.due().to_owned()));
+
+    let overdue_count = todos.iter().filter(|todo| is_overdue(todo)).count();
+
+    let entries: Vec<String> = todos
+        .iter()
+        .map(|todo| {
+            let location = todo.location();
+            let link = permalinks
+                .as_ref()
+                .and_then(|base| base.link(&todo.path, todo.file_position().0));
+
+            let mut when = todo.due().to_owned().unwrap_or("someday".to_owned());
+            if is_overdue(todo) {
+                when.push_str(" (overdue)");
+            }
+
+            let mut note = get_printable_note(&todo.delimiter, &todo.note)
+                .trim()
+                .to_owned();
+            if let Some(issue) = &todo.metadata.issue {
+                note = format!("{} ({})", note, issue.as_display_string());
+            }
+
+            match format {
+                DigestFormat::Markdown => {
+                    let location = match link {
+                        Some(link) => format!("[{}]({})", location, link),
+                        None => format!("`{}`", location),
+                    };
+                    format!("- **{}**: {} ({})", when, note, location)
+                }
+                DigestFormat::Slack => {
+                    let location = match link {
+                        Some(link) => format!("<{}|{}>", link, escape_slack(&location)),
+                        None => format!("`{}`", escape_slack(&location)),
+                    };
+                    format!("• *{}*: {} ({})", when, escape_slack(&note), location)
+                }
+                DigestFormat::Text => {
+                    format!("- {}: {}\n  {}", when, note, link.unwrap_or(location))
+                }
+            }
+        })
+        .collect();
+
+    let summary = format!("{} TODOs, {} overdue.", todos.len(), overdue_count);
+    let mention = format.mention(assignee, config);
+    match format {
+        DigestFormat::Markdown => format!(
+            "## TODOs for {}\n\n{}\n\n{}\n",
+            mention,
+            summary,
+            entries.join("\n")
+        ),
+        DigestFormat::Slack => format!(
+            "*TODOs for {}*\n\n{}\n\n{}\n",
+            mention,
+            summary,
+            entries.join("\n")
+        ),
+        DigestFormat::Text => format!(
+            "TODOs for {}\n\n{}\n\n{}\n",
+            mention,
+            summary,
+            entries.join("\n")
+        ),
+    }
+}
+
+impl TodoUpdate {
+    fn location(&self) -> String {
+        format_location(&self.path, self.cell, self.line_number)
+    }
+}
+
+/// What to do with the TODO's line.
+#[derive(Clone, Copy, PartialEq)]
+enum UpdateAction {
+    /// Writes the TODO with the update's metadata and note, as `format` would.
+    Rewrite,
+    /// Writes the TODO with the update's metadata and note, keeping the
+    /// metadata that didn't change as written.
+    Edit,
+    /// Removes the comment, and the line when nothing else is on it.
+    Delete,
+    /// Keeps the comment text, dropping the TODO keyword and metadata.
+    ConvertToNote,
+}
+
+enum RemovalStrategy {
+    Delete,
+    StripMetadata,
+    ConvertToNote,
+}
+
+impl RemovalStrategy {
+    fn from_str(s: &str) -> Option<Self> {
+        match s {
+            "delete" => Some(RemovalStrategy::Delete),
+            "strip-metadata" => Some(RemovalStrategy::StripMetadata),
+            "convert-to-note" => Some(RemovalStrategy::ConvertToNote),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Clone)]
+struct TodoUpdate {
+    path: PathBuf,
+    line_number: u64,
+    cell: Option<usize>,
+    delimiter: String,
+    marker: String,
+    note: String,
+    metadata: TodoMetadata,
+    raw: String,
+    action: UpdateAction,
+}
+
+struct ValidationErrorEntry {
+    todo: Todo,
+    errors: Vec<String>,
+}
+
+impl ValidationErrorEntry {
+    fn as_cli_result(&self) -> String {
+        let error_list = self
+            .errors
+            .iter()
+            .map(|s| format!("\t- {}", s))
+            .collect::<Vec<String>>()
+            .join("\n");
+
+        format!("{}\n{}", self.todo.as_search_result(), error_list)
+    }
+}
+
+/// How many times each rule reported an error, the most reported first.
+fn rule_counts<'a>(errors: impl Iterator<Item = &'a String>) -> String {
+    let mut counts: BTreeMap<&str, usize> = BTreeMap::new();
+    for error in errors {
+        *counts.entry(rules::name_of(error)).or_default() += 1;
+    }
+
+    let mut counts: Vec<(&str, usize)> = counts.into_iter().collect();
+    counts.sort_by_key(|(_, count)| Reverse(*count));
+    let width = counts.iter().map(|(name, _)| name.len()).max().unwrap_or(0);
+    counts
+        .into_iter()
+        .map(|(name, count)| format!("{:<width$}  {}", name, count, width = width))
+        .collect::<Vec<String>>()
+        .join("\n")
+}
+
+/// The entries separated by blank lines, only the first `max` of them if
+/// given, followed by how many more there are.
+fn violation_list(entries: impl Iterator<Item = String>, max: Option<usize>) -> String {
+    let entries: Vec<String> = entries.collect();
+    let shown = max.unwrap_or(entries.len()).min(entries.len());
+    let mut list = entries[..shown].join("\n\n");
+    if shown < entries.len() {
+        if shown > 0 {
+            list.push_str("\n\n");
+        }
+        list.push_str(&format!("and {} more", entries.len() - shown));
+    }
+    list
+}
+
+struct FileValidationErrorEntry {
+    path: PathBuf,
+    errors: Vec<String>,
+}
+
+impl FileValidationErrorEntry {
+    fn as_cli_result(&self) -> String {
+        let error_list = self
+            .errors
+            .iter()
+            .map(|s| format!("\t- {}", s))
+            .collect::<Vec<String>>()
+            .join("\n");
+
+        format!("{}\n{}", self.path.display(), error_list)
+    }
+}
+
+struct ValidationRules {
+    require_assignees: bool,
+    require_issues: bool,
+    require_due_dates: bool,
+    /// The lowest priority held to `--require-due-for-priority`.
+    due_for_priority: Option<u8>,
+    forbid_bare: bool,
+    max_todos_per_file: Option<usize>,
+    max_snooze: Option<(String, Duration)>,
+
+    allowed_assignees: Option<Vec<String>>,
+    single_assignee: bool,
+    forbid_unknown_metadata: bool,
+    issue_format: Option<IssueFormat>,
+    issue_project_keys: Option<Vec<String>>,
+
+    scopes: Vec<(PathFilter, ScopedRules)>,
+}
+
+impl ValidationRules {
+    /// The setting for TODOs in `path`, from the last scoped rule matching it
+    /// which sets one, otherwise the flag.
+    fn scoped(
+        &self,
+        path: &Path,
+        flag: bool,
+        setting: impl Fn(&ScopedRules) -> Option<bool>,
+    ) -> bool {
+        self.scopes
+            .iter()
+            .rev()
+            .filter(|(filter, _)| filter.is_match(path))
+            .find_map(|(_, rules)| setting(rules))
+            .unwrap_or(flag)
+    }
+}
+
+fn get_validation_errors(
+    todo: &Todo,
+    validation_rules: &ValidationRules,
+    config: &Config,
+) -> Vec<String> {
+    let mut errors: Vec<String> = vec![];
+
+    let formatted = format_todo_update(
+        &todo.delimiter,
+        &todo.marker,
+        &todo.note,
+        todo.metadata.to_owned(),
+        &todo.path,
+        config,
+    );
+    if todo.has_empty_metadata() {
+        errors.push("Empty metadata (empty-metadata)".to_owned());
+    } else if todo.raw != formatted {
+        let as_written = format_todo(
+            &todo.delimiter,
+            &todo.marker,
+            &todo.note,
+            todo.metadata.to_owned(),
+            &MetaOrder::Preserve(PreserveOrder::Preserve),
+            config.todo_style(&todo.path),
+        );
+
+        if todo.raw == as_written {
+            errors.push("Invalid metadata order".to_owned());
+        } else {
+            errors.push("Invalid format".to_owned());
+        }
+    }
+
+    for token in &todo.metadata.extra {
+        // Status tokens, like the closed marker of `sync --mark-closed`.
+        if token.starts_with("status:") || token == sync::closed_marker(config) {
+            continue;
+        }
+        if is_duplicate_token(token, config) {
+            errors.push(format!(
+                "Duplicate metadata \"{}\" (duplicate-metadata)",
+                token
+            ));
+            continue;
+        }
+
+        match parse_malformed_issue(token) {
+            Some(issue) => errors.push(format!(
+                "Malformed issue \"{}\", should be \"{}\" (issue-case)",
+                token,
+                issue.as_string()
+            )),
+            None if validation_rules.forbid_unknown_metadata => {
+                errors.push(format!("Unknown metadata \"{}\"", token))
+            }
+            None => {}
+        }
+    }
+
+    // Markers like `NOTE` may not expect any metadata.
+    let expects_metadata = config
+        .marker(&todo.marker)
+        .is_none_or(|marker| marker.metadata);
+    let require_assignees = expects_metadata
+        && validation_rules.scoped(&todo.path, validation_rules.require_assignees, |r| {
+            r.require_assignees
+        });
+    let require_issues = expects_metadata
+        && validation_rules.scoped(&todo.path, validation_rules.require_issues, |r| {
+            r.require_issues
+        });
+    let require_due_dates = expects_metadata
+        && validation_rules.scoped(&todo.path, validation_rules.require_due_dates, |r| {
+            r.require_due_dates
+        });
+    let forbid_bare = expects_metadata
+        && validation_rules.scoped(&todo.path, validation_rules.forbid_bare, |r| r.forbid_bare);
+
+    let prioritized = expects_metadata
+        && validation_rules
+            .due_for_priority
+            .zip(todo.metadata.priority)
+            .is_some_and(|(lowest, priority)| priority <= lowest);
+
+    if require_assignees && todo.metadata.assignees.is_empty() {
+        errors.push("Missing assignee".to_owned());
+    } else if prioritized && todo.metadata.assignees.is_empty() {
+        errors.push(format!(
+            "Missing assignee (required for p{})",
+            todo.metadata.priority.unwrap_or_default()
+        ));
+    }
+
+    if let Some(allowed) = &validation_rules.allowed_assignees {
+        if todo
+            .metadata
+            .assignees
+            .iter()
+            .any(|assignee| !allowed.contains(assignee))
+        {
+            errors.push("Invalid assignee".to_owned());
+        }
+    }
+
+    if validation_rules.single_assignee && todo.metadata.assignees.len() > 1 {
+        errors.push(format!(
+            "Assigned to {} people (single-assignee)",
+            todo.metadata.assignees.len()
+        ));
+    }
+
+    if require_issues && todo.metadata.issue.is_none() {
+        errors.push("Missing issue".to_owned());
+    }
+
+    if let Some(issue) = todo.metadata.issue.to_owned() {
+        if let Some(format) = &validation_rules.issue_format {
+            let valid_format = match format {
+                IssueFormat::Numbered => issue.is_numbered(),
+                IssueFormat::ProjectKey => issue.project_key().is_some(),
+            };
+
+            if !valid_format {
+                errors.push("Invalid issue format".to_owned());
+            }
+        }
+
+        if let Some(project_keys) = &validation_rules.issue_project_keys {
+            if let Some(project_key) = issue.project_key() {
+                if !project_keys.contains(&project_key) {
+                    errors.push("Invalid project key".to_owned());
+                }
+            }
+        }
+    }
+
+    if let Some(invalid_due) = &todo.metadata.invalid_due {
+        errors.push(format!(
+            "Invalid due date \"{}\" (invalid-due-date)",
+            invalid_due
+        ));
+    } else if require_due_dates && todo.metadata.due.is_none() {
+        errors.push("Missing due date".to_owned());
+    } else if prioritized && todo.metadata.due.is_none() {
+        errors.push(format!(
+            "Missing due date (required for p{})",
+            todo.metadata.priority.unwrap_or_default()
+        ));
+    }
+
+    if forbid_bare
+        && todo.metadata.assignees.is_empty()
+        && todo.metadata.issue.is_none()
+        && todo.metadata.due.is_none()
+    {
+        errors.push("Missing metadata".to_owned());
+    }
+
+    if let Some(every) = &todo.metadata.every {
+        match history::parse_period(every) {
+            Some(period) => {
+                let last_touched = todo
+                    .cell
+                    .is_none()
+                    .then(|| git::blame_date(&todo.path, todo.line_number))
+                    .flatten();
+
+                if let Some(last_touched) = last_touched {
+                    let due = last_touched.checked_add_signed(period);
+                    if due.is_some_and(|due| due < Local::now().date_naive()) {
+                        errors.push(format!(
+                            "Revisit overdue (every {}, last touched {})",
+                            every, last_touched
+                        ));
+                    }
+                }
+            }
+            None => errors.push("Invalid recurrence".to_owned()),
+        }
+    }
+
+    if let Some((period, max)) = &validation_rules.max_snooze {
+        let snoozed_until = todo.metadata.snooze.to_owned().and_then(parse_due_date);
+        if let Some(until) = snoozed_until {
+            let latest = Local::now().date_naive().checked_add_signed(*max);
+            if latest.is_some_and(|latest| until > latest) {
+                errors.push(format!(
+                    "Snooze too long (until {}, at most {})",
+                    until, period
+                ));
+            }
+        }
+    }
+
+    errors
+}
+
+/// Longer lines are skipped, as they're most likely minified or generated
+/// code rather than comments anyone wrote.
+const MAX_LINE_LENGTH: usize = 4096;
+
+/// More TODOs than this in one file are ignored, so a hostile or generated
+/// file can't slow every run down.
+const MAX_TODOS_PER_FILE: usize = 10_000;
+
+fn parse_todo_line(
+    matcher: &RegexMatcher,
+    config: &Config,
+    path: &Path,
+    line_number: u64,
+    line: &str,
+) -> Option<Todo> {
+    if line.len() > MAX_LINE_LENGTH {
+        return None;
+    }
+
+    // Lines from files with CRLF endings keep the `\r`, which isn't part of
+    // the note.
+    let line = line.trim_end_matches(['\r', '\n']);
+    let mut captures = matcher.new_captures().ok()?;
+
+    let did_match = matcher.captures(line.as_bytes(), &mut captures).ok()?;
+    if !did_match {
+        return None;
+    }
+
+    let delimiter_match = captures.get(1)?;
+    let raw = line[delimiter_match.start()..].to_owned();
+    let delimiter = line[delimiter_match].to_string();
+
+    // The keyword follows the delimiter, and is written as in the config
+    // when it's a marker's.
+    let keyword_start = line[delimiter_match.end()..].trim_start();
+    let keyword: String = keyword_start
+        .chars()
+        .take_while(|c| c.is_ascii_alphanumeric() || *c == '_')
+        .collect();
+    let configured = config.marker(&keyword);
+    if configured.is_some_and(|marker| !marker.is_marked(&keyword_start[keyword.len()..])) {
+        return None;
+    }
+    let marker = configured.map_or_else(
+        || keyword.to_ascii_uppercase(),
+        |marker| marker.keyword.clone(),
+    );
+
+    let meta_capture = captures.get(2);
+    let mut meta = meta_capture.map(|meta_match| line[meta_match].to_string());
+
+    let mut note = line[captures.get(3)?].to_string();
+    if meta.is_none() {
+        if let Some((alternate_meta, rest)) = parse_alternate_metadata(&note, config) {
+            meta = Some(alternate_meta);
+            note = rest;
+        }
+    }
+
+    let metadata = if let Some(meta_str) = meta.to_owned() {
+        TodoMetadata::from_string(meta_str, config)
+    } else {
+        TodoMetadata::empty()
+    };
+
+    Some(Todo {
+        root: None,
+        inherited_due: None,
+        generated: false,
+        raw,
+        delimiter,
+        marker,
+        path: path.to_path_buf(),
+        line_number,
+        column: delimiter_match.start() as u64 + 1,
+        cell: None,
+        note,
+        meta,
+        metadata,
+    })
+}
+
+/// Metadata written in the styles enabled in the config, like
+/// `[ABC-123] (alice): note` or `note (due 2025-01-01)`, as the metadata
+/// string it'd be canonically and the rest of the note.
+fn parse_alternate_metadata(note: &str, config: &Config) -> Option<(String, String)> {
+    // Tokens that mean nothing alone are taken as the names they're
+    // written as, like `alice` in `(alice)`.
+    let known = |token: &str| {
+        TodoMetadata::from_string(token.to_owned(), config)
+            .extra
+            .is_empty()
+    };
+
+    if config.bracket_metadata {
+        let bracket = Regex::new(r"^\[([^\]]+)\](?:\s*\(([^)]+)\))?:?\s+(.+)$").ok()?;
+        if let Some(captures) = bracket.captures(note) {
+            let mut tokens: Vec<String> = captures[1]
+                .split(',')
+                .map(|t| t.trim().to_owned())
+                .collect();
+            for token in captures
+                .get(2)
+                .into_iter()
+                .flat_map(|m| m.as_str().split(','))
+            {
+                let token = token.trim();
+                if !known(token) && is_valid_assignee(token) {
+                    tokens.push(format!("@{}", token));
+                } else {
+                    tokens.push(token.to_owned());
+                }
+            }
+            return Some((tokens.join(", "), captures[3].to_owned()));
+        }
+    }
+
+    if config.trailing_metadata {
+        let trailing = Regex::new(r"^(.+?)\s*\(([^()]+)\)(\s*\*/)?\s*$").ok()?;
+        let captures = trailing.captures(note)?;
+        let key_value = Regex::new(r"^(due|every|id)\s+(\S+)$").ok()?;
+        let tokens: Vec<String> = captures[2]
+            .split(',')
+            .map(|token| key_value.replace(token.trim(), "$1:$2").into_owned())
+            .collect();
+
+        // Only when it's all metadata, so parenthetical remarks stay notes.
+        if tokens.iter().all(|token| known(token)) {
+            let rest = format!(
+                "{}{}",
+                &captures[1],
+                captures.get(3).map(|m| m.as_str()).unwrap_or("")
+            );
+            return Some((tokens.join(", "), rest));
+        }
+    }
+
+    None
+}
+
+fn is_loop_error(err: &ignore::Error) -> bool {
+    match err {
+        ignore::Error::Loop { .. } => true,
+        ignore::Error::WithPath { err, .. } | ignore::Error::WithDepth { err, .. } => {
+            is_loop_error(err)
+        }
+        _ => false,
+    }
+}
+
+fn get_file_validation_errors(
+    todos: &[Todo],
+    validation_rules: &ValidationRules,
+) -> Vec<FileValidationErrorEntry> {
+    let mut counts: HashMap<&PathBuf, usize> = HashMap::new();
+    for todo in todos {
+        *counts.entry(&todo.path).or_default() += 1;
+    }
+
+    let mut entries: Vec<FileValidationErrorEntry> = counts
+        .into_iter()
+        .filter_map(|(path, count)| {
+            let mut errors = vec![];
+
+            if let Some(max) = validation_rules.max_todos_per_file {
+                if count > max {
+                    errors.push(format!("Too many TODOs ({}, at most {})", count, max));
+                }
+            }
+
+            if errors.is_empty() {
+                None
+            } else {
+                Some(FileValidationErrorEntry {
+                    path: path.to_owned(),
+                    errors,
+                })
+            }
+        })
+        .collect();
+
+    entries.sort_by(|a, b| a.path.cmp(&b.path));
+    entries
 }
 
 fn cli_error(error: String) -> ! {
     eprintln!("{}", error);
+    stats::finish_command();
     std::process::exit(1);
 }
 
-fn main() -> Result<(), ()> {
-    let matcher = RegexMatcher::new(r"(?m)^\W*(//|/\*|#) (?:(?i)TODO)(?:\((.+)\))?:? (.+?)$")
-        .map_err(|e| cli_error(format!("{}", e)))?;
+/// A path to scan, optionally labeled like `backend=services/backend`.
+struct ScanRoot {
+    label: Option<String>,
+    path: String,
+}
+
+impl ScanRoot {
+    fn parse(arg: &str) -> Self {
+        let labeled = arg.split_once('=').filter(|(label, path)| {
+            !label.is_empty()
+                && !path.is_empty()
+                && label
+                    .chars()
+                    .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+        });
+
+        match labeled {
+            Some((label, path)) => ScanRoot {
+                label: Some(label.to_owned()),
+                path: path.to_owned(),
+            },
+            None => ScanRoot {
+                label: None,
+                path: arg.to_owned(),
+            },
+        }
+    }
+}
+
+struct ScanOptions {
+    roots: Vec<ScanRoot>,
+    follow_links: bool,
+    path_filter: Option<PathFilter>,
+    file_types: Option<FileTypes>,
+    /// Scans the files inside `.zip` and `.tar(.gz)` archives, rather than
+    /// the archives themselves.
+    scan_archives: bool,
+    /// Applies the global gitignore, like git.
+    git_global: bool,
+    /// Applies `.git/info/exclude`, like git.
+    git_exclude: bool,
+    /// Lists files with `git ls-files` rather than walking directories,
+    /// including untracked ones when `Some(true)`.
+    vcs_files: Option<bool>,
+}
+
+impl ScanOptions {
+    /// The label of the most specific root containing `path`.
+    fn root_label(&self, path: &Path) -> Option<String> {
+        self.roots
+            .iter()
+            .filter(|root| root.label.is_some() && path.starts_with(&root.path))
+            .max_by_key(|root| root.path.len())
+            .and_then(|root| root.label.clone())
+    }
+}
+
+/// Finds the lines with a keyword, which are then matched against the full
+/// pattern. Searching for plain words is much faster than for the pattern,
+/// whose leading `^\W*` and case insensitive keyword defeat the regex
+/// engine's own literal optimizations. Built once per set of keywords, since
+/// `agenda` scans each repository with its own markers.
+fn prefilter(config: &Config) -> &'static RegexMatcher {
+    static PREFILTERS: OnceLock<Mutex<HashMap<Vec<String>, &'static RegexMatcher>>> =
+        OnceLock::new();
+    let keywords = markers::scanned_keywords(&config.markers);
+    let mut prefilters = PREFILTERS.get_or_init(Default::default).lock().unwrap();
+    prefilters
+        .entry(keywords.iter().map(|&keyword| keyword.to_owned()).collect())
+        .or_insert_with(|| {
+            Box::leak(Box::new(
+                RegexMatcherBuilder::new()
+                    .case_insensitive(true)
+                    .build_literals(&keywords)
+                    .unwrap_or_else(|e| cli_error(format!("{}", e))),
+            ))
+        })
+}
+
+/// Searches line by line, giving up on binary files at the first NUL byte
+/// rather than scanning them to the end.
+fn build_searcher(encoding: Option<grep::searcher::Encoding>) -> Searcher {
+    SearcherBuilder::new()
+        .line_terminator(LineTerminator::byte(b'\n'))
+        .binary_detection(BinaryDetection::quit(b'\x00'))
+        .encoding(encoding)
+        .build()
+}
+
+fn scan_file(
+    matcher: &RegexMatcher,
+    config: &Config,
+    searcher: &mut Searcher,
+    path: &Path,
+) -> Result<Vec<Todo>, String> {
+    let extension = path.extension().and_then(|e| e.to_str()).unwrap_or("");
+    let container_todos = if extension == "ipynb" {
+        Some(containers::scan_notebook(matcher, config, path))
+    } else if containers::is_markdown(path) {
+        Some(containers::scan_markdown(matcher, config, path))
+    } else if extension == "vue" || extension == "svelte" {
+        Some(containers::scan_script_blocks(matcher, config, path))
+    } else {
+        None
+    };
+
+    let mut todos = match container_todos {
+        Some(todos) => {
+            let todos = todos?;
+            // Containers are parsed line by line rather than searched.
+            stats::count(&stats::REGEX_MATCHES, todos.len());
+            todos
+        }
+        None => search_file(matcher, config, searcher, path)?,
+    };
+
+    // Only read the whole file for the lines after TODOs when it has any.
+    if todos.iter().any(|todo| todo.cell.is_none()) {
+        let bytes =
+            fs::read(path).map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+        let contents = FileEncoding::detect(&bytes, path, config).decode(&bytes);
+        let lines: Vec<&str> = contents.lines().collect();
+        join_continuations(&mut todos, &lines);
+    }
+    Ok(todos)
+}
+
+/// Searches a file that isn't a container for TODOs.
+fn search_file(
+    matcher: &RegexMatcher,
+    config: &Config,
+    searcher: &mut Searcher,
+    path: &Path,
+) -> Result<Vec<Todo>, String> {
+    let mut todos: Vec<Todo> = vec![];
+    let mut file_searcher = configured_encoding(path, config)
+        .and_then(|encoding| grep::searcher::Encoding::new(encoding.name()).ok())
+        .map(|encoding| build_searcher(Some(encoding)));
+
+    let search_result = file_searcher.as_mut().unwrap_or(searcher).search_path(
+        prefilter(config),
+        path,
+        UTF8(|line_number, line| {
+            stats::count(&stats::REGEX_MATCHES, 1);
+            // The prefilter also passes lines only mentioning a marker, like
+            // `.done(` in minified code, which aren't worth a warning.
+            if line.len() > MAX_LINE_LENGTH {
+                if matcher.is_match(line.as_bytes()).unwrap_or(false) {
+                    eprintln!(
+                        "Skipped {}, the line is longer than {} bytes",
+                        format_location(path, None, line_number),
+                        MAX_LINE_LENGTH
+                    );
+                }
+            } else if let Some(todo) = parse_todo_line(matcher, config, path, line_number, line) {
+                todos.push(todo);
+            }
+
+            // One past the limit, so the caller can tell it was reached.
+            Ok(todos.len() <= MAX_TODOS_PER_FILE)
+        }),
+    );
+
+    search_result.map_err(|err| format!("{}", err))?;
+    Ok(todos)
+}
+
+/// Ignore files in gitignore syntax excluding files from scans only, like
+/// test fixtures, on top of `.gitignore` and `.ignore`.
+const CODOIGNORE_FILENAME: &str = ".codoignore";
+
+/// The files git lists under each root, as paths under the root like the
+/// walk finds them. Like the walk, roots are scanned even if ignored.
+fn list_vcs_files(options: &ScanOptions, untracked: bool) -> Vec<(PathBuf, Option<fs::Metadata>)> {
+    let mut files: Vec<(PathBuf, Option<fs::Metadata>)> = vec![];
+    let mut visited: HashSet<PathBuf> = HashSet::new();
+
+    for root in &options.roots {
+        let mut listed = git::ls_files(&root.path, untracked)
+            .unwrap_or_else(|e| cli_error(format!("--vcs-files needs a git repository, {}", e)));
+        // Untracked files come after tracked ones, so all are put in order.
+        listed.sort();
+        let ignores = ignores::custom_ignores(&listed);
+        let base: PathBuf = Path::new(&root.path)
+            .components()
+            .filter(|component| !matches!(component, std::path::Component::CurDir))
+            .collect();
+
+        for listed_path in listed {
+            stats::count(&stats::FILES_WALKED, 1);
+            let path = match listed_path.strip_prefix(&base) {
+                Ok(relative) if relative.as_os_str().is_empty() => PathBuf::from(&root.path),
+                Ok(relative) => Path::new(&root.path).join(relative),
+                Err(_) => listed_path.clone(),
+            };
+            if path != Path::new(&root.path) && ignores::is_custom_ignored(&ignores, &listed_path) {
+                stats::count(&stats::FILES_SKIPPED, 1);
+                continue;
+            }
+
+            // Deleted files are still listed until the deletion is staged,
+            // and submodules are listed as directories.
+            let metadata = fs::metadata(&path).ok();
+            let is_file = metadata.as_ref().is_some_and(|metadata| metadata.is_file());
+            let filtered = options
+                .path_filter
+                .as_ref()
+                .is_some_and(|path_filter| !path_filter.is_match(&path))
+                || options
+                    .file_types
+                    .as_ref()
+                    .is_some_and(|file_types| !file_types.is_match(&path));
+            let canonical_path = path.canonicalize().unwrap_or(path.clone());
+            if !is_file || filtered || !visited.insert(canonical_path) {
+                stats::count(&stats::FILES_SKIPPED, 1);
+                continue;
+            }
+
+            files.push((path, metadata));
+        }
+    }
+
+    files
+}
+
+/// The files to scan under the roots, each once, with their metadata.
+fn walk_files(options: &ScanOptions) -> Vec<(PathBuf, Option<fs::Metadata>)> {
+    if let Some(untracked) = options.vcs_files {
+        return list_vcs_files(options, untracked);
+    }
+
+    let mut files: Vec<(PathBuf, Option<fs::Metadata>)> = vec![];
+
+    let mut walk_builder = ignore::WalkBuilder::new(&options.roots[0].path);
+    for root in &options.roots[1..] {
+        walk_builder.add(&root.path);
+    }
+
+    walk_builder.follow_links(options.follow_links);
+    walk_builder.add_custom_ignore_filename(CODOIGNORE_FILENAME);
+    walk_builder.git_global(options.git_global);
+    walk_builder.git_exclude(options.git_exclude);
+    walk_builder.sort_by_file_name(|a, b| a.cmp(b));
+
+    let walk = walk_builder.build();
+
+    // Overlapping paths and symlinks can lead to the same file more than once.
+    let mut visited: HashSet<PathBuf> = HashSet::new();
+
+    for result in walk {
+        match result {
+            Ok(entry) => {
+                let is_file = entry
+                    .file_type()
+                    .and_then(|f| if f.is_file() { Some(()) } else { None });
+
+                if is_file.is_none() {
+                    continue;
+                }
+
+                stats::count(&stats::FILES_WALKED, 1);
+                let path = entry.path();
+                if let Some(path_filter) = &options.path_filter {
+                    if !path_filter.is_match(path) {
+                        stats::count(&stats::FILES_SKIPPED, 1);
+                        continue;
+                    }
+                }
+                if let Some(file_types) = &options.file_types {
+                    if !file_types.is_match(path) {
+                        stats::count(&stats::FILES_SKIPPED, 1);
+                        continue;
+                    }
+                }
+
+                let canonical_path = path.canonicalize().unwrap_or(path.to_path_buf());
+                if !visited.insert(canonical_path) {
+                    stats::count(&stats::FILES_SKIPPED, 1);
+                    continue;
+                }
+
+                files.push((path.to_path_buf(), entry.metadata().ok()));
+            }
+            Err(err) if is_loop_error(&err) => continue,
+            Err(err) => {
+                cli_error(format!("{}", err));
+            }
+        }
+    }
+
+    files
+}
+
+/// Scans one file found by `walk_files`, labeling its TODOs with their root.
+fn scan_walked_file(
+    matcher: &RegexMatcher,
+    config: &Config,
+    options: &ScanOptions,
+    searcher: &mut Searcher,
+    path: &Path,
+) -> Result<Vec<Todo>, String> {
+    let root = options.root_label(path);
+    let mut todos = scan_file(matcher, config, searcher, path)?;
+    if todos.len() > MAX_TODOS_PER_FILE {
+        eprintln!(
+            "Skipped TODOs in {} after the first {}",
+            path.display(),
+            MAX_TODOS_PER_FILE
+        );
+        todos.truncate(MAX_TODOS_PER_FILE);
+    }
+    stats::count(&stats::TODOS, todos.len());
+    let generated = !todos.is_empty() && is_generated_file(path);
+
+    Ok(todos
+        .into_iter()
+        .map(|todo| Todo {
+            root: root.clone(),
+            inherited_due: todo
+                .metadata
+                .issue
+                .as_ref()
+                .filter(|_| todo.metadata.invalid_due.is_none())
+                .and_then(|issue| config.issue_due_date(&issue.as_string())),
+            generated,
+            ..todo
+        })
+        .collect())
+}
+
+/// Markers of files written by a build step, which would undo any edits.
+const GENERATED_MARKERS: [&str; 2] = ["@generated", "DO NOT EDIT"];
+
+/// How many lines at the top of a file are checked for generated markers.
+const GENERATED_MARKER_LINES: usize = 5;
+
+fn is_generated_file(path: &Path) -> bool {
+    let Ok(file) = File::open(path) else {
+        return false;
+    };
+
+    BufReader::new(file)
+        .split(b'\n')
+        .take(GENERATED_MARKER_LINES)
+        .map_while(Result::ok)
+        .any(|line| {
+            let line = String::from_utf8_lossy(&line);
+            GENERATED_MARKERS.iter().any(|marker| line.contains(marker))
+        })
+}
+
+/// Warns about a file that can't be scanned, like a malformed notebook, which
+/// then has no TODOs rather than failing the whole scan.
+fn skip_unscannable(error: String) -> Vec<Todo> {
+    eprintln!("{} (skipped)", error);
+    vec![]
+}
 
+fn scan(matcher: &RegexMatcher, config: &Config, options: &ScanOptions) -> Vec<Todo> {
+    let start = Instant::now();
     let mut matches: Vec<Todo> = vec![];
-    let mut searcher = Searcher::new();
+    let mut searcher = build_searcher(None);
+
+    for (path, metadata) in walk_files(options) {
+        stats::count_bytes(metadata.map(|m| m.len()).unwrap_or(0));
+        if options.scan_archives && archive::is_archive(&path) {
+            let root = options.root_label(&path);
+            let todos = archive::scan(&path, |inner| {
+                scan_walked_file(matcher, config, options, &mut searcher, inner)
+                    .unwrap_or_else(skip_unscannable)
+            })
+            .unwrap_or_else(|e| cli_error(e));
+            matches.extend(todos.into_iter().map(|todo| Todo {
+                root: root.clone(),
+                ..todo
+            }));
+            continue;
+        }
 
-    let cli = Cli::parse();
+        matches.extend(
+            scan_walked_file(matcher, config, options, &mut searcher, &path)
+                .unwrap_or_else(skip_unscannable),
+        );
+    }
+
+    stats::count_scan(start.elapsed());
+    matches
+}
 
-    let mut paths = cli.path.unwrap_or(vec!["./".to_owned()]);
-    let primary_path = paths.remove(0);
-    let mut walk_builder = ignore::WalkBuilder::new(primary_path);
-    for path in paths {
-        walk_builder.add(path);
+/// The lines around `todo`, each with its number and the byte offset where it
+/// starts in the decoded file, or in the cell source for notebooks.
+fn snippet_context(todo: &Todo, radius: usize, config: &Config) -> Result<Value, String> {
+    let text = match todo.cell {
+        Some(cell) => containers::cell_source(&todo.path, cell)?,
+        None => {
+            let bytes = fs::read(&todo.path)
+                .map_err(|e| format!("Failed to read {}: {}", todo.path.display(), e))?;
+            FileEncoding::detect(&bytes, &todo.path, config).decode(&bytes)
+        }
+    };
+
+    let todo_index = todo.line_number as usize - 1;
+    let first_index = todo_index.saturating_sub(radius);
+    let mut offset = 0;
+    let mut lines: Vec<Value> = vec![];
+    for (index, line) in text.split_inclusive('\n').enumerate() {
+        if index > todo_index + radius {
+            break;
+        }
+
+        if index >= first_index {
+            lines.push(serde_json::json!({
+                "line": index + 1,
+                "offset": offset,
+                "text": line.trim_end_matches(['\r', '\n']),
+            }));
+        }
+        offset += line.len();
     }
 
-    let walk = walk_builder.build();
+    Ok(Value::Array(lines))
+}
 
-    for result in walk {
-        match result {
-            Ok(entry) => {
-                let is_file = entry
-                    .file_type()
-                    .and_then(|f| if f.is_file() { Some(()) } else { None });
+/// Whether the line before or after `todo` is a comment other than a TODO,
+/// like the continuation of a wrapped note or an explanation.
+fn has_context_comment(todo: &Todo, config: &Config) -> bool {
+    // The rest of an unterminated block comment explains the TODO too.
+    if todo.delimiter == "/*" && !todo.note.trim_end().ends_with("*/") {
+        return true;
+    }
 
-                if is_file == None {
-                    continue;
+    let Ok(context) = snippet_context(todo, 1, config) else {
+        return false;
+    };
+
+    context.as_array().into_iter().flatten().any(|line| {
+        let number = line["line"].as_u64().unwrap_or(0);
+        let text = line["text"].as_str().unwrap_or("").trim_start();
+        let comment = DELIMITERS
+            .iter()
+            .chain(["*"].iter())
+            .find_map(|delimiter| text.strip_prefix(delimiter));
+
+        number != todo.line_number
+            && comment.is_some_and(|comment| {
+                let comment = comment.trim();
+                !comment.is_empty() && !starts_with_todo(comment)
+            })
+    })
+}
+
+/// Whether the comment is another TODO, not prose like `TODOs are tracked`.
+fn starts_with_todo(comment: &str) -> bool {
+    comment
+        .get(..4)
+        .is_some_and(|keyword| keyword.eq_ignore_ascii_case("TODO"))
+        && !comment[4..].starts_with(|c: char| c.is_alphanumeric() || c == '_')
+}
+
+/// Comments like `// DONE: ...` or `// RESOLVED(#12): ...` left behind once a
+/// TODO was finished, parsed with the same metadata as TODOs.
+fn find_done_markers(config: &Config, options: &ScanOptions) -> Vec<Todo> {
+    let done_matcher =
+        RegexMatcher::new(r"(?m)^\W*(//|/\*|#) (?:(?i)DONE|RESOLVED)(?:\((.+?)\))?:? (.+?)$")
+            .unwrap_or_else(|e| cli_error(format!("{}", e)));
+
+    // Markdown task items are found regardless of the matcher, and are TODOs.
+    scan(&done_matcher, config, options)
+        .into_iter()
+        .filter(|todo| DELIMITERS.contains(&todo.delimiter.as_str()))
+        .collect()
+}
+
+const DOC_TODO_ERROR: &str = "TODO in documentation (doc-todo)";
+
+/// TODOs in doc comments and docstrings, with `public_only` only those
+/// documenting public items and modules.
+fn find_doc_todos(config: &Config, options: &ScanOptions, public_only: bool) -> Vec<Todo> {
+    let mut todos: Vec<Todo> = vec![];
+    for (path, _) in walk_files(options) {
+        let Ok(bytes) = fs::read(&path) else {
+            continue;
+        };
+        let contents = FileEncoding::detect(&bytes, &path, config).decode(&bytes);
+        let root = options.root_label(&path);
+
+        todos.extend(
+            docs::doc_todos(&path, &contents)
+                .into_iter()
+                .filter(|doc| doc.public || !public_only)
+                .map(|doc| Todo {
+                    root: root.clone(),
+                    raw: doc.text.clone(),
+                    delimiter: doc.marker.to_owned(),
+                    marker: markers::TODO.to_owned(),
+                    path: path.clone(),
+                    line_number: doc.line_number,
+                    column: doc.column,
+                    cell: None,
+                    note: doc.text,
+                    meta: None,
+                    metadata: TodoMetadata::empty(),
+                    inherited_due: None,
+                    generated: false,
+                }),
+        );
+    }
+    todos
+}
+
+/// The entries without the errors their TODOs already had at `reference`.
+/// TODOs are the same as there when their text is unchanged, wherever they
+/// moved, or otherwise when they have the same `id:`.
+fn drop_known_errors(
+    entries: Vec<ValidationErrorEntry>,
+    reference: &str,
+    matcher: &RegexMatcher,
+    validation_rules: &ValidationRules,
+    config: &Config,
+    pathspecs: &[String],
+) -> Vec<ValidationErrorEntry> {
+    let keywords = markers::scanned_keywords(&config.markers);
+    let lines = git::grep_lines(reference, &keywords, pathspecs)
+        .unwrap_or_else(|| cli_error(format!("Failed to read TODOs at {}", reference)));
+
+    let mut known_texts: HashMap<String, usize> = HashMap::new();
+    let mut known_errors_by_id: HashMap<String, Vec<String>> = HashMap::new();
+    for (path, line_number, text) in lines {
+        match parse_todo_line(matcher, config, &path, line_number, &text) {
+            Some(todo) => {
+                if let Some(id) = &todo.metadata.id {
+                    known_errors_by_id.insert(
+                        id.to_owned(),
+                        get_validation_errors(&todo, validation_rules, config),
+                    );
                 }
+                *known_texts.entry(todo.raw.trim().to_owned()).or_default() += 1;
+            }
+            None => *known_texts.entry(text.trim().to_owned()).or_default() += 1,
+        }
+    }
 
-                let search_result = searcher.search_path(
-                    &matcher,
-                    entry.path(),
-                    UTF8(|line_number, line| {
-                        let mut captures = matcher.new_captures()?;
+    entries
+        .into_iter()
+        .filter_map(|mut entry| {
+            if let Some(count) = known_texts.get_mut(entry.todo.raw.trim()) {
+                if *count > 0 {
+                    *count -= 1;
+                    return None;
+                }
+            }
 
-                        let did_match = matcher.captures(line.as_bytes(), &mut captures)?;
-                        if !did_match {
-                            return Ok(true);
-                        }
+            let known_errors = entry
+                .todo
+                .metadata
+                .id
+                .as_ref()
+                .and_then(|id| known_errors_by_id.get(id));
+            if let Some(known_errors) = known_errors {
+                entry.errors.retain(|error| !known_errors.contains(error));
+            }
+            (!entry.errors.is_empty()).then_some(entry)
+        })
+        .collect()
+}
 
-                        let delimiter_capture = captures.get(1);
-                        let (raw, delimiter) = match delimiter_capture {
-                            Some(delimiter_match) => (
-                                line[delimiter_match.start()..(line.len() - 1)].to_owned(),
-                                line[delimiter_match].to_string(),
-                            ),
-                            None => return Ok(true),
-                        };
+/// Notebook lines are relative to their cell, so they never count as added.
+fn is_added_line(todo: &Todo, added_lines: &HashMap<PathBuf, HashSet<u64>>) -> bool {
+    let Ok(path) = todo.path.canonicalize() else {
+        return false;
+    };
 
-                        let meta_capture = captures.get(2);
-                        let meta = match meta_capture {
-                            Some(meta_match) => Some(line[meta_match].to_string()),
-                            None => None,
-                        };
+    todo.cell.is_none()
+        && added_lines
+            .get(&path)
+            .is_some_and(|lines| lines.contains(&todo.line_number))
+}
 
-                        let note_capture = captures.get(3);
-                        let note = match note_capture {
-                            Some(note_match) => line[note_match].to_string(),
-                            None => return Ok(true),
-                        };
+fn is_author(assignee: &str, author: &git::Author, config: &Config) -> bool {
+    let profile = config.assignees.get(assignee);
+    if profile.is_some_and(|profile| profile.shared) {
+        return true;
+    }
 
-                        let metadata = if let Some(meta_str) = meta.to_owned() {
-                            TodoMetadata::from_string(meta_str)
-                        } else {
-                            TodoMetadata::empty()
-                        };
+    let username = author.email.split('@').next().unwrap_or("");
+    let identities = [author.name.as_str(), author.email.as_str(), username];
+    identities
+        .iter()
+        .filter(|identity| !identity.is_empty())
+        .any(|identity| {
+            identity.eq_ignore_ascii_case(assignee)
+                || profile.is_some_and(|profile| {
+                    profile
+                        .aliases
+                        .iter()
+                        .any(|alias| alias.eq_ignore_ascii_case(identity))
+                })
+        })
+}
 
-                        let todo = Todo {
-                            raw,
-                            delimiter,
-                            path: entry.path().to_path_buf(),
-                            line_number,
-                            note,
-                            meta,
-                            metadata,
-                        };
+/// How long after their last commit someone is taken to have left the team,
+/// when the config doesn't list `active_members`.
+const ACTIVE_AUTHOR_DAYS: u32 = 180;
+
+fn active_members(config: &Config) -> Vec<git::Author> {
+    match &config.active_members {
+        Some(members) => members
+            .iter()
+            .map(|member| git::Author {
+                name: member.clone(),
+                email: String::new(),
+            })
+            .collect(),
+        None => git::recent_authors(ACTIVE_AUTHOR_DAYS).unwrap_or_else(|| {
+            cli_error(
+                "--forbid-assignee-left needs active_members in the config or a git repository"
+                    .to_owned(),
+            )
+        }),
+    }
+}
+
+fn get_assignee_left_errors(
+    todo: &Todo,
+    active_members: &[git::Author],
+    config: &Config,
+) -> Vec<String> {
+    todo.metadata
+        .assignees
+        .iter()
+        .filter(|assignee| {
+            !active_members
+                .iter()
+                .any(|member| is_author(assignee, member, config))
+        })
+        .map(|assignee| {
+            format!(
+                "Assigned to {}, who left the team (assignee-left)",
+                assignee
+            )
+        })
+        .collect()
+}
 
-                        matches.push(todo);
+fn get_author_assignee_error(todo: &Todo, config: &Config) -> Option<String> {
+    if todo.metadata.assignees.is_empty() {
+        return None;
+    }
+    let author = git::blame_author(&todo.path, todo.line_number)?;
+    if todo
+        .metadata
+        .assignees
+        .iter()
+        .any(|assignee| is_author(assignee, &author, config))
+    {
+        return None;
+    }
 
-                        Ok(true)
-                    }),
-                );
+    Some(format!(
+        "Assigned to someone other than its author {} (assignee-not-author)",
+        author.name
+    ))
+}
 
-                if let Err(err) = search_result {
-                    cli_error(format!("{}", err));
-                }
+/// Comments mentioning a marker's keyword or FIXME that the scan missed,
+/// because they lack the space after the delimiter, the colon, or use
+/// another keyword. The keyword must be followed by its metadata, a colon, a
+/// space, or nothing, so `TODO-list` and `todo.example.com` aren't taken for
+/// TODOs.
+fn find_unparseable_todos(todos: &[Todo], config: &Config, options: &ScanOptions) -> Vec<Todo> {
+    let loose_matcher = RegexMatcher::new(&format!(
+        r"(?m)^\W*(//|/\*|#)\s*(?:{}|(?i)FIXME)(?:\((.*?)\))?(?::|\s|$)\s*(.*?)$",
+        markers::keyword_pattern(&config.markers)
+    ))
+    .unwrap_or_else(|e| cli_error(format!("{}", e)));
+
+    let parsed: HashSet<(&PathBuf, Option<usize>, u64)> = todos
+        .iter()
+        .map(|todo| (&todo.path, todo.cell, todo.line_number))
+        .collect();
+
+    scan(&loose_matcher, config, options)
+        .into_iter()
+        .filter(|todo| !parsed.contains(&(&todo.path, todo.cell, todo.line_number)))
+        .collect()
+}
+
+/// Matches lines with a TODO or one of the config's markers.
+fn todo_matcher(config: &Config) -> Result<RegexMatcher, grep::regex::Error> {
+    RegexMatcher::new(&format!(
+        r"(?m)^\W*(//|/\*|#) {}(?:\((.*?)\))?:? (.+?)$",
+        markers::keyword_pattern(&config.markers)
+    ))
+}
+
+fn main() -> Result<(), ()> {
+    let cli = Cli::parse();
+    if cli.stats {
+        stats::start_command();
+    }
+    let config = Config {
+        include_generated: cli.include_generated,
+        ..Config::load(cli.config.as_deref()).unwrap_or_else(|e| cli_error(e))
+    };
+
+    let matcher = todo_matcher(&config).map_err(|e| cli_error(format!("{}", e)))?;
+
+    let mut roots: Vec<ScanRoot> = cli
+        .path
+        .unwrap_or(vec!["./".to_owned()])
+        .iter()
+        .map(|arg| ScanRoot::parse(arg))
+        .collect();
+    if let Some(selected) = cli.root {
+        for label in &selected {
+            if !roots.iter().any(|root| root.label.as_ref() == Some(label)) {
+                cli_error(format!("--root={} matches no labeled --path", label));
             }
-            Err(err) => {
-                cli_error(format!("{}", err));
+        }
+        roots.retain(|root| root.label.as_ref().is_some_and(|l| selected.contains(l)));
+    }
+
+    let pathspecs: Vec<String> = roots.iter().map(|root| root.path.clone()).collect();
+    // Everything deciding which TODOs a scan finds keys the completion cache.
+    let scan_inputs: Vec<Vec<String>> = vec![
+        pathspecs.clone(),
+        cli.config.iter().cloned().collect(),
+        cli.path_glob.clone().unwrap_or_default(),
+        cli.file_type.clone().unwrap_or_default(),
+        cli.type_add.clone(),
+    ];
+    let path_filter = cli
+        .path_glob
+        .map(|patterns| PathFilter::new(&patterns).unwrap_or_else(|e| cli_error(e)));
+    let file_types = cli
+        .file_type
+        .map(|selected| FileTypes::new(&selected, &cli.type_add).unwrap_or_else(|e| cli_error(e)));
+    let mut scan_options = ScanOptions {
+        roots,
+        follow_links: cli.follow_links,
+        path_filter,
+        file_types,
+        scan_archives: cli.scan_archives,
+        git_global: config.global_gitignore && !cli.no_ignore_global,
+        git_exclude: config.git_exclude && !cli.no_ignore_exclude,
+        vcs_files: cli.vcs_files.then_some(cli.vcs_untracked),
+    };
+
+    if let Some(path) = &cli.debug_ignores {
+        println!("{}", ignores::explain(path, &scan_options));
+        stats::finish_command();
+        return Ok(());
+    }
+
+    // The config was validated when loaded, so checking it needs no scan.
+    if let Some(Commands::Config {
+        action: ConfigAction::Check,
+    }) = &cli.command
+    {
+        match Config::path(cli.config.as_deref()) {
+            Some(path) => println!("{} is valid.", path.display()),
+            None => println!("No {} found, the defaults apply.", DEFAULT_CONFIG_PATH),
+        }
+        stats::finish_command();
+        return Ok(());
+    }
+
+    // Rules are documented in code, so explaining them needs no scan.
+    if let Some(Commands::Explain { rule }) = &cli.command {
+        match rule {
+            Some(name) => match rules::find(name) {
+                Some(rule) => println!("{}", rule.as_explanation()),
+                None => cli_error(format!(
+                    "Unknown rule \"{}\", run `codo explain` to list them",
+                    name
+                )),
+            },
+            None => println!("{}", rules::as_list()),
+        }
+        stats::finish_command();
+        return Ok(());
+    }
+
+    // Tokens are stored outside the repository, so managing them needs no scan.
+    if let Some(Commands::Auth { action }) = &cli.command {
+        let result = match action {
+            AuthAction::Login { service, keychain } => credentials::read_token(service)
+                .and_then(|token| credentials::login(service, &token, *keychain)),
+            AuthAction::Status => Ok(credentials::status()),
+            AuthAction::Logout { service } => credentials::logout(service),
+        };
+        println!("{}", result.unwrap_or_else(|e| cli_error(e)));
+        stats::finish_command();
+        return Ok(());
+    }
+
+    // Scans each listed repository instead of the --path ones, with its own
+    // config if it has one.
+    if let Some(Commands::Agenda { repos, assignee }) = &cli.command {
+        let mut todos: Vec<Todo> = vec![];
+        for repo in agenda::read_repos(repos).unwrap_or_else(|e| cli_error(e)) {
+            if Path::new(&repo.path).is_file() {
+                let saved = snapshot::read(&repo.path, &config).unwrap_or_else(|e| cli_error(e));
+                todos.extend(saved.into_iter().map(|todo| Todo {
+                    root: repo.label.clone(),
+                    ..todo
+                }));
+            } else {
+                let repo_config = Path::new(&repo.path).join(DEFAULT_CONFIG_PATH);
+                let repo_config = repo_config
+                    .exists()
+                    .then(|| Config::load(repo_config.to_str()).unwrap_or_else(|e| cli_error(e)));
+                let repo_config = repo_config.as_ref().unwrap_or(&config);
+                let repo_matcher =
+                    todo_matcher(repo_config).unwrap_or_else(|e| cli_error(format!("{}", e)));
+
+                scan_options.roots = vec![repo];
+                todos.extend(scan(&repo_matcher, repo_config, &scan_options));
             }
         }
+
+        let agenda = agenda::render(&todos, assignee.as_deref());
+        if agenda.is_empty() {
+            cli_error("<no TODOs>".to_owned());
+        }
+        println!("{}", agenda);
+        stats::finish_command();
+        return Ok(());
+    }
+
+    // Completions are cached, so only scan when the cache is stale.
+    if let Some(Commands::Complete { flag, prefix }) = &cli.command {
+        let values = complete::complete(flag, prefix, &scan_inputs, &|| {
+            scan(&matcher, &config, &scan_options)
+        });
+        for value in values {
+            println!("{}", value);
+        }
+        stats::finish_command();
+        return Ok(());
+    }
+
+    let rewrites_files = match &cli.command {
+        Some(Commands::Format { .. } | Commands::Mod { .. } | Commands::Review { .. }) => true,
+        Some(Commands::Sync {
+            create_issues,
+            mark_closed,
+            unblock,
+            ..
+        }) => *create_issues || *mark_closed || *unblock,
+        _ => false,
+    };
+    if rewrites_files && cli.scan_archives {
+        cli_error("Archives are read-only, --scan-archives can't be used to edit files".to_owned());
     }
 
+    // Batch linting only checks what's sent on stdin.
+    let matches = match &cli.command {
+        Some(Commands::Validate {
+            stdin_json: true, ..
+        }) => vec![],
+        Some(Commands::Stat {
+            json_lines: Some(source),
+            ..
+        }) => snapshot::read(source, &config).unwrap_or_else(|e| cli_error(e)),
+        _ => scan(&matcher, &config, &scan_options),
+    };
+
     let command = cli.command.unwrap_or(Commands::List {
-        assignee: None,
-        issue: None,
-        due: None,
-        untracked: false,
-        unassigned: false,
-        overdue: false,
-        someday: false,
+        filters: FilterArgs::default(),
+        blocked_by: None,
+        vimgrep: false,
+        files_with_matches: false,
+        tree: false,
+        permalinks: false,
+        watch: false,
+        open: false,
+        pick: false,
+        format: None,
+        summary: false,
     });
 
+    let edits_files = matches!(command, Commands::Format { .. } | Commands::Mod { .. });
+    let matches: Vec<Todo> = if edits_files && !cli.include_generated {
+        matches.into_iter().filter(|todo| !todo.generated).collect()
+    } else {
+        matches
+    };
+
+    let mut prompt = match &command {
+        Commands::Mod {
+            interactive: true,
+            code_mod: CodeMod::Apply { .. },
+            ..
+        } => cli_error(
+            "--interactive can't be used with mod apply, which reads edits from stdin".to_owned(),
+        ),
+        Commands::Mod { interactive, .. } => interactive.then(interactive::FilePrompt::default),
+        _ => None,
+    };
+    let mod_verbose = match &command {
+        Commands::Mod { verbose, .. } => Some(*verbose),
+        _ => None,
+    };
+    let mut summary = UpdateSummary::default();
+
     match command {
         Commands::Stat {
-            assignee,
-            unassigned,
-            issue,
-            untracked,
-            due,
-            someday,
-            overdue,
+            filters,
             group_by,
+            depth,
+            sort,
+            include_empty,
+            split_count,
+            burndown,
+            since,
+            by_age,
+            metrics,
+            assignees_missing_from,
+            format,
+            json_lines: _,
         } => {
-            let results = filter_todo_list(
-                matches,
-                TodoFilters {
-                    assignee,
-                    unassigned,
-                    issue,
-                    untracked,
-                    due,
-                    overdue,
-                    someday,
-                },
-            );
+            let format = match format.as_deref().map(StatFormat::from_str) {
+                Some(Some(format)) => format,
+                Some(None) => cli_error(format!("Stat format invalid: \"{}\"", format.unwrap())),
+                None => StatFormat::Text,
+            };
 
-            if let Some(group_by) = group_by {
-                if let Some(grouping) = Grouping::from_str(&group_by) {
-                    let mut map: HashMap<String, u32> = HashMap::new();
+            let filters = filters.into_filters(None);
+            let results = filter_todo_list(matches, &filters);
 
-                    for todo in results {
-                        let key = match grouping {
-                            Grouping::Assignee => {
-                                todo.metadata.assignee.unwrap_or("<unassigned>".to_string())
-                            }
-                            Grouping::Due => todo.metadata.due.unwrap_or("<someday>".to_string()),
-                            Grouping::Issue => todo
-                                .metadata
-                                .issue
-                                .map(|i| i.as_string())
-                                .unwrap_or("<untracked>".to_string()),
-                        };
+            if depth.is_some() && group_by.as_deref() != Some("path") {
+                cli_error("--depth needs --group-by=path".to_owned());
+            }
+            let parse_grouping = |group_by: &str| {
+                Grouping::from_str(group_by).map(|grouping| match grouping {
+                    Grouping::Path(_) => Grouping::Path(depth.unwrap_or(1) as usize),
+                    grouping => grouping,
+                })
+            };
 
-                        let count = map.get(&key).unwrap_or(&0);
-                        map.insert(key, count + 1);
-                    }
+            if let Some(roster_file) = assignees_missing_from {
+                let roster = match roster_file {
+                    Some(path) => roster::read(&path).unwrap_or_else(|e| cli_error(e)),
+                    None => config.active_members.clone().unwrap_or_else(|| {
+                        cli_error(
+                            "--assignees-missing-from needs a roster file or active_members in the config"
+                                .to_owned(),
+                        )
+                    }),
+                };
 
-                    let mut entries: Vec<(String, u32)> = vec![];
-                    for (key, value) in map {
-                        entries.push((key, value));
+                let gaps = roster::compare(&results, &roster, &config);
+                if gaps.unknown.is_empty() {
+                    println!("{}", gaps.as_text());
+                } else {
+                    cli_error(gaps.as_text());
+                }
+            } else if metrics {
+                let metrics = metrics::per_assignee(&results, Local::now().date_naive());
+                match format {
+                    StatFormat::Text => println!("{}", metrics::as_table(&metrics)),
+                    StatFormat::Json => println!(
+                        "{}",
+                        serde_json::to_string_pretty(&metrics::as_json(&metrics))
+                            .unwrap_or_else(|e| cli_error(format!("Failed to print JSON: {}", e)))
+                    ),
+                    StatFormat::Prometheus | StatFormat::Csv | StatFormat::Tsv => {
+                        cli_error("--metrics supports text and json formats".to_owned())
                     }
+                }
+            } else if let StatFormat::Json = format {
+                cli_error("--format=json needs --metrics".to_owned());
+            } else if matches!(format, StatFormat::Csv | StatFormat::Tsv) && group_by.is_none() {
+                cli_error("--format=csv and --format=tsv need --group-by".to_owned());
+            } else if let StatFormat::Prometheus = format {
+                let grouping = group_by.map(|group_by| {
+                    let grouping = parse_grouping(&group_by).unwrap_or_else(|| {
+                        cli_error(format!("--group-by={} not supported", group_by))
+                    });
+                    (group_by, grouping)
+                });
+                print!("{}", prometheus_metrics(&results, grouping, &config));
+            } else if burndown {
+                let period = since.unwrap_or("90d".to_owned());
+                let duration = history::parse_period(&period)
+                    .unwrap_or_else(|| cli_error(format!("--since={} not supported", period)));
+                let today = Local::now().date_naive();
+                let start = today.checked_sub_signed(duration).unwrap_or_else(|| {
+                    cli_error(format!("--since={} is before the first date", period))
+                });
+
+                let changes = history::todo_changes(
+                    &matcher,
+                    &config,
+                    start,
+                    &pathspecs,
+                    scan_options.path_filter.as_ref(),
+                    scan_options.file_types.as_ref(),
+                    &filters,
+                )
+                .unwrap_or_else(|e| cli_error(e));
 
-                    entries.sort_by(|(_, a), (_, b)| b.cmp(a));
+                println!(
+                    "{}",
+                    history::burndown_chart(results.len(), &changes, start, today)
+                );
+            } else if by_age {
+                println!(
+                    "{}",
+                    history::age_histogram(&results, Local::now().date_naive())
+                );
+            } else if let Some(group_by) = group_by {
+                if let Some(grouping) = parse_grouping(&group_by) {
+                    let sort = match sort.as_deref().map(GroupSort::from_str) {
+                        Some(Some(sort)) => sort,
+                        Some(None) => cli_error(format!("--sort={} not supported", sort.unwrap())),
+                        None => GroupSort::Count,
+                    };
 
-                    println!(
-                        "{}",
-                        entries
-                            .iter()
-                            .map(|(key, count)| format!("{}: {}", key, count))
-                            .collect::<Vec<String>>()
-                            .join("\n")
-                    )
+                    let empty_keys: Vec<String> = if !include_empty {
+                        vec![]
+                    } else {
+                        match &grouping {
+                            Grouping::Assignee => filters
+                                .assignee
+                                .clone()
+                                .or(config.active_members.clone())
+                                .unwrap_or(config.assignees.keys().cloned().collect())
+                                .iter()
+                                .map(|member| roster::assignee_name(member, &config))
+                                .collect(),
+                            Grouping::Due => config.milestones.values().cloned().collect(),
+                            Grouping::Root => scan_options
+                                .roots
+                                .iter()
+                                .filter_map(|root| root.label.clone())
+                                .collect(),
+                            _ => cli_error(
+                                "--include-empty supports --group-by assignee, due, and root"
+                                    .to_owned(),
+                            ),
+                        }
+                    };
+
+                    let mut report = aggregate::aggregate(&results, grouping, split_count, &config);
+                    for key in empty_keys {
+                        report.add_empty(key);
+                    }
+                    match format {
+                        StatFormat::Csv => println!("{}", report.as_delimited(&sort, ',')),
+                        StatFormat::Tsv => println!("{}", report.as_delimited(&sort, '\t')),
+                        _ => println!("{}", report.as_text(&sort)),
+                    }
                 } else {
                     cli_error(format!("--group-by={} not supported", group_by));
                 }
@@ -749,47 +4578,99 @@ fn main() -> Result<(), ()> {
             }
         }
         Commands::List {
-            assignee,
-            unassigned,
-            issue,
-            untracked,
-            due,
-            someday,
-            overdue,
+            filters,
+            blocked_by,
+            vimgrep,
+            files_with_matches,
+            tree,
+            permalinks,
+            watch,
+            open,
+            pick,
+            format,
+            summary,
         } => {
-            let results = filter_todo_list(
-                matches,
-                TodoFilters {
-                    assignee,
-                    unassigned,
-                    issue,
-                    untracked,
-                    due,
-                    overdue,
-                    someday,
-                },
-            );
+            let style = if permalinks {
+                ListStyle::Permalinks(detect_permalinks(&config))
+            } else if vimgrep {
+                ListStyle::Vimgrep
+            } else if files_with_matches {
+                ListStyle::FilesWithMatches
+            } else if tree {
+                ListStyle::Tree
+            } else if summary {
+                ListStyle::Summary
+            } else {
+                match format.as_deref() {
+                    None | Some("text") => ListStyle::SearchResults,
+                    Some("ndjson") => ListStyle::Ndjson,
+                    Some(format) => cli_error(format!("--format={} not supported", format)),
+                }
+            };
+            let filters = filters.into_filters(blocked_by);
+
+            if watch {
+                watch::watch(&matcher, &config, &scan_options, &|todos| {
+                    warn_invalid_dues(todos.iter().copied(), &filters);
+                    let results: Vec<&Todo> = todos
+                        .into_iter()
+                        .filter(|todo| todo_matches(todo, &filters))
+                        .collect();
+
+                    // Clears the terminal before each update.
+                    print!("\x1b[2J\x1b[H");
+                    if results.is_empty() {
+                        println!("<no TODOs>");
+                    } else {
+                        println!("{}", list_output(&results, &style));
+                    }
+                });
+            }
 
+            let results = filter_todo_list(matches, &filters);
             if results.is_empty() {
                 cli_error("<no TODOs>".to_owned());
+            } else if open {
+                let results: Vec<&Todo> = results.iter().collect();
+                let todo = if pick {
+                    editor::pick(&results)
+                } else {
+                    results.first().copied()
+                };
+                if let Some(todo) = todo {
+                    editor::open(&config, todo).unwrap_or_else(|e| cli_error(e));
+                }
             } else {
-                println!(
-                    "{}",
-                    results
-                        .iter()
-                        .map(|t| t.as_search_result())
-                        .collect::<Vec<String>>()
-                        .join("\n")
-                );
+                let results: Vec<&Todo> = results.iter().collect();
+                println!("{}", list_output(&results, &style));
             }
         }
         Commands::Validate {
             require_assignees,
             require_issues,
             require_due_dates,
+            require_due_for_priority,
+            forbid_bare,
+            max_todos_per_file,
+            max_snooze,
             allowed_assignees,
+            single_assignee,
+            forbid_unknown_metadata,
             issue_format,
             issue_project_keys,
+            require_issues_for,
+            pedantic,
+            forbid_done,
+            require_context_comment,
+            new_since,
+            compare_ref,
+            require_author_assignee,
+            forbid_assignee_left,
+            forbid_doc_todos,
+            emit_patch,
+            stdin_json,
+            quiet,
+            max_violations,
         } => {
             let issue_format = if let Some(input_format) = issue_format {
                 if let Some(valid_format) = IssueFormat::from_str(&input_format) {
@@ -801,90 +4682,661 @@ fn main() -> Result<(), ()> {
                 None
             };
 
+            let doc_todos_public_only = forbid_doc_todos.map(|scope| match scope.as_str() {
+                "all" => false,
+                "public" => true,
+                _ => cli_error(format!("Doc TODO scope invalid: \"{}\"", scope)),
+            });
+
+            let max_snooze = max_snooze.map(|period| {
+                let duration = history::parse_period(&period)
+                    .unwrap_or_else(|| cli_error(format!("--max-snooze={} not supported", period)));
+                (period, duration)
+            });
+
+            let due_for_priority = require_due_for_priority.map(|input| {
+                parse_priority(&input)
+                    .unwrap_or_else(|| cli_error(format!("Priority invalid: \"{}\"", input)))
+            });
+
+            let mut scoped_rules = config.rules.clone();
+            if let Some(paths) = require_issues_for {
+                scoped_rules.push(ScopedRules {
+                    paths,
+                    require_assignees: None,
+                    require_issues: Some(true),
+                    require_due_dates: None,
+                    forbid_bare: None,
+                });
+            }
+
+            let scopes = scoped_rules
+                .into_iter()
+                .map(|rules| {
+                    let filter = PathFilter::new(&rules.paths).unwrap_or_else(|e| cli_error(e));
+                    (filter, rules)
+                })
+                .collect();
+
             let validation_rules = ValidationRules {
                 require_assignees,
                 require_issues,
                 require_due_dates,
+                due_for_priority,
+                forbid_bare,
+                max_todos_per_file,
+                max_snooze,
                 allowed_assignees,
+                single_assignee,
+                forbid_unknown_metadata,
                 issue_format,
                 issue_project_keys,
+                scopes,
+            };
+
+            if stdin_json {
+                batch::serve(&matcher, &validation_rules, &config);
+                stats::finish_command();
+                return Ok(());
+            }
+
+            let mut file_validation_errors =
+                get_file_validation_errors(&matches, &validation_rules);
+            file_validation_errors.extend(
+                budget::statuses(&matches, &config.budget)
+                    .into_iter()
+                    .filter(|status| status.is_exceeded())
+                    .map(|status| FileValidationErrorEntry {
+                        path: PathBuf::from(status.directory),
+                        errors: vec![format!(
+                            "Over budget ({} TODOs, at most {})",
+                            status.count, status.allowance
+                        )],
+                    }),
+            );
+
+            let unparseable_todos = if pedantic {
+                find_unparseable_todos(&matches, &config, &scan_options)
+            } else {
+                vec![]
+            };
+
+            let done_markers = if forbid_done {
+                find_done_markers(&config, &scan_options)
+            } else {
+                vec![]
             };
 
-            let validation_errors: Vec<ValidationErrorEntry> = matches
-                .into_iter()
+            let active_members = forbid_assignee_left.then(|| active_members(&config));
+
+            // TODOs written like `/// TODO:` are scanned too, and get the
+            // error added to theirs rather than listed twice.
+            let doc_todos = doc_todos_public_only
+                .map(|public_only| find_doc_todos(&config, &scan_options, public_only))
+                .unwrap_or_default();
+            let doc_todo_lines: HashSet<(PathBuf, u64)> = doc_todos
+                .iter()
+                .map(|todo| (todo.path.clone(), todo.line_number))
+                .collect();
+            let scanned_lines: HashSet<(PathBuf, u64)> = matches
+                .iter()
+                .filter(|todo| todo.cell.is_none())
+                .map(|todo| (todo.path.clone(), todo.line_number))
+                .collect();
+
+            // Per file rules and budgets still count every TODO.
+            let added_lines = new_since.map(|base| {
+                git::added_lines(&base)
+                    .unwrap_or_else(|| cli_error(format!("Failed to diff against {}", base)))
+            });
+            let is_new = |todo: &Todo| match &added_lines {
+                Some(added_lines) => is_added_line(todo, added_lines),
+                None => true,
+            };
+
+            if let Some(patch_path) = emit_patch {
+                let fixes: Vec<TodoUpdate> = matches
+                    .iter()
+                    .filter(|todo| is_new(todo))
+                    .filter_map(|todo| fix_update(todo, &config))
+                    .collect();
+                let fix_count = fixes.len();
+                let patch = updates_patch(fixes, &config).unwrap_or_else(|e| cli_error(e));
+                if patch_path == "-" {
+                    print!("{}", patch);
+                } else {
+                    fs::write(&patch_path, patch).unwrap_or_else(|e| {
+                        cli_error(format!("Failed to write {}: {}", patch_path, e))
+                    });
+                }
+                eprintln!(
+                    "Wrote fixes for {} to {}.",
+                    count_noun(fix_count, "TODO"),
+                    if patch_path == "-" {
+                        "stdout"
+                    } else {
+                        &patch_path
+                    }
+                );
+            }
+
+            let mut validation_errors: Vec<ValidationErrorEntry> =
+                matches
+                    .into_iter()
+                    .filter(is_new)
+                    .filter_map(|todo| {
+                        let mut errors = get_validation_errors(&todo, &validation_rules, &config);
+                        if require_author_assignee {
+                            errors.extend(get_author_assignee_error(&todo, &config));
+                        }
+                        if let Some(active_members) = &active_members {
+                            errors.extend(get_assignee_left_errors(&todo, active_members, &config));
+                        }
+                        if todo.cell.is_none()
+                            && doc_todo_lines.contains(&(todo.path.clone(), todo.line_number))
+                        {
+                            errors.push(DOC_TODO_ERROR.to_owned());
+                        }
+                        if let Some(min_length) = require_context_comment {
+                            let note = get_printable_note(&todo.delimiter, &todo.note);
+                            if note.trim().chars().count() < min_length
+                                && !has_context_comment(&todo, &config)
+                            {
+                                errors.push(format!(
+                                    "Short note needs a context comment (under {} characters)",
+                                    min_length
+                                ));
+                            }
+                        }
+
+                        if errors.is_empty() {
+                            None
+                        } else {
+                            Some(ValidationErrorEntry { todo, errors })
+                        }
+                    })
+                    .chain(unparseable_todos.into_iter().filter(is_new).map(|todo| {
+                        ValidationErrorEntry {
+                            todo,
+                            errors: vec!["Unparseable TODO (unparseable-todo)".to_owned()],
+                        }
+                    }))
+                    .chain(
+                        doc_todos
+                            .into_iter()
+                            .filter(|todo| {
+                                !scanned_lines.contains(&(todo.path.clone(), todo.line_number))
+                            })
+                            .filter(is_new)
+                            .map(|todo| ValidationErrorEntry {
+                                todo,
+                                errors: vec![DOC_TODO_ERROR.to_owned()],
+                            }),
+                    )
+                    .chain(
+                        done_markers
+                            .into_iter()
+                            .filter(is_new)
+                            .map(|todo| ValidationErrorEntry {
+                                todo,
+                                errors: vec![
+                                    "Finished work left as a comment (done-marker)".to_owned()
+                                ],
+                            }),
+                    )
+                    .collect();
+            validation_errors.sort_by(|a, b| {
+                (&a.todo.path, a.todo.cell, a.todo.line_number).cmp(&(
+                    &b.todo.path,
+                    b.todo.cell,
+                    b.todo.line_number,
+                ))
+            });
+            if let Some(reference) = compare_ref {
+                validation_errors = drop_known_errors(
+                    validation_errors,
+                    &reference,
+                    &matcher,
+                    &validation_rules,
+                    &config,
+                    &pathspecs,
+                );
+            }
+
+            // Only markers with the error severity fail validation.
+            let (validation_warnings, validation_errors): (Vec<_>, Vec<_>) =
+                validation_errors.into_iter().partition(|entry| {
+                    config
+                        .marker(&entry.todo.marker)
+                        .is_some_and(|marker| marker.severity == Severity::Warning)
+                });
+            if !validation_warnings.is_empty() {
+                let report = if quiet {
+                    rule_counts(validation_warnings.iter().flat_map(|t| &t.errors))
+                } else {
+                    violation_list(
+                        validation_warnings.iter().map(|t| t.as_cli_result()),
+                        max_violations,
+                    )
+                };
+                eprintln!(
+                    "Validation warnings ({}):\n\n{}\n",
+                    validation_warnings.len(),
+                    report
+                );
+            }
+
+            if validation_errors.is_empty() && file_validation_errors.is_empty() {
+                println!("Validation errors (0): Great job!")
+            } else {
+                let report = if quiet {
+                    rule_counts(
+                        file_validation_errors
+                            .iter()
+                            .flat_map(|f| &f.errors)
+                            .chain(validation_errors.iter().flat_map(|t| &t.errors)),
+                    )
+                } else {
+                    violation_list(
+                        file_validation_errors
+                            .iter()
+                            .map(|f| f.as_cli_result())
+                            .chain(validation_errors.iter().map(|t| t.as_cli_result())),
+                        max_violations,
+                    )
+                };
+                cli_error(format!(
+                    "Validation errors ({}):\n\n{}",
+                    validation_errors.len() + file_validation_errors.len(),
+                    report
+                ));
+            }
+        }
+        Commands::Format { verify: true, .. } => {
+            let unstable: Vec<String> = matches
+                .iter()
+                .filter_map(|todo| format_instability(&matcher, todo, &config))
+                .collect();
+
+            if unstable.is_empty() {
+                println!("Formatting is stable.")
+            } else {
+                cli_error(format!(
+                    "TODOs formatted differently on a second run ({}):\n\n{}",
+                    unstable.len(),
+                    unstable.join("\n\n")
+                ));
+            }
+        }
+        Commands::Format { check: true, .. } => {
+            let changes: Vec<String> = matches
+                .iter()
                 .filter_map(|todo| {
-                    let errors = get_validation_errors(&todo, &validation_rules);
-                    if errors.is_empty() {
-                        None
-                    } else {
-                        Some(ValidationErrorEntry { todo, errors })
-                    }
+                    let formatted = format_todo_update(
+                        &todo.delimiter,
+                        &todo.marker,
+                        &todo.note,
+                        todo.metadata.to_owned(),
+                        &todo.path,
+                        &config,
+                    );
+
+                    (todo.raw != formatted)
+                        .then(|| format!("{}\n-{}\n+{}", todo.location(), todo.raw, formatted))
                 })
                 .collect();
 
-            if validation_errors.is_empty() {
-                println!("Validation errors (0): Great job!")
+            if changes.is_empty() {
+                println!("TODOs are formatted.")
             } else {
                 cli_error(format!(
-                    "Validation errors ({}):\n\n{}",
-                    validation_errors.len(),
-                    validation_errors
-                        .into_iter()
-                        .map(|t| t.as_cli_result())
-                        .collect::<Vec<String>>()
-                        .join("\n\n"),
+                    "TODOs to format ({}):\n\n{}",
+                    changes.len(),
+                    changes.join("\n\n")
                 ));
             }
         }
-        Commands::Format => {
+        Commands::Format {
+            check: false,
+            assign_ids,
+            verbose,
+            verify: false,
+        } => {
+            debug_assert!(
+                matches
+                    .iter()
+                    .all(|todo| format_instability(&matcher, todo, &config).is_none()),
+                "formatting isn't idempotent, see `codo format --verify`"
+            );
+
+            let mut taken_ids: HashSet<String> = matches
+                .iter()
+                .filter_map(|todo| todo.metadata.id.clone())
+                .collect();
+
             let updates: Vec<TodoUpdate> = matches
                 .into_iter()
                 .map(|item| TodoUpdate {
-                    metadata: item.metadata,
+                    metadata: if assign_ids && item.metadata.id.is_none() {
+                        TodoMetadata {
+                            id: Some(generate_id(&mut taken_ids)),
+                            ..item.metadata
+                        }
+                    } else {
+                        item.metadata
+                    },
                     note: item.note,
                     path: item.path,
                     line_number: item.line_number,
+                    cell: item.cell,
                     delimiter: item.delimiter,
+                    marker: item.marker,
+                    raw: item.raw,
+                    action: UpdateAction::Rewrite,
                 })
                 .collect();
 
             if updates.is_empty() {
                 cli_error("No TODOs found".to_owned());
             } else {
-                apply_updates(updates);
-                println!("TODOs formatted.")
+                let summary =
+                    apply_updates(updates, cli.force, &config).unwrap_or_else(|e| cli_error(e));
+                println!("TODOs formatted.\n{}", summary.as_cli_result(verbose))
             }
         }
         Commands::Export { medium } => match medium {
-            ExportMedium::Json => {
-                let todo_items: Vec<serde_json::Value> = matches
+            ExportMedium::Json {
+                context,
+                permalinks,
+            } => {
+                let mut json_doc = schema::document(matches.iter());
+                if let Some(radius) = context {
+                    let items = json_doc["todos"].as_array_mut().into_iter().flatten();
+                    for (item, todo) in items.zip(&matches) {
+                        item["context"] =
+                            snippet_context(todo, radius, &config).unwrap_or_else(|e| cli_error(e));
+                    }
+                }
+                if permalinks {
+                    let base = detect_permalinks(&config);
+                    let items = json_doc["todos"].as_array_mut().into_iter().flatten();
+                    for (item, todo) in items.zip(&matches) {
+                        item["permalink"] = base.link(&todo.path, todo.file_position().0).into();
+                    }
+                }
+
+                let output_str = serde_json::ser::to_string_pretty(&json_doc)
+                    .or_else(|e| cli_error(format!("Failed to print JSON: {}", e)))?;
+
+                println!("{}", output_str);
+            }
+        },
+        Commands::Digest {
+            assignee,
+            all_assignees,
+            format,
+            out_dir,
+        } => {
+            let format = if let Some(input_format) = format {
+                DigestFormat::from_str(&input_format).unwrap_or_else(|| {
+                    cli_error(format!("Digest format invalid: \"{}\"", input_format))
+                })
+            } else {
+                DigestFormat::Markdown
+            };
+
+            let assignees: Vec<String> = if all_assignees {
+                let mut all: Vec<String> = matches
+                    .iter()
+                    .flat_map(|todo| todo.metadata.assignees.iter().cloned())
+                    .collect();
+                all.sort();
+                all.dedup();
+                all
+            } else if let Some(assignee) = assignee {
+                vec![assignee]
+            } else {
+                cli_error("Either --assignee or --all-assignees is required".to_owned());
+            };
+
+            if assignees.is_empty() {
+                cli_error("No TODOs assigned".to_owned());
+            }
+
+            let permalinks = git::PermalinkBase::detect(config.repo_url.as_deref());
+            let mut digests: Vec<(String, String)> = vec![];
+            for assignee in assignees {
+                let todos: Vec<&Todo> = matches
+                    .iter()
+                    .filter(|todo| todo.metadata.assignees.contains(&assignee))
+                    .collect();
+
+                if todos.is_empty() {
+                    cli_error(format!("No TODOs assigned to \"{}\"", assignee));
+                }
+
+                let digest = make_digest(&assignee, &todos, &format, &permalinks, &config);
+                digests.push((assignee, digest));
+            }
+
+            if let Some(out_dir) = out_dir {
+                let dir = PathBuf::from(out_dir);
+                // Checks every name before writing, so a bad one doesn't leave
+                // a partial set of digests behind.
+                let files: Vec<(PathBuf, String)> = digests
                     .into_iter()
-                    .map(|todo| {
-                        serde_json::json!({
-                            "path": todo.path.to_str(),
-                            "line": todo.line_number,
-                            "note": get_printable_note(&todo.delimiter, &todo.note),
-                            "issue": todo.metadata.issue.map(|f| f.as_string()),
-                            "assignee": todo.metadata.assignee,
-                            "due": todo.metadata.due,
-                        })
+                    .map(|(assignee, digest)| {
+                        digest_path(&dir, &assignee, format.extension())
+                            .map(|file_path| (file_path, digest))
+                            .unwrap_or_else(|e| cli_error(e))
                     })
                     .collect();
+                fs::create_dir_all(&dir).unwrap_or_else(|e| {
+                    cli_error(format!("Failed to create {}: {}", dir.display(), e))
+                });
+
+                for (file_path, digest) in files {
+                    fs::write(&file_path, digest).unwrap_or_else(|e| {
+                        cli_error(format!("Failed to write {}: {}", file_path.display(), e))
+                    });
+                    println!("Wrote {}", file_path.display());
+                }
+            } else {
+                println!(
+                    "{}",
+                    digests
+                        .into_iter()
+                        .map(|(_, digest)| digest)
+                        .collect::<Vec<String>>()
+                        .join("\n")
+                );
+            }
+        }
+        Commands::Serve { port } => {
+            serve::serve(port, &config, &|| scan(&matcher, &config, &scan_options))
+                .unwrap_or_else(|e| cli_error(e));
+        }
+        Commands::Review { filters } => {
+            let results = filter_todo_list(matches, &filters.into_filters(None));
 
-                let mut doc = ::serde_json::Map::<String, Value>::new();
-                doc.insert("version".to_owned(), Value::String(VERSION.to_owned()));
-                doc.insert("todos".to_owned(), Value::Array(todo_items));
+            review::review(results, &mut std::io::stdin().lock(), cli.force, &config)
+                .unwrap_or_else(|e| cli_error(e));
+        }
+        Commands::Due {
+            assignee,
+            calendar,
+            from,
+            months,
+        } => {
+            let filters = FilterArgs {
+                assignee,
+                ..FilterArgs::default()
+            }
+            .into_filters(None);
+            let results = filter_todo_list(matches, &filters);
+            let results: Vec<&Todo> = results.iter().filter(|todo| todo.due().is_some()).collect();
+            if results.is_empty() {
+                cli_error("<no TODOs>".to_owned());
+            }
 
-                let json_doc = Value::Object(doc);
+            if calendar {
+                let first = match from {
+                    Some(month) => NaiveDate::parse_from_str(&format!("{}-01", month), "%Y-%m-%d")
+                        .unwrap_or_else(|_| cli_error(format!("Month invalid: \"{}\"", month))),
+                    None => Local::now().date_naive(),
+                };
+                println!("{}", calendar::render(&results, first, months));
+            } else {
+                println!("{}", calendar::timeline(&results));
+            }
+        }
+        Commands::Budget => {
+            if config.budget.is_empty() {
+                cli_error("No budgets configured, add a [budget] table to the config".to_owned());
+            }
 
-                let output_str = serde_json::ser::to_string_pretty(&json_doc)
-                    .or_else(|e| cli_error(format!("Failed to print JSON: {}", e)))?;
+            let statuses = budget::statuses(&matches, &config.budget);
+            for status in &statuses {
+                println!("{}", status.as_cli_result());
+            }
 
-                println!("{}", output_str);
+            let exceeded_count = statuses.iter().filter(|s| s.is_exceeded()).count();
+            if exceeded_count > 0 {
+                cli_error(format!("\nBudgets exceeded ({})", exceeded_count));
             }
-        },
-        Commands::Mod { code_mod } => match code_mod {
-            CodeMod::RemoveIssue { issue } => {
+        }
+        Commands::Issues { format } => {
+            let format = match format.as_deref().map(IssuesFormat::from_str) {
+                Some(Some(format)) => format,
+                Some(None) => cli_error(format!("Issues format invalid: \"{}\"", format.unwrap())),
+                None => IssuesFormat::Text,
+            };
+
+            let cited = issues::cited_issues(&matches);
+            match format {
+                IssuesFormat::Json => println!(
+                    "{}",
+                    serde_json::to_string_pretty(&issues::as_json(&cited))
+                        .unwrap_or_else(|e| cli_error(format!("{}", e)))
+                ),
+                _ if cited.is_empty() => cli_error("<no issues>".to_owned()),
+                IssuesFormat::Markdown => println!("{}", issues::as_markdown(&cited)),
+                IssuesFormat::Text => println!("{}", issues::as_text(&cited)),
+            }
+        }
+        Commands::Sync {
+            create_issues,
+            close_done,
+            mark_closed,
+            unblock,
+        } => {
+            let done_markers = if close_done {
+                find_done_markers(&config, &scan_options)
+            } else {
+                vec![]
+            };
+
+            sync::sync(
+                matches,
+                done_markers,
+                sync::SyncActions {
+                    create_issues,
+                    close_done,
+                    mark_closed,
+                    unblock,
+                },
+                cli.force,
+                &config,
+            )
+            .unwrap_or_else(|e| cli_error(e));
+        }
+        Commands::Schema { format } => {
+            let schema = match SchemaFormat::from_str(&format) {
+                Some(SchemaFormat::JsonSchema) => schema::json_schema(),
+                None => cli_error(format!("Schema format invalid: \"{}\"", format)),
+            };
+
+            let output_str = serde_json::ser::to_string_pretty(&schema)
+                .or_else(|e| cli_error(format!("Failed to print JSON: {}", e)))?;
+
+            println!("{}", output_str);
+        }
+        Commands::Complete { .. } => unreachable!("completions are handled before scanning"),
+        Commands::Explain { .. } => unreachable!("rules are explained before scanning"),
+        Commands::Auth { .. } => unreachable!("tokens are managed before scanning"),
+        Commands::Config { .. } => unreachable!("the config is checked before scanning"),
+        Commands::Agenda { .. } => unreachable!("agendas are made before scanning"),
+        Commands::Selftest { dir, bless } => {
+            let dir = PathBuf::from(dir);
+            let corpus = scan(
+                &matcher,
+                &Config::default(),
+                &ScanOptions {
+                    roots: vec![ScanRoot {
+                        label: None,
+                        path: dir.join("corpus").to_string_lossy().into_owned(),
+                    }],
+                    follow_links: false,
+                    path_filter: None,
+                    file_types: None,
+                    scan_archives: false,
+                    // Only the corpus's own ignore files count, so the result is the
+                    // same on every machine.
+                    git_global: false,
+                    git_exclude: false,
+                    vcs_files: None,
+                },
+            );
+
+            let actual: Vec<String> = corpus
+                .iter()
+                .map(|todo| todo.as_parse_description(&dir))
+                .collect();
+
+            let golden_path = dir.join("golden").join("parse.txt");
+            if bless {
+                fs::write(&golden_path, format!("{}\n", actual.join("\n"))).unwrap_or_else(|e| {
+                    cli_error(format!("Failed to write {}: {}", golden_path.display(), e))
+                });
+                println!("Wrote {}", golden_path.display());
+            } else {
+                let expected = fs::read_to_string(&golden_path).unwrap_or_else(|e| {
+                    cli_error(format!("Failed to read {}: {}", golden_path.display(), e))
+                });
+                let expected: Vec<&str> = expected.lines().collect();
+
+                let differences: Vec<String> = expected
+                    .iter()
+                    .filter(|line| !actual.iter().any(|a| a == *line))
+                    .map(|line| format!("- {}", line))
+                    .chain(
+                        actual
+                            .iter()
+                            .filter(|line| !expected.contains(&line.as_str()))
+                            .map(|line| format!("+ {}", line)),
+                    )
+                    .collect();
+
+                if differences.is_empty() {
+                    println!("Selftest passed ({} TODOs).", actual.len())
+                } else {
+                    cli_error(format!(
+                        "Selftest failed, the parser no longer matches {}:\n\n{}",
+                        golden_path.display(),
+                        differences.join("\n")
+                    ));
+                }
+            }
+        }
+        Commands::Mod { code_mod, .. } => match code_mod {
+            CodeMod::RemoveIssue { issue, strategy } => {
+                let action = match RemovalStrategy::from_str(&strategy) {
+                    Some(RemovalStrategy::StripMetadata) => UpdateAction::Edit,
+                    Some(RemovalStrategy::Delete) => UpdateAction::Delete,
+                    Some(RemovalStrategy::ConvertToNote) => UpdateAction::ConvertToNote,
+                    None => cli_error(format!("--strategy={} not supported", strategy)),
+                };
+
                 let updates: Vec<TodoUpdate> = matches
                     .into_iter()
                     .filter(|todo| {
@@ -902,7 +5354,11 @@ fn main() -> Result<(), ()> {
                             note: item.note,
                             path: item.path,
                             line_number: item.line_number,
+                            cell: item.cell,
                             delimiter: item.delimiter,
+                            marker: item.marker,
+                            raw: item.raw,
+                            action,
                         }
                     })
                     .collect();
@@ -910,14 +5366,15 @@ fn main() -> Result<(), ()> {
                 if updates.is_empty() {
                     cli_error(format!("No TODOs citing issue \"{}\"", issue));
                 } else {
-                    apply_updates(updates);
+                    summary = apply_updates_confirmed(updates, cli.force, prompt.as_mut(), &config)
+                        .unwrap_or_else(|e| cli_error(e));
                     println!("All citations of issue \"{}\" were removed.", issue)
                 }
             }
             CodeMod::RemoveAllIssues => {
                 let updates: Vec<TodoUpdate> = matches
                     .into_iter()
-                    .filter(|todo| !todo.metadata.issue.is_none())
+                    .filter(|todo| todo.metadata.issue.is_some())
                     .map(|item| {
                         let new_metadata = TodoMetadata {
                             issue: None,
@@ -929,7 +5386,11 @@ fn main() -> Result<(), ()> {
                             note: item.note,
                             path: item.path,
                             line_number: item.line_number,
+                            cell: item.cell,
                             delimiter: item.delimiter,
+                            marker: item.marker,
+                            raw: item.raw,
+                            action: UpdateAction::Edit,
                         }
                     })
                     .collect();
@@ -937,10 +5398,116 @@ fn main() -> Result<(), ()> {
                 if updates.is_empty() {
                     cli_error("No TODOs citing any issues".to_owned());
                 } else {
-                    apply_updates(updates);
+                    summary = apply_updates_confirmed(updates, cli.force, prompt.as_mut(), &config)
+                        .unwrap_or_else(|e| cli_error(e));
                     println!("All citations of issues were removed.")
                 }
             }
+            CodeMod::NormalizeIssueCase => {
+                let updates: Vec<TodoUpdate> = matches
+                    .into_iter()
+                    .filter(|todo| todo.metadata.issue.is_none())
+                    .filter_map(|item| {
+                        let mut extra = item.metadata.extra.clone();
+                        let index = extra
+                            .iter()
+                            .position(|token| parse_malformed_issue(token).is_some())?;
+                        let issue = parse_malformed_issue(&extra.remove(index));
+
+                        Some(TodoUpdate {
+                            metadata: TodoMetadata {
+                                issue,
+                                extra,
+                                ..item.metadata
+                            },
+                            note: item.note,
+                            path: item.path,
+                            line_number: item.line_number,
+                            cell: item.cell,
+                            delimiter: item.delimiter,
+                            marker: item.marker,
+                            raw: item.raw,
+                            action: UpdateAction::Edit,
+                        })
+                    })
+                    .collect();
+
+                if updates.is_empty() {
+                    cli_error("No malformed issues".to_owned());
+                } else {
+                    summary = apply_updates_confirmed(updates, cli.force, prompt.as_mut(), &config)
+                        .unwrap_or_else(|e| cli_error(e));
+                    println!("All malformed issues were normalized.")
+                }
+            }
+            CodeMod::DedupeMetadata => {
+                let updates: Vec<TodoUpdate> = matches
+                    .into_iter()
+                    .filter(|todo| {
+                        todo.metadata
+                            .extra
+                            .iter()
+                            .any(|token| is_duplicate_token(token, &config))
+                    })
+                    .map(|item| {
+                        let extra = item
+                            .metadata
+                            .extra
+                            .iter()
+                            .filter(|token| !is_duplicate_token(token, &config))
+                            .cloned()
+                            .collect();
+
+                        TodoUpdate {
+                            metadata: TodoMetadata {
+                                extra,
+                                ..item.metadata
+                            },
+                            note: item.note,
+                            path: item.path,
+                            line_number: item.line_number,
+                            cell: item.cell,
+                            delimiter: item.delimiter,
+                            marker: item.marker,
+                            raw: item.raw,
+                            action: UpdateAction::Edit,
+                        }
+                    })
+                    .collect();
+
+                if updates.is_empty() {
+                    cli_error("No duplicate metadata".to_owned());
+                } else {
+                    summary = apply_updates_confirmed(updates, cli.force, prompt.as_mut(), &config)
+                        .unwrap_or_else(|e| cli_error(e));
+                    println!("All duplicate metadata was removed.")
+                }
+            }
+            CodeMod::PruneEmptyMeta => {
+                let updates: Vec<TodoUpdate> = matches
+                    .into_iter()
+                    .filter(|todo| todo.has_empty_metadata())
+                    .map(|item| TodoUpdate {
+                        metadata: item.metadata,
+                        note: item.note,
+                        path: item.path,
+                        line_number: item.line_number,
+                        cell: item.cell,
+                        delimiter: item.delimiter,
+                        marker: item.marker,
+                        raw: item.raw,
+                        action: UpdateAction::Edit,
+                    })
+                    .collect();
+
+                if updates.is_empty() {
+                    cli_error("No empty metadata".to_owned());
+                } else {
+                    summary = apply_updates_confirmed(updates, cli.force, prompt.as_mut(), &config)
+                        .unwrap_or_else(|e| cli_error(e));
+                    println!("All empty metadata was removed.")
+                }
+            }
             CodeMod::RenameIssue { from, to } => {
                 let to_issue = parse_issue(&to)
                     .ok_or_else(|| cli_error(format!("Invalid replacement issue \"{}\"", to)))?;
@@ -961,7 +5528,11 @@ fn main() -> Result<(), ()> {
                             note: item.note,
                             path: item.path,
                             line_number: item.line_number,
+                            cell: item.cell,
                             delimiter: item.delimiter,
+                            marker: item.marker,
+                            raw: item.raw,
+                            action: UpdateAction::Edit,
                         }
                     })
                     .collect();
@@ -969,11 +5540,24 @@ fn main() -> Result<(), ()> {
                 if updates.is_empty() {
                     cli_error(format!("No TODOs citing issue \"{}\"", from));
                 } else {
-                    apply_updates(updates);
+                    summary = apply_updates_confirmed(updates, cli.force, prompt.as_mut(), &config)
+                        .unwrap_or_else(|e| cli_error(e));
                     println!("All TODOs citing issue \"{}\" assigned to \"{}\"", from, to)
                 }
             }
-            CodeMod::AddIssueForAllUntracked { issue } => {
+            CodeMod::AddIssueForAllUntracked { issue, from_branch } => {
+                let issue = if from_branch {
+                    let branch = git::current_branch()
+                        .unwrap_or_else(|| cli_error("Not on a git branch".to_owned()));
+                    issue_from_branch_name(&branch)
+                        .map(|i| i.as_string())
+                        .unwrap_or_else(|| {
+                            cli_error(format!("No issue key in branch name \"{}\"", branch))
+                        })
+                } else {
+                    issue.unwrap_or_default()
+                };
+
                 let valid_issue = parse_issue(&issue)
                     .ok_or_else(|| cli_error(format!("Invalid issue \"{}\"", issue)))?;
 
@@ -991,7 +5575,11 @@ fn main() -> Result<(), ()> {
                             note: item.note,
                             path: item.path,
                             line_number: item.line_number,
+                            cell: item.cell,
                             delimiter: item.delimiter,
+                            marker: item.marker,
+                            raw: item.raw,
+                            action: UpdateAction::Edit,
                         }
                     })
                     .collect();
@@ -999,17 +5587,24 @@ fn main() -> Result<(), ()> {
                 if updates.is_empty() {
                     cli_error("No TODOs untracked".to_owned());
                 } else {
-                    apply_updates(updates);
+                    summary = apply_updates_confirmed(updates, cli.force, prompt.as_mut(), &config)
+                        .unwrap_or_else(|e| cli_error(e));
                     println!("All untracked TODOs now cite issue \"{}\".", issue)
                 }
             }
             CodeMod::RemoveAssignee { assignee } => {
                 let updates: Vec<TodoUpdate> = matches
                     .into_iter()
-                    .filter(|todo| todo.metadata.assignee == Some(assignee.to_owned()))
+                    .filter(|todo| todo.metadata.assignees.contains(&assignee))
                     .map(|item| {
                         let new_metadata = TodoMetadata {
-                            assignee: None,
+                            assignees: item
+                                .metadata
+                                .assignees
+                                .iter()
+                                .filter(|a| **a != assignee)
+                                .cloned()
+                                .collect(),
                             ..item.metadata
                         };
 
@@ -1018,7 +5613,11 @@ fn main() -> Result<(), ()> {
                             note: item.note,
                             path: item.path,
                             line_number: item.line_number,
+                            cell: item.cell,
                             delimiter: item.delimiter,
+                            marker: item.marker,
+                            raw: item.raw,
+                            action: UpdateAction::Edit,
                         }
                     })
                     .collect();
@@ -1026,17 +5625,18 @@ fn main() -> Result<(), ()> {
                 if updates.is_empty() {
                     cli_error(format!("No TODOs assigned to \"{}\"", assignee));
                 } else {
-                    apply_updates(updates);
+                    summary = apply_updates_confirmed(updates, cli.force, prompt.as_mut(), &config)
+                        .unwrap_or_else(|e| cli_error(e));
                     println!("All TODOs assigned to \"{}\" were unassigned.", assignee)
                 }
             }
             CodeMod::RemoveAllAssignees => {
                 let updates: Vec<TodoUpdate> = matches
                     .into_iter()
-                    .filter(|todo| todo.metadata.assignee != None)
+                    .filter(|todo| !todo.metadata.assignees.is_empty())
                     .map(|item| {
                         let new_metadata = TodoMetadata {
-                            assignee: None,
+                            assignees: vec![],
                             ..item.metadata
                         };
 
@@ -1045,7 +5645,11 @@ fn main() -> Result<(), ()> {
                             note: item.note,
                             path: item.path,
                             line_number: item.line_number,
+                            cell: item.cell,
                             delimiter: item.delimiter,
+                            marker: item.marker,
+                            raw: item.raw,
+                            action: UpdateAction::Edit,
                         }
                     })
                     .collect();
@@ -1053,17 +5657,25 @@ fn main() -> Result<(), ()> {
                 if updates.is_empty() {
                     cli_error("No TODOs assigned".to_owned());
                 } else {
-                    apply_updates(updates);
+                    summary = apply_updates_confirmed(updates, cli.force, prompt.as_mut(), &config)
+                        .unwrap_or_else(|e| cli_error(e));
                     println!("All TODOs were unassigned.")
                 }
             }
             CodeMod::RenameAssignee { from, to } => {
                 let updates: Vec<TodoUpdate> = matches
                     .into_iter()
-                    .filter(|todo| todo.metadata.assignee == Some(from.to_owned()))
+                    .filter(|todo| todo.metadata.assignees.contains(&from))
                     .map(|item| {
+                        let mut assignees: Vec<String> = vec![];
+                        for assignee in &item.metadata.assignees {
+                            let assignee = if *assignee == from { &to } else { assignee };
+                            if !assignees.contains(assignee) {
+                                assignees.push(assignee.clone());
+                            }
+                        }
                         let new_metadata = TodoMetadata {
-                            assignee: Some(to.clone()),
+                            assignees,
                             ..item.metadata
                         };
 
@@ -1072,7 +5684,11 @@ fn main() -> Result<(), ()> {
                             note: item.note,
                             path: item.path,
                             line_number: item.line_number,
+                            cell: item.cell,
                             delimiter: item.delimiter,
+                            marker: item.marker,
+                            raw: item.raw,
+                            action: UpdateAction::Edit,
                         }
                     })
                     .collect();
@@ -1080,7 +5696,8 @@ fn main() -> Result<(), ()> {
                 if updates.is_empty() {
                     cli_error(format!("No TODOs assigned to \"{}\"", from));
                 } else {
-                    apply_updates(updates);
+                    summary = apply_updates_confirmed(updates, cli.force, prompt.as_mut(), &config)
+                        .unwrap_or_else(|e| cli_error(e));
                     println!(
                         "All TODOs assigned to \"{}\" were reassigned to \"{}\"",
                         from, to
@@ -1090,10 +5707,10 @@ fn main() -> Result<(), ()> {
             CodeMod::AssignUnassigned { assignee } => {
                 let updates: Vec<TodoUpdate> = matches
                     .into_iter()
-                    .filter(|todo| todo.metadata.assignee == None)
+                    .filter(|todo| todo.metadata.assignees.is_empty())
                     .map(|item| {
                         let new_metadata = TodoMetadata {
-                            assignee: Some(assignee.clone()),
+                            assignees: vec![assignee.clone()],
                             ..item.metadata
                         };
 
@@ -1102,7 +5719,11 @@ fn main() -> Result<(), ()> {
                             note: item.note,
                             path: item.path,
                             line_number: item.line_number,
+                            cell: item.cell,
                             delimiter: item.delimiter,
+                            marker: item.marker,
+                            raw: item.raw,
+                            action: UpdateAction::Edit,
                         }
                     })
                     .collect();
@@ -1110,10 +5731,49 @@ fn main() -> Result<(), ()> {
                 if updates.is_empty() {
                     cli_error("No TODOs unassigned".to_owned());
                 } else {
-                    apply_updates(updates);
+                    summary = apply_updates_confirmed(updates, cli.force, prompt.as_mut(), &config)
+                        .unwrap_or_else(|e| cli_error(e));
                     println!("All unassigned TODOs assigned to \"{}\"", assignee)
                 }
             }
+            CodeMod::AssignByRules => {
+                if config.owners.is_empty() {
+                    cli_error("No owners configured, add an [owners] table mapping path globs to assignees to the config".to_owned());
+                }
+                let owner_rules = OwnerRules::new(&config.owners).unwrap_or_else(|e| cli_error(e));
+
+                let updates: Vec<TodoUpdate> = matches
+                    .into_iter()
+                    .filter(|todo| todo.metadata.assignees.is_empty())
+                    .filter_map(|item| {
+                        let owner = owner_rules.owner(&item.path)?;
+                        let new_metadata = TodoMetadata {
+                            assignees: vec![owner.to_owned()],
+                            ..item.metadata
+                        };
+
+                        Some(TodoUpdate {
+                            metadata: new_metadata,
+                            note: item.note,
+                            path: item.path,
+                            line_number: item.line_number,
+                            cell: item.cell,
+                            delimiter: item.delimiter,
+                            marker: item.marker,
+                            raw: item.raw,
+                            action: UpdateAction::Edit,
+                        })
+                    })
+                    .collect();
+
+                if updates.is_empty() {
+                    cli_error("No unassigned TODOs in owned paths".to_owned());
+                } else {
+                    summary = apply_updates_confirmed(updates, cli.force, prompt.as_mut(), &config)
+                        .unwrap_or_else(|e| cli_error(e));
+                    println!("Unassigned TODOs assigned to the owners of their paths")
+                }
+            }
             CodeMod::AssignIssue { issue, assignee } => {
                 let updates: Vec<TodoUpdate> = matches
                     .into_iter()
@@ -1123,7 +5783,7 @@ fn main() -> Result<(), ()> {
                     })
                     .map(|item| {
                         let new_metadata = TodoMetadata {
-                            assignee: Some(assignee.clone()),
+                            assignees: vec![assignee.clone()],
                             ..item.metadata
                         };
 
@@ -1132,7 +5792,11 @@ fn main() -> Result<(), ()> {
                             note: item.note,
                             path: item.path,
                             line_number: item.line_number,
+                            cell: item.cell,
                             delimiter: item.delimiter,
+                            marker: item.marker,
+                            raw: item.raw,
+                            action: UpdateAction::Edit,
                         }
                     })
                     .collect();
@@ -1140,7 +5804,8 @@ fn main() -> Result<(), ()> {
                 if updates.is_empty() {
                     cli_error(format!("No TODOs citing issue \"{}\"", issue));
                 } else {
-                    apply_updates(updates);
+                    summary = apply_updates_confirmed(updates, cli.force, prompt.as_mut(), &config)
+                        .unwrap_or_else(|e| cli_error(e));
                     println!(
                         "All TODOs citing issue \"{}\" assigned to \"{}\"",
                         issue, assignee
@@ -1150,10 +5815,13 @@ fn main() -> Result<(), ()> {
             CodeMod::RemoveAllDueDates => {
                 let updates: Vec<TodoUpdate> = matches
                     .into_iter()
-                    .filter(|todo| todo.metadata.due != None)
+                    .filter(|todo| {
+                        todo.metadata.due.is_some() || todo.metadata.invalid_due.is_some()
+                    })
                     .map(|item| {
                         let new_metadata = TodoMetadata {
                             due: None,
+                            invalid_due: None,
                             ..item.metadata
                         };
 
@@ -1162,7 +5830,11 @@ fn main() -> Result<(), ()> {
                             note: item.note,
                             path: item.path,
                             line_number: item.line_number,
+                            cell: item.cell,
                             delimiter: item.delimiter,
+                            marker: item.marker,
+                            raw: item.raw,
+                            action: UpdateAction::Edit,
                         }
                     })
                     .collect();
@@ -1170,14 +5842,15 @@ fn main() -> Result<(), ()> {
                 if updates.is_empty() {
                     cli_error("No TODOs with due dates".to_owned());
                 } else {
-                    apply_updates(updates);
+                    summary = apply_updates_confirmed(updates, cli.force, prompt.as_mut(), &config)
+                        .unwrap_or_else(|e| cli_error(e));
                     println!("All TODO due dates were removed.")
                 }
             }
             CodeMod::AddMissingDueDates { date } => {
                 let updates: Vec<TodoUpdate> = matches
                     .into_iter()
-                    .filter(|todo| todo.metadata.due == None)
+                    .filter(|todo| todo.metadata.due.is_none())
                     .map(|item| {
                         let new_metadata = TodoMetadata {
                             due: Some(date.clone()),
@@ -1189,7 +5862,11 @@ fn main() -> Result<(), ()> {
                             note: item.note,
                             path: item.path,
                             line_number: item.line_number,
+                            cell: item.cell,
                             delimiter: item.delimiter,
+                            marker: item.marker,
+                            raw: item.raw,
+                            action: UpdateAction::Edit,
                         }
                     })
                     .collect();
@@ -1197,7 +5874,8 @@ fn main() -> Result<(), ()> {
                 if updates.is_empty() {
                     cli_error("No TODOs without due dates".to_owned());
                 } else {
-                    apply_updates(updates);
+                    summary = apply_updates_confirmed(updates, cli.force, prompt.as_mut(), &config)
+                        .unwrap_or_else(|e| cli_error(e));
                     println!(
                         "All TODO without due dates were set to be due \"{}\".",
                         date
@@ -1222,7 +5900,11 @@ fn main() -> Result<(), ()> {
                             note: item.note,
                             path: item.path,
                             line_number: item.line_number,
+                            cell: item.cell,
                             delimiter: item.delimiter,
+                            marker: item.marker,
+                            raw: item.raw,
+                            action: UpdateAction::Edit,
                         }
                     })
                     .collect();
@@ -1230,15 +5912,162 @@ fn main() -> Result<(), ()> {
                 if updates.is_empty() {
                     cli_error(format!("No TODOs citing issue \"{}\"", issue));
                 } else {
-                    apply_updates(updates);
+                    summary = apply_updates_confirmed(updates, cli.force, prompt.as_mut(), &config)
+                        .unwrap_or_else(|e| cli_error(e));
                     println!(
                         "All TODO citing issue \"{}\" to be due \"{}\".",
                         issue, date
                     )
                 }
             }
+            CodeMod::Snooze { issue, until } => {
+                let date = parse_due_str(&until, &config)
+                    .unwrap_or_else(|| cli_error(format!("--until={} not supported", until)));
+
+                let updates: Vec<TodoUpdate> = matches
+                    .into_iter()
+                    .filter(|todo| {
+                        todo.metadata.issue.as_ref().map(|i| i.as_string())
+                            == Some(issue.to_owned())
+                    })
+                    .map(|item| {
+                        let new_metadata = TodoMetadata {
+                            snooze: Some(date.clone()),
+                            ..item.metadata
+                        };
+
+                        TodoUpdate {
+                            metadata: new_metadata,
+                            note: item.note,
+                            path: item.path,
+                            line_number: item.line_number,
+                            cell: item.cell,
+                            delimiter: item.delimiter,
+                            marker: item.marker,
+                            raw: item.raw,
+                            action: UpdateAction::Edit,
+                        }
+                    })
+                    .collect();
+
+                if updates.is_empty() {
+                    cli_error(format!("No TODOs citing issue \"{}\"", issue));
+                } else {
+                    summary = apply_updates_confirmed(updates, cli.force, prompt.as_mut(), &config)
+                        .unwrap_or_else(|e| cli_error(e));
+                    println!(
+                        "All TODOs citing issue \"{}\" snoozed until \"{}\".",
+                        issue, date
+                    )
+                }
+            }
+            CodeMod::ChangeDelimiter { to } => {
+                if let Some(to) = &to {
+                    if !DELIMITERS.contains(&to.as_str()) {
+                        cli_error(format!(
+                            "--to={} not supported, use one of {}",
+                            to,
+                            DELIMITERS.join(", ")
+                        ));
+                    }
+                } else if config.comment_styles.is_empty() {
+                    cli_error("Pass --to or configure [comment_styles]".to_owned());
+                }
+
+                let updates: Vec<TodoUpdate> = matches
+                    .into_iter()
+                    .filter_map(|item| {
+                        let extension =
+                            item.path.extension().and_then(|e| e.to_str()).unwrap_or("");
+                        let target = to
+                            .as_ref()
+                            .or_else(|| config.comment_styles.get(extension))?;
+                        if *target == item.delimiter
+                            || !supported_delimiters(extension).contains(&target.as_str())
+                        {
+                            return None;
+                        }
+
+                        let note = change_note_delimiter(&item.delimiter, &item.note, target)?;
+
+                        Some(TodoUpdate {
+                            metadata: item.metadata,
+                            note,
+                            path: item.path,
+                            line_number: item.line_number,
+                            cell: item.cell,
+                            delimiter: target.to_owned(),
+                            marker: item.marker,
+                            raw: item.raw,
+                            action: UpdateAction::Edit,
+                        })
+                    })
+                    .collect();
+
+                if updates.is_empty() {
+                    cli_error("No TODOs to change the delimiter of".to_owned());
+                } else {
+                    summary = apply_updates_confirmed(updates, cli.force, prompt.as_mut(), &config)
+                        .unwrap_or_else(|e| cli_error(e));
+                    println!("TODO delimiters changed.")
+                }
+            }
+            CodeMod::RemoveDone => {
+                let updates: Vec<TodoUpdate> = find_done_markers(&config, &scan_options)
+                    .into_iter()
+                    .filter(|item| cli.include_generated || !item.generated)
+                    .map(|item| TodoUpdate {
+                        metadata: item.metadata,
+                        note: item.note,
+                        path: item.path,
+                        line_number: item.line_number,
+                        cell: item.cell,
+                        delimiter: item.delimiter,
+                        marker: item.marker,
+                        raw: item.raw,
+                        action: UpdateAction::Delete,
+                    })
+                    .collect();
+
+                if updates.is_empty() {
+                    cli_error("No DONE or RESOLVED comments".to_owned());
+                } else {
+                    summary = apply_updates_confirmed(updates, cli.force, prompt.as_mut(), &config)
+                        .unwrap_or_else(|e| cli_error(e));
+                    println!("DONE and RESOLVED comments removed.")
+                }
+            }
+            CodeMod::Apply { .. } => {
+                let mut input = String::new();
+                std::io::stdin()
+                    .read_to_string(&mut input)
+                    .unwrap_or_else(|e| cli_error(format!("Failed to read stdin: {}", e)));
+
+                let edits = apply::parse_edits(&input).unwrap_or_else(|e| cli_error(e));
+                if edits.is_empty() {
+                    cli_error("No edits on stdin".to_owned());
+                }
+                let updates =
+                    apply::updates(edits, &matches, &config).unwrap_or_else(|e| cli_error(e));
+
+                let count = updates.len();
+                summary = match updates.is_empty() {
+                    true => UpdateSummary::default(),
+                    false => apply_updates_confirmed(updates, cli.force, prompt.as_mut(), &config)
+                        .unwrap_or_else(|e| cli_error(e)),
+                };
+                println!("{} TODOs edited.", count)
+            }
         },
     }
 
+    if let Some(verbose) = mod_verbose {
+        println!("{}", summary.as_cli_result(verbose));
+        if let Some(prompt) = prompt.filter(|prompt| prompt.skipped > 0) {
+            println!("Files left unchanged ({})", prompt.skipped);
+        }
+    }
+
+    stats::finish_command();
     Ok(())
 }
@@ -0,0 +1,117 @@
+use serde::Deserialize;
+
+/// Whether `validate` fails on violations in a marker's comments, or only
+/// warns about them.
+#[derive(Clone, Copy, Deserialize, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum Severity {
+    Error,
+    Warning,
+}
+
+/// A keyword comments are scanned for, like `TODO` or `HACK`.
+#[derive(Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Marker {
+    /// The keyword as `format` writes it, matched in any case. Unlike
+    /// `TODO`, it must be followed by metadata or a colon, so prose like
+    /// `Note that` isn't taken for a marker.
+    pub keyword: String,
+    pub severity: Severity,
+    /// Whether `validate` requires assignees, issues, and due dates per its
+    /// flags. Without, markers like `NOTE` are only checked for format.
+    pub metadata: bool,
+}
+
+impl Marker {
+    /// Whether the text after the keyword marks a comment, which for markers
+    /// besides `TODO` must start with their metadata or a colon.
+    pub fn is_marked(&self, rest: &str) -> bool {
+        self.keyword == TODO || rest.starts_with(['(', ':'])
+    }
+}
+
+impl Default for Marker {
+    fn default() -> Self {
+        Marker {
+            keyword: String::new(),
+            severity: Severity::Error,
+            metadata: true,
+        }
+    }
+}
+
+/// The keyword of TODOs, which is always scanned for.
+pub const TODO: &str = "TODO";
+
+/// Keywords that can't be markers: `FIXME` and the ones marking finished
+/// work, which `validate` reports on its own.
+const RESERVED: [&str; 3] = ["FIXME", "DONE", "RESOLVED"];
+
+/// Every keyword a scan looks for: the markers' and the reserved ones.
+pub fn scanned_keywords(markers: &[Marker]) -> Vec<&str> {
+    markers
+        .iter()
+        .map(|marker| marker.keyword.as_str())
+        .chain(RESERVED)
+        .collect()
+}
+
+/// A regex alternation matching any of the markers' keywords in any case.
+pub fn keyword_pattern(markers: &[Marker]) -> String {
+    let keywords: Vec<&str> = markers
+        .iter()
+        .map(|marker| marker.keyword.as_str())
+        .collect();
+    format!("(?:(?i){})", keywords.join("|"))
+}
+
+/// The configured markers after the default `TODO` one, unless they
+/// configure `TODO` themselves.
+pub fn registry(configured: Vec<Marker>) -> Result<Vec<Marker>, String> {
+    let mut markers: Vec<Marker> = vec![];
+    if !configured.iter().any(|marker| marker.keyword == TODO) {
+        markers.push(Marker {
+            keyword: TODO.to_owned(),
+            ..Marker::default()
+        });
+    }
+
+    for marker in configured {
+        let valid = marker
+            .keyword
+            .chars()
+            .next()
+            .is_some_and(|c| c.is_ascii_alphabetic())
+            && marker
+                .keyword
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || c == '_');
+        if !valid {
+            return Err(format!(
+                "\"{}\" isn't a keyword, which is letters, digits, and underscores",
+                marker.keyword
+            ));
+        }
+
+        if marker.keyword != TODO && marker.keyword.eq_ignore_ascii_case(TODO) {
+            return Err(format!("\"{}\" should be written TODO", marker.keyword));
+        }
+        if RESERVED
+            .iter()
+            .any(|reserved| marker.keyword.eq_ignore_ascii_case(reserved))
+        {
+            return Err(format!("\"{}\" is reserved", marker.keyword));
+        }
+        if markers
+            .iter()
+            .any(|known| known.keyword.eq_ignore_ascii_case(&marker.keyword))
+        {
+            return Err(format!("\"{}\" is listed twice", marker.keyword));
+        }
+
+        markers.push(marker);
+    }
+
+    Ok(markers)
+}
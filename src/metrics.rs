@@ -0,0 +1,117 @@
+use std::collections::BTreeMap;
+
+use chrono::NaiveDate;
+use serde_json::{json, Value};
+
+use crate::{is_overdue, parse_due_date, Grouping, Todo};
+
+/// How far behind one assignee is, for `stat --metrics`.
+pub struct AssigneeMetrics {
+    /// `None` for unassigned TODOs.
+    assignee: Option<String>,
+    total: usize,
+    overdue: usize,
+    days_overdue: i64,
+}
+
+impl AssigneeMetrics {
+    fn label(&self) -> String {
+        self.assignee.clone().unwrap_or("<unassigned>".to_owned())
+    }
+
+    fn overdue_ratio(&self) -> f64 {
+        self.overdue as f64 / self.total as f64
+    }
+
+    /// Average over the overdue TODOs only, if there are any.
+    fn average_days_overdue(&self) -> Option<f64> {
+        (self.overdue > 0).then(|| self.days_overdue as f64 / self.overdue as f64)
+    }
+}
+
+/// Metrics per assignee, with unassigned TODOs last. TODOs with several
+/// assignees count for each.
+pub fn per_assignee(todos: &[Todo], today: NaiveDate) -> Vec<AssigneeMetrics> {
+    let mut metrics: BTreeMap<Option<String>, AssigneeMetrics> = BTreeMap::new();
+    for todo in todos {
+        let assignees: Vec<Option<String>> = match todo.metadata.assignees.as_slice() {
+            [] => vec![None],
+            assignees => assignees.iter().cloned().map(Some).collect(),
+        };
+        for assignee in assignees {
+            let entry = metrics
+                .entry(assignee.clone())
+                .or_insert_with(|| AssigneeMetrics {
+                    assignee,
+                    total: 0,
+                    overdue: 0,
+                    days_overdue: 0,
+                });
+
+            entry.total += 1;
+            let due = todo.due().to_owned().and_then(parse_due_date);
+            if let Some(due) = due.filter(|_| is_overdue(todo)) {
+                entry.overdue += 1;
+                entry.days_overdue += (today - due).num_days();
+            }
+        }
+    }
+
+    let mut metrics: Vec<AssigneeMetrics> = metrics.into_values().collect();
+    metrics.sort_by(|a, b| Grouping::Assignee.compare_keys(&a.label(), &b.label()));
+    metrics
+}
+
+pub fn as_table(metrics: &[AssigneeMetrics]) -> String {
+    let width = metrics
+        .iter()
+        .map(|m| m.label().chars().count())
+        .chain(["assignee".len()])
+        .max()
+        .unwrap_or(0);
+
+    let header = format!(
+        "{:<width$}  {:>5}  {:>7}  {:>6}  {:>16}",
+        "assignee",
+        "total",
+        "overdue",
+        "ratio",
+        "avg days overdue",
+        width = width
+    );
+    let rows = metrics.iter().map(|m| {
+        format!(
+            "{:<width$}  {:>5}  {:>7}  {:>5.1}%  {:>16}",
+            m.label(),
+            m.total,
+            m.overdue,
+            m.overdue_ratio() * 100.0,
+            m.average_days_overdue()
+                .map(|days| format!("{:.1}", days))
+                .unwrap_or("-".to_owned()),
+            width = width
+        )
+    });
+
+    std::iter::once(header)
+        .chain(rows)
+        .collect::<Vec<String>>()
+        .join("\n")
+}
+
+pub fn as_json(metrics: &[AssigneeMetrics]) -> Value {
+    let assignees: Vec<Value> = metrics
+        .iter()
+        .map(|m| {
+            json!({
+                "assignee": m.assignee,
+                "total": m.total,
+                "overdue": m.overdue,
+                "overdue_ratio": m.overdue_ratio(),
+                "average_days_overdue": m.average_days_overdue(),
+            })
+        })
+        .collect();
+
+    json!({ "assignees": assignees })
+}
@@ -0,0 +1,78 @@
+use std::path::Path;
+
+/// Unchanged lines shown around each change, as `diff -u` does.
+const CONTEXT: usize = 3;
+
+/// Appends the line with its prefix, marking a missing final newline the
+/// way `diff` does.
+fn push_line(diff: &mut String, prefix: char, line: &str) {
+    diff.push(prefix);
+    diff.push_str(line);
+    if !line.ends_with('\n') {
+        diff.push_str("\n\\ No newline at end of file\n");
+    }
+}
+
+/// A unified diff replacing lines of the file, with `edits` holding the index
+/// of each line replaced, in order, and the lines replacing it, with endings.
+pub fn unified_diff(path: &Path, old_lines: &[String], edits: &[(usize, Vec<String>)]) -> String {
+    let display = path.display().to_string();
+    let name = display.strip_prefix("./").unwrap_or(&display);
+    let mut diff = format!("--- a/{}\n+++ b/{}\n", name, name);
+
+    // Changes with few enough lines between them share a hunk.
+    let mut hunks: Vec<&[(usize, Vec<String>)]> = vec![];
+    let mut first = 0;
+    for index in 1..=edits.len() {
+        if index == edits.len() || edits[index].0 - edits[index - 1].0 > 2 * CONTEXT {
+            hunks.push(&edits[first..index]);
+            first = index;
+        }
+    }
+
+    // How many lines the changes before the hunk added, less those removed.
+    let mut offset: isize = 0;
+    for hunk in hunks {
+        let start = hunk[0].0.saturating_sub(CONTEXT);
+        let end = (hunk[hunk.len() - 1].0 + CONTEXT + 1).min(old_lines.len());
+
+        let mut body = String::new();
+        let mut new_count = 0;
+        let mut edits = hunk.iter().peekable();
+        for (index, line) in old_lines.iter().enumerate().take(end).skip(start) {
+            match edits.next_if(|(edited, _)| *edited == index) {
+                Some((_, replacement)) => {
+                    push_line(&mut body, '-', line);
+                    for new_line in replacement {
+                        push_line(&mut body, '+', new_line);
+                    }
+                    new_count += replacement.len();
+                }
+                None => {
+                    push_line(&mut body, ' ', line);
+                    new_count += 1;
+                }
+            }
+        }
+
+        let old_count = end - start;
+        let new_start = (start as isize + offset) as usize + 1;
+        // Empty ranges start at the line before, per the format.
+        let new_start = if new_count == 0 {
+            new_start - 1
+        } else {
+            new_start
+        };
+        diff.push_str(&format!(
+            "@@ -{},{} +{},{} @@\n{}",
+            start + 1,
+            old_count,
+            new_start,
+            new_count,
+            body
+        ));
+        offset += new_count as isize - old_count as isize;
+    }
+
+    diff
+}
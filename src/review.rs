@@ -0,0 +1,290 @@
+use std::fs;
+use std::io::{BufRead, Write};
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+use chrono::{Duration, Local};
+use serde::{Deserialize, Serialize};
+
+use crate::config::Config;
+use crate::{
+    apply_updates, is_valid_assignee, snippet_context, Todo, TodoMetadata, TodoUpdate, UpdateAction,
+};
+
+const STATE_PATH: &str = ".codo/state.json";
+
+/// The root of the repository, or the working directory outside of one.
+/// Snoozed paths are relative to it, so they match wherever codo runs from.
+fn state_root() -> PathBuf {
+    let root = crate::git::repo_root().unwrap_or_else(|| PathBuf::from("."));
+    root.canonicalize().unwrap_or(root)
+}
+
+/// Lines of code shown before and after each TODO.
+const CONTEXT_LINES: usize = 2;
+
+const DEFAULT_SNOOZE_DAYS: i64 = 7;
+
+/// A TODO skipped by `review` until `until`. It's recognized by its location
+/// and text, so the snooze ends early if the line moves or changes.
+#[derive(Serialize, Deserialize)]
+struct Snooze {
+    path: PathBuf,
+    cell: Option<usize>,
+    line: u64,
+    raw: String,
+    /// The date as YYYY-MM-DD, which sorts chronologically.
+    until: String,
+}
+
+impl Snooze {
+    /// Whether the snooze is for the TODO, found at `path` under the root.
+    fn matches(&self, todo: &Todo, path: &Path) -> bool {
+        self.path == path
+            && self.cell == todo.cell
+            && self.line == todo.line_number
+            && self.raw == todo.raw
+    }
+}
+
+/// The path relative to `root`, or absolute if it's outside of it.
+fn root_relative(path: &Path, root: &Path) -> PathBuf {
+    let absolute = path.canonicalize().unwrap_or(path.to_owned());
+    absolute
+        .strip_prefix(root)
+        .map(Path::to_path_buf)
+        .unwrap_or(absolute)
+}
+
+/// What `review` remembers between runs, in `.codo/state.json` under the
+/// state root.
+#[derive(Default, Serialize, Deserialize)]
+struct ReviewState {
+    snoozes: Vec<Snooze>,
+    #[serde(skip)]
+    root: PathBuf,
+}
+
+impl ReviewState {
+    fn load() -> Result<Self, String> {
+        let root = state_root();
+        let path = root.join(STATE_PATH);
+        let state = match fs::read_to_string(&path) {
+            Ok(contents) => serde_json::from_str(&contents)
+                .map_err(|e| format!("Invalid {}: {}", path.display(), e))?,
+            // Anything but a missing file is an error, so the snoozes in it
+            // aren't overwritten.
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => ReviewState::default(),
+            Err(e) => return Err(format!("Failed to read {}: {}", path.display(), e)),
+        };
+        Ok(ReviewState { root, ..state })
+    }
+
+    fn save(&self) -> Result<(), String> {
+        let path = self.root.join(STATE_PATH);
+        let error =
+            |e: &dyn std::fmt::Display| format!("Failed to write {}: {}", path.display(), e);
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent).map_err(|e| error(&e))?;
+        }
+
+        let contents = serde_json::to_string_pretty(self).map_err(|e| error(&e))?;
+        fs::write(&path, contents).map_err(|e| error(&e))
+    }
+
+    fn is_snoozed(&self, todo: &Todo, today: &str) -> bool {
+        let path = root_relative(&todo.path, &self.root);
+        self.snoozes
+            .iter()
+            .any(|snooze| snooze.until.as_str() > today && snooze.matches(todo, &path))
+    }
+}
+
+fn prompt(input: &mut impl BufRead, question: &str) -> Option<String> {
+    print!("{} ", question);
+    let _ = std::io::stdout().flush();
+
+    let mut answer = String::new();
+    match input.read_line(&mut answer) {
+        Ok(0) | Err(_) => None,
+        Ok(_) => Some(answer.trim().to_owned()),
+    }
+}
+
+fn print_todo(todo: &Todo, position: usize, count: usize, config: &Config) {
+    println!("\n[{}/{}] {}", position, count, todo.as_search_result());
+
+    let Ok(context) = snippet_context(todo, CONTEXT_LINES, config) else {
+        return;
+    };
+    for line in context.as_array().into_iter().flatten() {
+        let number = line["line"].as_u64().unwrap_or(0);
+        let marker = if number == todo.line_number { ">" } else { " " };
+        println!(
+            "{} {:>4} | {}",
+            marker,
+            number,
+            line["text"].as_str().unwrap_or("")
+        );
+    }
+}
+
+/// Opens `$VISUAL` or `$EDITOR` (falling back to `vi`) at the TODO's line.
+fn open_editor(todo: &Todo) -> Result<(), String> {
+    let editor = std::env::var("VISUAL")
+        .or_else(|_| std::env::var("EDITOR"))
+        .unwrap_or("vi".to_owned());
+    let mut words = editor.split_whitespace();
+    let program = words.next().unwrap_or("vi");
+
+    let mut command = Command::new(program);
+    command.args(words);
+    // Notebook line numbers are relative to the cell, so just open the file.
+    if todo.cell.is_none() {
+        command.arg(format!("+{}", todo.line_number));
+    }
+
+    command
+        .arg(&todo.path)
+        .status()
+        .map_err(|e| format!("Failed to run {}: {}", editor, e))
+        .map(|_| ())
+}
+
+fn todo_update(todo: &Todo, metadata: TodoMetadata, action: UpdateAction) -> TodoUpdate {
+    TodoUpdate {
+        metadata,
+        note: todo.note.clone(),
+        path: todo.path.clone(),
+        line_number: todo.line_number,
+        cell: todo.cell,
+        delimiter: todo.delimiter.clone(),
+        marker: todo.marker.clone(),
+        raw: todo.raw.clone(),
+        action,
+    }
+}
+
+/// Steps through `todos`, asking what to do with each. Reassignments and
+/// deletions are written once the review ends, snoozes are saved to
+/// `.codo/state.json`.
+pub fn review(
+    todos: Vec<Todo>,
+    input: &mut impl BufRead,
+    force: bool,
+    config: &Config,
+) -> Result<(), String> {
+    let today = Local::now().date_naive();
+    let today_str = today.format("%Y-%m-%d").to_string();
+    let mut state = ReviewState::load()?;
+    let saved_count = state.snoozes.len();
+    state.snoozes.retain(|snooze| snooze.until > today_str);
+    let expired_count = saved_count - state.snoozes.len();
+
+    let todos: Vec<Todo> = todos
+        .into_iter()
+        .filter(|todo| !state.is_snoozed(todo, &today_str))
+        .collect();
+    if todos.is_empty() {
+        return Err("<no TODOs>".to_owned());
+    }
+
+    let mut updates: Vec<TodoUpdate> = vec![];
+    let mut snoozed_count = 0;
+    let mut reviewed_count = 0;
+
+    'todos: for (index, todo) in todos.iter().enumerate() {
+        print_todo(todo, index + 1, todos.len(), config);
+        reviewed_count += 1;
+
+        loop {
+            let Some(action) = prompt(
+                input,
+                "[s]nooze, [r]eassign, [d]elete, [e]dit, [n]ext, [q]uit?",
+            ) else {
+                break 'todos;
+            };
+
+            match action.as_str() {
+                "s" => {
+                    let days = prompt(input, &format!("Days [{}]:", DEFAULT_SNOOZE_DAYS))
+                        .filter(|days| !days.is_empty())
+                        .map(|days| days.parse::<i64>().ok().filter(|days| *days > 0))
+                        .unwrap_or(Some(DEFAULT_SNOOZE_DAYS));
+                    let Some(days) = days else {
+                        println!("Days must be a positive number.");
+                        continue;
+                    };
+                    let until = Duration::try_days(days)
+                        .and_then(|duration| today.checked_add_signed(duration));
+                    let Some(until) = until else {
+                        println!("Snoozing for {} days is past the last date.", days);
+                        continue;
+                    };
+
+                    let path = root_relative(&todo.path, &state.root);
+                    state.snoozes.push(Snooze {
+                        path,
+                        cell: todo.cell,
+                        line: todo.line_number,
+                        raw: todo.raw.clone(),
+                        until: until.format("%Y-%m-%d").to_string(),
+                    });
+                    snoozed_count += 1;
+                    println!("Snoozed for {} days.", days);
+                }
+                "r" => {
+                    let assignee =
+                        prompt(input, "Assignee (empty to unassign):").unwrap_or_default();
+                    let assignee = assignee.trim_start_matches('@');
+                    if !assignee.is_empty() && !is_valid_assignee(assignee) {
+                        println!("Invalid assignee \"{}\".", assignee);
+                        continue;
+                    }
+
+                    let metadata = TodoMetadata {
+                        assignees: Some(assignee.to_owned())
+                            .filter(|a| !a.is_empty())
+                            .into_iter()
+                            .collect(),
+                        ..todo.metadata.clone()
+                    };
+                    updates.push(todo_update(todo, metadata, UpdateAction::Edit));
+                }
+                "d" => {
+                    updates.push(todo_update(
+                        todo,
+                        todo.metadata.clone(),
+                        UpdateAction::Delete,
+                    ));
+                }
+                "e" => {
+                    if let Err(e) = open_editor(todo) {
+                        println!("{}", e);
+                    }
+                    continue;
+                }
+                "n" | "" => {}
+                "q" => break 'todos,
+                _ => continue,
+            }
+
+            break;
+        }
+    }
+
+    let changed_count = updates.len();
+    apply_updates(updates, force, config)?;
+    if snoozed_count > 0 || expired_count > 0 {
+        state.save()?;
+    }
+
+    println!(
+        "\nReviewed {} of {} TODOs: {} snoozed, {} changed.",
+        reviewed_count,
+        todos.len(),
+        snoozed_count,
+        changed_count
+    );
+    Ok(())
+}
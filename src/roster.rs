@@ -0,0 +1,112 @@
+use std::collections::BTreeMap;
+use std::fs;
+
+use crate::config::Config;
+use crate::{Grouping, Todo};
+
+/// The names in a roster file, one per line with an optional `@`. Blank
+/// lines and `#` comments are skipped.
+pub fn read(path: &str) -> Result<Vec<String>, String> {
+    let contents =
+        fs::read_to_string(path).map_err(|e| format!("Failed to read {}: {}", path, e))?;
+
+    Ok(contents
+        .lines()
+        .map(|line| line.split('#').next().unwrap_or("").trim())
+        .filter(|line| !line.is_empty())
+        .map(|name| name.trim_start_matches('@').to_owned())
+        .collect())
+}
+
+/// How the assignees of TODOs compare to who's on the roster.
+pub struct RosterGaps {
+    /// Assignees not on the roster, with how many TODOs they have.
+    pub unknown: Vec<(String, usize)>,
+    /// Roster members without any TODOs.
+    pub idle: Vec<String>,
+}
+
+/// Whether the roster entry is the assignee, by name or one of their
+/// `aliases` in the config.
+fn is_member(member: &str, assignee: &str, config: &Config) -> bool {
+    member == assignee
+        || config
+            .assignees
+            .get(assignee)
+            .is_some_and(|profile| profile.aliases.iter().any(|alias| alias == member))
+}
+
+/// The assignee a roster entry stands for, which differs when it's one of
+/// their `aliases` in the config.
+pub fn assignee_name(member: &str, config: &Config) -> String {
+    config
+        .assignees
+        .iter()
+        .find(|(_, profile)| profile.aliases.iter().any(|alias| alias == member))
+        .map_or(member, |(assignee, _)| assignee.as_str())
+        .to_owned()
+}
+
+pub fn compare(todos: &[Todo], roster: &[String], config: &Config) -> RosterGaps {
+    let mut counts: BTreeMap<&str, usize> = BTreeMap::new();
+    for assignee in todos
+        .iter()
+        .flat_map(|todo| todo.metadata.assignees.iter().map(String::as_str))
+    {
+        *counts.entry(assignee).or_insert(0) += 1;
+    }
+
+    let mut idle: Vec<String> = roster
+        .iter()
+        .filter(|member| {
+            !counts
+                .keys()
+                .any(|assignee| is_member(member, assignee, config))
+        })
+        .cloned()
+        .collect();
+    idle.sort_by(|a, b| Grouping::Assignee.compare_keys(a, b));
+    idle.dedup();
+
+    RosterGaps {
+        unknown: counts
+            .into_iter()
+            .filter(|(assignee, _)| {
+                !roster
+                    .iter()
+                    .any(|member| is_member(member, assignee, config))
+            })
+            .map(|(assignee, count)| (assignee.to_owned(), count))
+            .collect(),
+        idle,
+    }
+}
+
+impl RosterGaps {
+    pub fn as_text(&self) -> String {
+        let unknown: Vec<String> = self
+            .unknown
+            .iter()
+            .map(|(assignee, count)| format!("  {}: {}", assignee, count))
+            .collect();
+        let idle: Vec<String> = self
+            .idle
+            .iter()
+            .map(|member| format!("  {}", member))
+            .collect();
+
+        let section = |title: &str, lines: Vec<String>| {
+            if lines.is_empty() {
+                format!("{} (0): none", title)
+            } else {
+                format!("{} ({}):\n{}", title, lines.len(), lines.join("\n"))
+            }
+        };
+
+        format!(
+            "{}\n\n{}",
+            section("Assignees not on the roster", unknown),
+            section("Roster members without TODOs", idle)
+        )
+    }
+}
@@ -0,0 +1,360 @@
+use std::sync::OnceLock;
+
+use regex::Regex;
+
+/// Documentation of a `validate` rule, shown by `codo explain`.
+pub struct Rule {
+    pub name: &'static str,
+    summary: &'static str,
+    /// The error as `validate` reports it.
+    message: &'static str,
+    /// The flag or config setting that turns the rule on, if it's not always
+    /// checked.
+    enabled_by: Option<&'static str>,
+    details: &'static str,
+    invalid: &'static str,
+    valid: &'static str,
+    fix: &'static str,
+}
+
+pub const RULES: &[Rule] = &[
+    Rule {
+        name: "invalid-format",
+        summary: "TODOs are written the way format writes them",
+        message: "Invalid format",
+        enabled_by: None,
+        details: "Reported when a TODO differs from how `codo format` would write it, e.g. in spacing, date format, or the case of the TODO keyword.",
+        invalid: "// TODO(@alice,2025-01-05) fix the retry loop",
+        valid: "// TODO(@alice, 2025-01-05): fix the retry loop",
+        fix: "Run `codo format`.",
+    },
+    Rule {
+        name: "invalid-metadata-order",
+        summary: "Metadata is in the configured order",
+        message: "Invalid metadata order",
+        enabled_by: None,
+        details: "Reported when a TODO's metadata is otherwise well formatted but not in the order of `meta_order` in the config, by default issue, assignee, due date, priority, recurrence, and ID.",
+        invalid: "// TODO(@alice, #12): Retry on timeouts",
+        valid: "// TODO(#12, @alice): Retry on timeouts",
+        fix: "Run `codo format`, or set `meta_order = \"preserve\"` in the config to allow any order.",
+    },
+    Rule {
+        name: "unknown-metadata",
+        summary: "Every metadata token is recognized",
+        message: "Unknown metadata \"<token>\"",
+        enabled_by: Some("--forbid-unknown-metadata"),
+        details: "Reported for each token in the parentheses that isn't an assignee, issue, due date, priority, recurrence, or ID, which usually is a typo. Unknown tokens are kept as written by `format` and the code mods.",
+        invalid: "// TODO(@@alice): Retry on timeouts",
+        valid: "// TODO(@alice): Retry on timeouts",
+        fix: "Correct the token, or move it into the note.",
+    },
+    Rule {
+        name: "duplicate-metadata",
+        summary: "Each kind of metadata is given once",
+        message: "Duplicate metadata \"<token>\" (duplicate-metadata)",
+        enabled_by: None,
+        details: "Reported for tokens repeating a kind of metadata the TODO already has, like the same assignee again or a second due date. Only the first counts, the rest are kept as written.",
+        invalid: "// TODO(@alice, 2025-01-05, @alice, 2025-02-01): Retry on timeouts",
+        valid: "// TODO(@alice, 2025-01-05): Retry on timeouts",
+        fix: "Run `codo mod dedupe-metadata`, which keeps the first of each kind.",
+    },
+    Rule {
+        name: "empty-metadata",
+        summary: "Parentheses after TODO hold metadata",
+        message: "Empty metadata (empty-metadata)",
+        enabled_by: None,
+        details: "Reported for TODOs with parentheses holding nothing but whitespace and commas, usually left over after their metadata was removed by hand.",
+        invalid: "// TODO(): Retry on timeouts",
+        valid: "// TODO: Retry on timeouts",
+        fix: "Run `codo mod prune-empty-meta`, or `codo format`.",
+    },
+    Rule {
+        name: "issue-case",
+        summary: "Issues are written in their canonical case",
+        message: "Malformed issue \"<token>\", should be \"<issue>\" (issue-case)",
+        enabled_by: None,
+        details: "Reported for tokens that would be issues if not for their case or spacing, like a lowercase project key.",
+        invalid: "// TODO(proj-12): Retry on timeouts",
+        valid: "// TODO(PROJ-12): Retry on timeouts",
+        fix: "Run `codo mod normalize-issue-case`.",
+    },
+    Rule {
+        name: "invalid-due-date",
+        summary: "Due dates are real dates",
+        message: "Invalid due date \"<token>\" (invalid-due-date)",
+        enabled_by: None,
+        details: "Reported for `due:` tokens that aren't a date or weekday, and for tokens shaped like a date that doesn't exist, like February 30th. Such TODOs have no due date, and date filters like `--overdue` skip them with a warning rather than taking them as due someday.",
+        invalid: "// TODO(@alice, 2025-02-30): Retry on timeouts",
+        valid: "// TODO(@alice, 2025-02-28): Retry on timeouts",
+        fix: "Correct the date.",
+    },
+    Rule {
+        name: "missing-assignee",
+        summary: "TODOs are assigned",
+        message: "Missing assignee",
+        enabled_by: Some("--require-assignees, or require_assignees in [[rules]] in the config"),
+        details: "Reported for TODOs without an @assignee.",
+        invalid: "// TODO: Retry on timeouts",
+        valid: "// TODO(@alice): Retry on timeouts",
+        fix: "Assign the TODO, or use `codo mod assign-unassigned --assignee <name>`.",
+    },
+    Rule {
+        name: "missing-issue",
+        summary: "TODOs cite an issue",
+        message: "Missing issue",
+        enabled_by: Some("--require-issues, --require-issues-for <glob>, or require_issues in [[rules]] in the config"),
+        details: "Reported for TODOs without an issue like #12, PROJ-12, or an issue URL.",
+        invalid: "// TODO: Retry on timeouts",
+        valid: "// TODO(#12): Retry on timeouts",
+        fix: "Cite an issue, or use `codo mod add-issue-for-all-untracked --issue <issue>`.",
+    },
+    Rule {
+        name: "missing-due-date",
+        summary: "TODOs have a due date",
+        message: "Missing due date",
+        enabled_by: Some("--require-due-dates, or require_due_dates in [[rules]] in the config"),
+        details: "Reported for TODOs without a due date of their own or inherited from their issue through `issue_due_dates` in the config.",
+        invalid: "// TODO: Retry on timeouts",
+        valid: "// TODO(2025-01-05): Retry on timeouts",
+        fix: "Add a due date.",
+    },
+    Rule {
+        name: "priority-requirements",
+        summary: "High priority TODOs have a due date and an assignee",
+        message: "Missing due date (required for p<N>), Missing assignee (required for p<N>)",
+        enabled_by: Some("--require-due-for-priority <pN>"),
+        details: "Reported for TODOs of the given priority or higher, e.g. p0 and p1 for `p1`, without a due date or an assignee. Lower priorities and TODOs without one are exempt.",
+        invalid: "// TODO(p0): Retry on timeouts",
+        valid: "// TODO(@alice, 2025-01-05, p0): Retry on timeouts",
+        fix: "Add the missing due date or assignee, or lower the priority.",
+    },
+    Rule {
+        name: "missing-metadata",
+        summary: "TODOs have some metadata",
+        message: "Missing metadata",
+        enabled_by: Some("--forbid-bare, or forbid_bare in [[rules]] in the config"),
+        details: "Reported for TODOs with no assignee, issue, or due date. Any one of them is enough.",
+        invalid: "// TODO: Retry on timeouts",
+        valid: "// TODO(#12): Retry on timeouts",
+        fix: "Add an assignee, issue, or due date.",
+    },
+    Rule {
+        name: "invalid-assignee",
+        summary: "Assignees are on the allowed list",
+        message: "Invalid assignee",
+        enabled_by: Some("--allowed-assignees <name>..."),
+        details: "Reported for TODOs assigned to anyone not given to --allowed-assignees.",
+        invalid: "// TODO(@mallory): Retry on timeouts",
+        valid: "// TODO(@alice): Retry on timeouts",
+        fix: "Reassign the TODO, or use `codo mod rename-assignee --from <old> --to <new>`.",
+    },
+    Rule {
+        name: "single-assignee",
+        summary: "TODOs have one owner",
+        message: "Assigned to <count> people (single-assignee)",
+        enabled_by: Some("--single-assignee"),
+        details: "Reported for TODOs assigned to several people, for teams where shared ownership means nobody's.",
+        invalid: "// TODO(@alice, @bob): Retry on timeouts",
+        valid: "// TODO(@alice): Retry on timeouts",
+        fix: "Keep one assignee, or use `codo mod remove-assignee --assignee <name>`.",
+    },
+    Rule {
+        name: "invalid-issue-format",
+        summary: "Issues are in the expected format",
+        message: "Invalid issue format",
+        enabled_by: Some("--issue-format numbered|project-key"),
+        details: "Reported for issues not in the given format, numbered like #12, or with a project key like PROJ-12.",
+        invalid: "// TODO(#12): Retry on timeouts, with --issue-format project-key",
+        valid: "// TODO(PROJ-12): Retry on timeouts",
+        fix: "Cite the issue in the expected format.",
+    },
+    Rule {
+        name: "invalid-project-key",
+        summary: "Issues belong to known projects",
+        message: "Invalid project key",
+        enabled_by: Some("--issue-project-keys <key>..."),
+        details: "Reported for issues whose project key isn't given to --issue-project-keys.",
+        invalid: "// TODO(OLD-12): Retry on timeouts, with --issue-project-keys PROJ",
+        valid: "// TODO(PROJ-12): Retry on timeouts",
+        fix: "Cite the issue in the current project, or use `codo mod rename-issue --from <old> --to <new>`.",
+    },
+    Rule {
+        name: "invalid-recurrence",
+        summary: "Recurrence periods can be parsed",
+        message: "Invalid recurrence",
+        enabled_by: None,
+        details: "Reported for `every:` tokens that aren't a number followed by d (days), w (weeks), m (months), or y (years).",
+        invalid: "// TODO(every:often): Rotate the keys",
+        valid: "// TODO(every:90d): Rotate the keys",
+        fix: "Write the period as a number of days, weeks, months, or years.",
+    },
+    Rule {
+        name: "revisit-overdue",
+        summary: "Recurring TODOs are revisited in time",
+        message: "Revisit overdue (every <period>, last touched <date>)",
+        enabled_by: None,
+        details: "Reported once more time than the `every:` period has passed since the line was last changed, according to `git blame`.",
+        invalid: "// TODO(every:90d): Rotate the keys, last changed a year ago",
+        valid: "// TODO(every:90d): Rotate the keys, last changed last month",
+        fix: "Do the work, then touch the line, e.g. by updating the note.",
+    },
+    Rule {
+        name: "snooze-too-long",
+        summary: "TODOs aren't snoozed indefinitely",
+        message: "Snooze too long (until <date>, at most <period>)",
+        enabled_by: Some("--max-snooze"),
+        details: "Reported for `snooze:` dates further ahead than the period, so snoozing can't be used to hide overdue TODOs for good.",
+        invalid: "// TODO(2025-01-05, snooze:2026-01-05): Retry on timeouts, with --max-snooze 30d",
+        valid: "// TODO(2025-01-05, snooze:2025-02-01): Retry on timeouts",
+        fix: "Snooze for a shorter time with `codo mod snooze`, or update the due date.",
+    },
+    Rule {
+        name: "too-many-todos",
+        summary: "Files don't pile up TODOs",
+        message: "Too many TODOs (<count>, at most <max>)",
+        enabled_by: Some("--max-todos-per-file <max>"),
+        details: "Reported for files with more TODOs than the maximum.",
+        invalid: "A file with 12 TODOs, with --max-todos-per-file 10",
+        valid: "A file with 10 TODOs",
+        fix: "Resolve some of the TODOs, or move them to issues.",
+    },
+    Rule {
+        name: "over-budget",
+        summary: "Directories stay within their TODO budget",
+        message: "Over budget (<count> TODOs, at most <allowance>)",
+        enabled_by: Some("[budget] in the config"),
+        details: "Reported for directories with more TODOs than their budget. `codo budget` shows how much of each budget is used.",
+        invalid: "30 TODOs under src/api, with \"src/api\" = 25",
+        valid: "25 TODOs under src/api",
+        fix: "Resolve some of the TODOs, or raise the budget.",
+    },
+    Rule {
+        name: "unparseable-todo",
+        summary: "Comments mentioning TODO are well-formed",
+        message: "Unparseable TODO (unparseable-todo)",
+        enabled_by: Some("--pedantic"),
+        details: "Reported for comments mentioning TODO or FIXME that don't match the expected shape, and so are invisible to every other command.",
+        invalid: "//TODO fix this",
+        valid: "// TODO: fix this",
+        fix: "Rewrite the comment as a TODO.",
+    },
+    Rule {
+        name: "done-marker",
+        summary: "Finished work isn't left as comments",
+        message: "Finished work left as a comment (done-marker)",
+        enabled_by: Some("--forbid-done"),
+        details: "Reported for `DONE:` and `RESOLVED:` comments, which tend to linger after the work is finished.",
+        invalid: "// RESOLVED(#12): Retry on timeouts",
+        valid: "(the comment removed)",
+        fix: "Run `codo mod remove-done`.",
+    },
+    Rule {
+        name: "doc-todo",
+        summary: "TODOs stay out of documentation",
+        message: "TODO in documentation (doc-todo)",
+        enabled_by: Some("--forbid-doc-todos, or --forbid-doc-todos=public for the docs of public items only"),
+        details: "Reported for TODOs in doc comments (`///`, `//!`, `/** */`, `/*! */`) and Python docstrings, which end up in rendered documentation. With `public`, only docs of items starting with `pub`, `public`, `export`, or `open`, module docs, and docstrings of modules and of functions and classes not starting with `_` count.",
+        invalid: "/// Loads the config. TODO: explain the search order\npub fn load() {}",
+        valid: "/// Loads the config.\n// TODO: explain the search order\npub fn load() {}",
+        fix: "Move the TODO into a regular comment next to the item.",
+    },
+    Rule {
+        name: "short-note",
+        summary: "Terse TODOs come with context",
+        message: "Short note needs a context comment (under <length> characters)",
+        enabled_by: Some("--require-context-comment <length>"),
+        details: "Reported for TODOs with notes shorter than the length, unless a comment line right before or after them explains more.",
+        invalid: "// TODO: fix",
+        valid: "// TODO: fix\n// Retries give up after the first timeout.",
+        fix: "Explain the TODO in its note or a comment next to it.",
+    },
+    Rule {
+        name: "assignee-not-author",
+        summary: "New TODOs are assigned to whoever added them",
+        message: "Assigned to someone other than its author <name> (assignee-not-author)",
+        enabled_by: Some("--require-author-assignee, with --new-since <ref>"),
+        details: "Reported for new TODOs assigned to someone other than their git author, matched by name, email, email username, or `aliases` in the config. Assignees marked `shared` in the config are exempt.",
+        invalid: "// TODO(@bob): Retry on timeouts, added by alice",
+        valid: "// TODO(@alice): Retry on timeouts, added by alice",
+        fix: "Assign the TODO to yourself, or mark team handles as `shared`.",
+    },
+    Rule {
+        name: "assignee-left",
+        summary: "TODOs aren't assigned to people who left",
+        message: "Assigned to <name>, who left the team (assignee-left)",
+        enabled_by: Some("--forbid-assignee-left"),
+        details: "Reported for TODOs assigned to someone not in `active_members` in the config, or if that's not set, who hasn't committed in the last 180 days.",
+        invalid: "// TODO(@mallory): Retry on timeouts, with active_members = [\"alice\"]",
+        valid: "// TODO(@alice): Retry on timeouts",
+        fix: "Hand the TODOs over with `codo mod rename-assignee --from <old> --to <new>`.",
+    },
+];
+
+pub fn find(name: &str) -> Option<&'static Rule> {
+    RULES.iter().find(|rule| rule.name == name)
+}
+
+/// Patterns of the errors each rule reports, from its message with the
+/// placeholders like `<token>` standing for any text. Rules reporting several
+/// errors, like `priority-requirements`, list them separated by commas, so
+/// their parts are matched after every whole message.
+fn error_patterns() -> &'static [(Regex, &'static str)] {
+    static PATTERNS: OnceLock<Vec<(Regex, &'static str)>> = OnceLock::new();
+    PATTERNS.get_or_init(|| {
+        let placeholder = Regex::new(r"<[^>]+>").unwrap();
+        let pattern = |message: &str| {
+            let literals: Vec<String> = placeholder.split(message).map(regex::escape).collect();
+            Regex::new(&format!("^{}$", literals.join(".+"))).unwrap()
+        };
+
+        let whole = RULES.iter().map(|rule| (pattern(rule.message), rule.name));
+        let parts = RULES.iter().flat_map(|rule| {
+            rule.message
+                .split(", ")
+                .map(move |part| (pattern(part), rule.name))
+        });
+        whole.chain(parts).collect()
+    })
+}
+
+/// The name of the rule reporting the error. Errors no rule reports are
+/// named by themselves.
+pub fn name_of(error: &str) -> &str {
+    error_patterns()
+        .iter()
+        .find(|(pattern, _)| pattern.is_match(error))
+        .map_or(error, |(_, name)| name)
+}
+
+/// Each rule's name and summary, one per line.
+pub fn as_list() -> String {
+    let width = RULES.iter().map(|rule| rule.name.len()).max().unwrap_or(0);
+    RULES
+        .iter()
+        .map(|rule| format!("{:<width$}  {}", rule.name, rule.summary, width = width))
+        .collect::<Vec<String>>()
+        .join("\n")
+}
+
+fn indent(text: &str) -> String {
+    text.lines()
+        .map(|line| format!("    {}", line))
+        .collect::<Vec<String>>()
+        .join("\n")
+}
+
+impl Rule {
+    pub fn as_explanation(&self) -> String {
+        format!(
+            "{}: {}\n\nReported as: {}\nEnabled by: {}\n\n{}\n\nInvalid:\n{}\n\nValid:\n{}\n\nFix: {}",
+            self.name,
+            self.summary,
+            self.message,
+            self.enabled_by.unwrap_or("always checked"),
+            self.details,
+            indent(self.invalid),
+            indent(self.valid),
+            self.fix
+        )
+    }
+}
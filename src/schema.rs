@@ -0,0 +1,113 @@
+use serde_json::{json, Value};
+
+use crate::{Todo, VERSION};
+
+/// Bumped whenever a field of the JSON output is removed, renamed, or changes
+/// type. Adding fields keeps the version, so consumers should ignore fields
+/// they don't know.
+pub const SCHEMA_VERSION: u32 = 1;
+
+pub enum SchemaFormat {
+    JsonSchema,
+}
+
+impl SchemaFormat {
+    pub fn from_str(s: &str) -> Option<Self> {
+        match s {
+            "json-schema" => Some(SchemaFormat::JsonSchema),
+            _ => None,
+        }
+    }
+}
+
+/// The JSON document listing `todos`, as printed by `export json` and served
+/// by `/api/todos`.
+pub fn document<'a>(todos: impl Iterator<Item = &'a Todo>) -> Value {
+    let items: Vec<Value> = todos.map(|todo| todo.as_json()).collect();
+
+    json!({
+        "schema_version": SCHEMA_VERSION,
+        "version": VERSION,
+        "todos": items,
+    })
+}
+
+fn nullable(kind: &str, description: &str) -> Value {
+    json!({ "type": [kind, "null"], "description": description })
+}
+
+/// Describes `document` as a JSON Schema.
+pub fn json_schema() -> Value {
+    json!({
+        "$schema": "https://json-schema.org/draft/2020-12/schema",
+        "title": "codo TODO list",
+        "type": "object",
+        "required": ["schema_version", "version", "todos"],
+        "properties": {
+            "schema_version": {
+                "const": SCHEMA_VERSION,
+                "description": "Version of this schema, bumped on incompatible changes"
+            },
+            "version": {
+                "type": "string",
+                "description": "Version of codo that produced the document"
+            },
+            "todos": {
+                "type": "array",
+                "items": todo_schema()
+            }
+        }
+    })
+}
+
+/// Describes one TODO in `document`, as written by `Todo::as_json`.
+fn todo_schema() -> Value {
+    json!({
+        "type": "object",
+        "required": ["path", "line", "cell", "note", "issue", "blocked_by", "assignee", "assignees", "due", "priority", "every", "snooze", "id", "extra", "root"],
+        "properties": {
+            "path": { "type": "string", "description": "File path relative to the working directory" },
+            "line": { "type": "integer", "minimum": 1, "description": "Line number, relative to the cell in notebooks" },
+            "cell": nullable("integer", "Index of the notebook cell"),
+            "note": { "type": "string", "description": "Text after the TODO keyword and metadata" },
+            "issue": nullable("string", "Cited issue, e.g. #123, PROJ-123, or an issue URL"),
+            "blocked_by": nullable("string", "Issue the TODO waits on, from a blocked-by: token"),
+            "assignee": nullable("string", "The first assignee, without the leading @"),
+            "assignees": {
+                "type": "array",
+                "items": { "type": "string" },
+                "description": "Every assignee, without the leading @, in the order written"
+            },
+            "due": nullable("string", "Due date as YYYY-MM-DD"),
+            "priority": nullable("integer", "Priority from 0 (p0, the highest) to 9"),
+            "every": nullable("string", "Recurrence period, e.g. 90d"),
+            "snooze": nullable("string", "Date until which the TODO is left out of overdue lists, as YYYY-MM-DD"),
+            "id": nullable("string", "Stable identifier, e.g. td_8f3a"),
+            "extra": {
+                "type": "array",
+                "items": { "type": "string" },
+                "description": "Metadata tokens codo doesn't recognize"
+            },
+            "root": nullable("string", "Label of the --path the TODO was found under"),
+            "marker": { "type": "string", "description": "Keyword of the marker, present when it isn't TODO, e.g. HACK" },
+            "generated": {
+                "const": true,
+                "description": "Present when the file is marked @generated or DO NOT EDIT"
+            },
+            "permalink": nullable("string", "Link to the line at the commit checked out, only with export json --permalinks"),
+            "context": {
+                "type": "array",
+                "description": "Surrounding lines, only with export json --context",
+                "items": {
+                    "type": "object",
+                    "required": ["line", "offset", "text"],
+                    "properties": {
+                        "line": { "type": "integer", "minimum": 1 },
+                        "offset": { "type": "integer", "minimum": 0, "description": "Byte offset of the line's start" },
+                        "text": { "type": "string" }
+                    }
+                }
+            }
+        }
+    })
+}
@@ -0,0 +1,407 @@
+use std::collections::BTreeMap;
+use std::io::{BufRead, BufReader, Write};
+use std::net::{TcpListener, TcpStream};
+use std::time::Duration;
+
+use crate::config::Config;
+use crate::{
+    get_printable_note, history, is_overdue, normalize_issues, schema, todo_matches,
+    warn_invalid_dues, Grouping, Issue, Todo, TodoFilters, VERSION,
+};
+
+fn percent_decode(input: &str) -> String {
+    let bytes = input.as_bytes();
+    let mut output: Vec<u8> = vec![];
+    let mut i = 0;
+    while i < bytes.len() {
+        match bytes[i] {
+            b'+' => output.push(b' '),
+            b'%' if i + 2 < bytes.len() => {
+                let hex = std::str::from_utf8(&bytes[i + 1..i + 3]).unwrap_or("");
+                match u8::from_str_radix(hex, 16) {
+                    Ok(byte) => {
+                        output.push(byte);
+                        i += 2;
+                    }
+                    Err(_) => output.push(b'%'),
+                }
+            }
+            byte => output.push(byte),
+        }
+        i += 1;
+    }
+
+    String::from_utf8_lossy(&output).into_owned()
+}
+
+/// Percent-encodes a path for a URL, keeping its `/` separators.
+fn percent_encode_path(path: &str) -> String {
+    path.bytes()
+        .map(|byte| match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'.' | b'_' | b'~' | b'/' => {
+                (byte as char).to_string()
+            }
+            _ => format!("%{:02X}", byte),
+        })
+        .collect()
+}
+
+fn parse_query(query: &str) -> BTreeMap<String, String> {
+    query
+        .split('&')
+        .filter(|pair| !pair.is_empty())
+        .map(|pair| {
+            let (key, value) = pair.split_once('=').unwrap_or((pair, ""));
+            (percent_decode(key), percent_decode(value))
+        })
+        .collect()
+}
+
+fn escape_html(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+fn filters_from_query(query: &BTreeMap<String, String>) -> TodoFilters {
+    let list = |key: &str| {
+        query
+            .get(key)
+            .filter(|value| !value.is_empty())
+            .map(|value| value.split(',').map(|s| s.trim().to_owned()).collect())
+    };
+    let flag = |key: &str| query.get(key).is_some_and(|value| value == "on");
+
+    TodoFilters {
+        assignee: list("assignee"),
+        unassigned: flag("unassigned"),
+        issue: list("issue").map(|issues: Vec<String>| normalize_issues(&issues)),
+        project_key: list("project_key")
+            .map(|keys: Vec<String>| keys.iter().map(|key| key.to_uppercase()).collect()),
+        issue_gt: None,
+        issue_lt: None,
+        untracked: flag("untracked"),
+        blocked_by: None,
+        due: list("due"),
+        overdue: flag("overdue"),
+        someday: flag("someday"),
+        due_within: query
+            .get("due_within")
+            .and_then(|period| history::parse_period(period)),
+    }
+}
+
+fn editor_link(todo: &Todo) -> String {
+    let absolute = todo.path.canonicalize().unwrap_or(todo.path.to_owned());
+    format!(
+        "vscode://file{}:{}",
+        percent_encode_path(&absolute.to_string_lossy()),
+        todo.line_number
+    )
+}
+
+fn assignee_html(assignee: &str, config: &Config) -> String {
+    let mention = escape_html(&format!("@{}", assignee));
+    match config.assignees.get(assignee).and_then(|p| p.url.as_ref()) {
+        Some(url) => format!("<a href=\"{}\">{}</a>", escape_html(url), mention),
+        None => mention,
+    }
+}
+
+fn issue_html(issue: &Issue) -> String {
+    match issue {
+        Issue::Url { url, .. } => format!(
+            "<a href=\"{}\">{}</a>",
+            escape_html(url),
+            escape_html(&issue.as_display_string())
+        ),
+        _ => escape_html(&issue.as_string()),
+    }
+}
+
+fn render_row(todo: &Todo, config: &Config) -> String {
+    let metadata: Vec<String> = todo
+        .metadata
+        .issue
+        .as_ref()
+        .map(issue_html)
+        .into_iter()
+        .chain(
+            todo.metadata
+                .assignees
+                .iter()
+                .map(|a| assignee_html(a, config)),
+        )
+        .chain(
+            todo.metadata
+                .due
+                .as_ref()
+                .map(|d| escape_html(&format!("due:{}", d))),
+        )
+        .chain(todo.metadata.extra.iter().map(|extra| escape_html(extra)))
+        .collect();
+
+    format!(
+        "<tr{}><td><a href=\"{}\">{}</a></td><td>{}</td><td>{}</td></tr>",
+        if is_overdue(todo) {
+            " class=\"overdue\""
+        } else {
+            ""
+        },
+        escape_html(&editor_link(todo)),
+        escape_html(&todo.location()),
+        metadata.join(", "),
+        escape_html(get_printable_note(&todo.delimiter, &todo.note).trim())
+    )
+}
+
+fn render_dashboard(todos: &[&Todo], query: &BTreeMap<String, String>, config: &Config) -> String {
+    let value = |key: &str| escape_html(query.get(key).map(|s| s.as_str()).unwrap_or(""));
+    let checked = |key: &str| {
+        if query.get(key).is_some_and(|value| value == "on") {
+            " checked"
+        } else {
+            ""
+        }
+    };
+    let selected = |grouping: &str| {
+        if query.get("group_by").map(|s| s.as_str()) == Some(grouping) {
+            " selected"
+        } else {
+            ""
+        }
+    };
+
+    let mut groups: BTreeMap<String, Vec<&Todo>> = BTreeMap::new();
+    let grouping = query
+        .get("group_by")
+        .and_then(|group_by| Grouping::from_str(group_by));
+    for todo in todos {
+        let keys = match &grouping {
+            Some(grouping) => grouping.keys(todo, config),
+            None => vec![String::new()],
+        };
+        for key in keys {
+            groups.entry(key).or_default().push(todo);
+        }
+    }
+
+    let tables: Vec<String> = groups
+        .iter()
+        .map(|(key, todos)| {
+            let heading = if grouping.is_some() {
+                format!("<h2>{} ({})</h2>", escape_html(key), todos.len())
+            } else {
+                String::new()
+            };
+            let rows: Vec<String> = todos.iter().map(|todo| render_row(todo, config)).collect();
+            format!("{}<table>{}</table>", heading, rows.join(""))
+        })
+        .collect();
+
+    format!(
+        r#"<!DOCTYPE html>
+<html>
+<head>
+<meta charset="utf-8">
+<title>codo</title>
+<style>
+body {{ font-family: sans-serif; margin: 2em; }}
+form {{ margin-bottom: 1em; }}
+table {{ border-collapse: collapse; width: 100%; }}
+td {{ border-bottom: 1px solid #ddd; padding: 0.3em 0.6em; vertical-align: top; }}
+tr.overdue td {{ color: #b00; }}
+</style>
+</head>
+<body>
+<h1>{count} TODOs</h1>
+<form>
+<input name="assignee" placeholder="assignee" value="{assignee}">
+<input name="issue" placeholder="issue" value="{issue}">
+<input name="due" placeholder="due" value="{due}">
+<input name="due_within" placeholder="due within, e.g. 14d" value="{due_within}">
+<label><input type="checkbox" name="unassigned"{unassigned}> unassigned</label>
+<label><input type="checkbox" name="untracked"{untracked}> untracked</label>
+<label><input type="checkbox" name="someday"{someday}> someday</label>
+<label><input type="checkbox" name="overdue"{overdue}> overdue</label>
+<select name="group_by">
+<option value="">no grouping</option>
+<option value="assignee"{by_assignee}>by assignee</option>
+<option value="issue"{by_issue}>by issue</option>
+<option value="due"{by_due}>by due date</option>
+<option value="root"{by_root}>by root</option>
+<option value="language"{by_language}>by language</option>
+</select>
+<button>Filter</button>
+</form>
+{tables}
+<p><small>codo {version}</small></p>
+</body>
+</html>
+"#,
+        count = todos.len(),
+        assignee = value("assignee"),
+        issue = value("issue"),
+        due = value("due"),
+        due_within = value("due_within"),
+        unassigned = checked("unassigned"),
+        untracked = checked("untracked"),
+        someday = checked("someday"),
+        overdue = checked("overdue"),
+        by_assignee = selected("assignee"),
+        by_issue = selected("issue"),
+        by_due = selected("due"),
+        by_root = selected("root"),
+        by_language = selected("language"),
+        tables = tables.join("\n"),
+        version = VERSION,
+    )
+}
+
+/// How long a connection may take to send its request.
+const READ_TIMEOUT: Duration = Duration::from_secs(5);
+
+fn respond(stream: &mut TcpStream, status: &str, content_type: &str, body: &str) {
+    let _ = write!(
+        stream,
+        "HTTP/1.1 {}\r\nContent-Type: {}\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+        status,
+        content_type,
+        body.len(),
+        body
+    );
+}
+
+/// Whether the `Host` header names this server, so pages on other sites
+/// can't read the TODOs by rebinding their domain to 127.0.0.1.
+fn is_local_host(host: &str, port: u16) -> bool {
+    host == format!("127.0.0.1:{}", port) || host == format!("localhost:{}", port)
+}
+
+fn handle_connection(
+    mut stream: TcpStream,
+    port: u16,
+    config: &Config,
+    scan: &dyn Fn() -> Vec<Todo>,
+) {
+    // Connections are handled one at a time, so an idle one can't be
+    // allowed to hold up the rest.
+    let _ = stream.set_read_timeout(Some(READ_TIMEOUT));
+    let mut reader = BufReader::new(&stream);
+    let mut request_line = String::new();
+    if reader.read_line(&mut request_line).is_err() {
+        return;
+    }
+
+    // Only the `Host` header is needed, but all must be read before
+    // responding.
+    let mut host: Option<String> = None;
+    let mut header = String::new();
+    while reader.read_line(&mut header).is_ok_and(|n| n > 2) {
+        if let Some((name, value)) = header.split_once(':') {
+            if name.trim().eq_ignore_ascii_case("host") {
+                host = Some(value.trim().to_owned());
+            }
+        }
+        header.clear();
+    }
+
+    if !host.is_some_and(|host| is_local_host(&host, port)) {
+        respond(&mut stream, "403 Forbidden", "text/plain", "Forbidden");
+        return;
+    }
+
+    let mut parts = request_line.split_whitespace();
+    let method = parts.next().unwrap_or("");
+    let target = parts.next().unwrap_or("/");
+    let (path, query) = target.split_once('?').unwrap_or((target, ""));
+    let query = parse_query(query);
+
+    if method != "GET" {
+        respond(&mut stream, "405 Method Not Allowed", "text/plain", "");
+        return;
+    }
+
+    match path {
+        "/" => {
+            let todos = scan();
+            let filters = filters_from_query(&query);
+            warn_invalid_dues(&todos, &filters);
+            let results: Vec<&Todo> = todos
+                .iter()
+                .filter(|todo| todo_matches(todo, &filters))
+                .collect();
+            let body = render_dashboard(&results, &query, config);
+            respond(&mut stream, "200 OK", "text/html; charset=utf-8", &body);
+        }
+        "/api/todos" => {
+            let todos = scan();
+            let filters = filters_from_query(&query);
+            warn_invalid_dues(&todos, &filters);
+            let body = schema::document(todos.iter().filter(|todo| todo_matches(todo, &filters)))
+                .to_string();
+            respond(&mut stream, "200 OK", "application/json", &body);
+        }
+        _ => respond(&mut stream, "404 Not Found", "text/plain", "Not found"),
+    }
+}
+
+/// Serves the dashboard on localhost, rescanning for every page load so it
+/// always reflects the working tree.
+pub fn serve(port: u16, config: &Config, scan: &dyn Fn() -> Vec<Todo>) -> Result<(), String> {
+    let listener = TcpListener::bind(("127.0.0.1", port))
+        .map_err(|e| format!("Failed to listen on port {}: {}", port, e))?;
+
+    // Port 0 picks a free port.
+    let port = listener.local_addr().map_or(port, |address| address.port());
+    println!("Serving TODOs at http://127.0.0.1:{}", port);
+
+    for stream in listener.incoming() {
+        match stream {
+            Ok(stream) => handle_connection(stream, port, config, scan),
+            Err(err) => eprintln!("Connection failed: {}", err),
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn percent_decode_reads_escapes_and_plus() {
+        assert_eq!(percent_decode("due+within%3A%2014d"), "due within: 14d");
+        assert_eq!(percent_decode("%40alice"), "@alice");
+        assert_eq!(percent_decode("100%"), "100%");
+        assert_eq!(percent_decode("%zz"), "%zz");
+        assert_eq!(percent_decode("caf%C3%A9"), "café");
+    }
+
+    #[test]
+    fn parse_query_splits_pairs() {
+        let query = parse_query("assignee=alice%2Cbob&overdue=on&&untracked");
+        assert_eq!(query.get("assignee").unwrap(), "alice,bob");
+        assert_eq!(query.get("overdue").unwrap(), "on");
+        assert_eq!(query.get("untracked").unwrap(), "");
+        assert_eq!(query.len(), 3);
+    }
+
+    #[test]
+    fn percent_encode_path_keeps_separators() {
+        assert_eq!(
+            percent_encode_path("/home/me/my repo/#1.rs"),
+            "/home/me/my%20repo/%231.rs"
+        );
+    }
+
+    #[test]
+    fn is_local_host_needs_the_port() {
+        assert!(is_local_host("127.0.0.1:8080", 8080));
+        assert!(is_local_host("localhost:8080", 8080));
+        assert!(!is_local_host("localhost:80", 8080));
+        assert!(!is_local_host("attacker.example:8080", 8080));
+    }
+}
@@ -0,0 +1,105 @@
+use std::fs::File;
+use std::io::{self, BufRead, BufReader};
+use std::path::PathBuf;
+
+use serde_json::{Map, Value};
+
+use crate::apply;
+use crate::config::Config;
+use crate::markers;
+use crate::{format_todo_update, Todo, TodoMetadata};
+
+fn string_field(fields: &Map<String, Value>, field: &str) -> Result<Option<String>, String> {
+    match fields.get(field) {
+        None | Some(Value::Null) => Ok(None),
+        Some(Value::String(value)) => Ok(Some(value.to_owned())),
+        Some(_) => Err(format!("Expected a string or null for \"{}\"", field)),
+    }
+}
+
+/// A TODO as printed by `list --format ndjson` or `export json`. The comment
+/// delimiter and column aren't part of those, so `//` and 1 stand in, and
+/// the marker is only there when it isn't TODO.
+fn todo_from_json(value: Value, config: &Config) -> Result<Todo, String> {
+    let Value::Object(fields) = value else {
+        return Err(format!("Expected a JSON object, got {}", value));
+    };
+
+    let path = PathBuf::from(string_field(&fields, "path")?.ok_or("Missing \"path\"")?);
+    let line_number = fields
+        .get("line")
+        .and_then(Value::as_u64)
+        .ok_or("Missing \"line\"")?;
+    let cell = fields
+        .get("cell")
+        .and_then(Value::as_u64)
+        .map(|cell| cell as usize);
+
+    let delimiter = "//".to_owned();
+    let marker = string_field(&fields, "marker")?.unwrap_or(markers::TODO.to_owned());
+    let mut note = String::new();
+    let mut metadata = TodoMetadata::empty();
+    // Everything but where the TODO is is read like the fields of an edit.
+    let mut edited = fields.clone();
+    for field in
+        apply::READ_ONLY_FIELDS
+            .into_iter()
+            .chain(["id", "path", "cell", "line", "generated"])
+    {
+        edited.remove(field);
+    }
+    apply::edit_metadata(&mut metadata, &mut note, &delimiter, &edited, config)?;
+    metadata.id = string_field(&fields, "id")?;
+    metadata.extra = match fields.get("extra") {
+        Some(Value::Array(extra)) => extra
+            .iter()
+            .map(|token| token.as_str().map(str::to_owned))
+            .collect::<Option<Vec<String>>>()
+            .ok_or("Expected strings in \"extra\"")?,
+        _ => vec![],
+    };
+
+    let parts = metadata.written_parts(false, &[]);
+    Ok(Todo {
+        root: string_field(&fields, "root")?,
+        raw: format_todo_update(&delimiter, &marker, &note, metadata.clone(), &path, config),
+        delimiter,
+        marker,
+        path,
+        line_number,
+        column: 1,
+        cell,
+        note,
+        meta: (!parts.is_empty()).then(|| parts.join(", ")),
+        metadata,
+        inherited_due: None,
+        generated: fields.get("generated") == Some(&Value::Bool(true)),
+    })
+}
+
+/// Reads the TODOs of a saved `list --format ndjson` run, one JSON object per
+/// line, from `source` or from stdin if it's `-`.
+pub fn read(source: &str, config: &Config) -> Result<Vec<Todo>, String> {
+    let reader: Box<dyn BufRead> = if source == "-" {
+        Box::new(BufReader::new(io::stdin()))
+    } else {
+        let file = File::open(source).map_err(|e| format!("Failed to open {}: {}", source, e))?;
+        Box::new(BufReader::new(file))
+    };
+
+    let mut todos: Vec<Todo> = vec![];
+    for (index, line) in reader.lines().enumerate() {
+        let line = line.map_err(|e| format!("Failed to read {}: {}", source, e))?;
+        if line.trim().is_empty() {
+            continue;
+        }
+
+        let todo = serde_json::from_str(&line)
+            .map_err(|e| e.to_string())
+            .and_then(|value| todo_from_json(value, config))
+            .map_err(|e| format!("{}:{}: {}", source, index + 1, e))?;
+        todos.push(todo);
+    }
+
+    Ok(todos)
+}
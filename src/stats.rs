@@ -0,0 +1,86 @@
+use std::sync::atomic::{AtomicU64, AtomicUsize, Ordering};
+use std::sync::OnceLock;
+use std::time::{Duration, Instant};
+
+/// When the command started running, set only with `--stats`.
+static COMMAND_START: OnceLock<Instant> = OnceLock::new();
+
+pub static FILES_WALKED: AtomicUsize = AtomicUsize::new(0);
+/// Files excluded by `--path-glob` or already scanned through another path or
+/// link.
+pub static FILES_SKIPPED: AtomicUsize = AtomicUsize::new(0);
+pub static BYTES_SEARCHED: AtomicU64 = AtomicU64::new(0);
+/// Lines with a keyword, including ones that then don't match the TODO
+/// pattern or fail to parse.
+pub static REGEX_MATCHES: AtomicUsize = AtomicUsize::new(0);
+pub static TODOS: AtomicUsize = AtomicUsize::new(0);
+pub static SCANS: AtomicUsize = AtomicUsize::new(0);
+pub static SCAN_NANOS: AtomicU64 = AtomicU64::new(0);
+pub static CACHE_LOOKUPS: AtomicUsize = AtomicUsize::new(0);
+pub static CACHE_HITS: AtomicUsize = AtomicUsize::new(0);
+
+pub fn count(counter: &AtomicUsize, amount: usize) {
+    counter.fetch_add(amount, Ordering::Relaxed);
+}
+
+pub fn count_bytes(bytes: u64) {
+    BYTES_SEARCHED.fetch_add(bytes, Ordering::Relaxed);
+}
+
+pub fn count_scan(elapsed: Duration) {
+    SCANS.fetch_add(1, Ordering::Relaxed);
+    SCAN_NANOS.fetch_add(elapsed.as_nanos() as u64, Ordering::Relaxed);
+}
+
+fn format_bytes(bytes: u64) -> String {
+    match bytes {
+        0..=1023 => format!("{} B", bytes),
+        1024..=1048575 => format!("{:.1} KiB", bytes as f64 / 1024.0),
+        _ => format!("{:.1} MiB", bytes as f64 / 1048576.0),
+    }
+}
+
+pub fn start_command() {
+    let _ = COMMAND_START.set(Instant::now());
+}
+
+/// Prints timings and scan counters to stderr, if `--stats` was passed.
+pub fn finish_command() {
+    let Some(start) = COMMAND_START.get() else {
+        return;
+    };
+
+    let get = |counter: &AtomicUsize| counter.load(Ordering::Relaxed);
+    let mut lines = vec![
+        format!("command: {:.1?}", start.elapsed()),
+        format!(
+            "scan: {:.1?} ({} scans)",
+            Duration::from_nanos(SCAN_NANOS.load(Ordering::Relaxed)),
+            get(&SCANS)
+        ),
+        format!(
+            "files: {} walked, {} skipped",
+            get(&FILES_WALKED),
+            get(&FILES_SKIPPED)
+        ),
+        format!(
+            "searched: {}",
+            format_bytes(BYTES_SEARCHED.load(Ordering::Relaxed))
+        ),
+        format!(
+            "matches: {} lines, {} TODOs",
+            get(&REGEX_MATCHES),
+            get(&TODOS)
+        ),
+    ];
+
+    if get(&CACHE_LOOKUPS) > 0 {
+        lines.push(format!(
+            "cache: {} of {} lookups hit",
+            get(&CACHE_HITS),
+            get(&CACHE_LOOKUPS)
+        ));
+    }
+
+    eprintln!("{}", lines.join("\n"));
+}
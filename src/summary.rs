@@ -0,0 +1,56 @@
+use std::collections::HashMap;
+
+use crate::{get_printable_note, Todo};
+
+/// How many locations are shown for each note.
+const SHOWN_LOCATIONS: usize = 3;
+
+/// The note as compared between TODOs: lowercase, with whitespace collapsed
+/// and without punctuation at its ends, so `Remove this hack.` and
+/// `remove  this hack` are the same note.
+fn normalize_note(todo: &Todo) -> String {
+    get_printable_note(&todo.delimiter, &todo.note)
+        .split_whitespace()
+        .collect::<Vec<&str>>()
+        .join(" ")
+        .trim_matches(|c: char| c.is_ascii_punctuation())
+        .to_lowercase()
+}
+
+/// Each distinct note once, the most repeated first, with how many TODOs
+/// have it and where the first few are.
+pub fn render(todos: &[&Todo]) -> String {
+    let mut clusters: Vec<(String, Vec<&Todo>)> = vec![];
+    let mut indexes: HashMap<String, usize> = HashMap::new();
+    for todo in todos {
+        let key = normalize_note(todo);
+        match indexes.get(&key) {
+            Some(&index) => clusters[index].1.push(todo),
+            None => {
+                indexes.insert(key.clone(), clusters.len());
+                clusters.push((key, vec![todo]));
+            }
+        }
+    }
+    clusters.sort_by(|(a_key, a), (b_key, b)| b.len().cmp(&a.len()).then_with(|| a_key.cmp(b_key)));
+
+    clusters
+        .iter()
+        .map(|(_, todos)| {
+            let note = get_printable_note(&todos[0].delimiter, &todos[0].note);
+            let note = note.split_whitespace().collect::<Vec<&str>>().join(" ");
+            let mut lines = vec![format!("{} ({})", note, todos.len())];
+            lines.extend(
+                todos
+                    .iter()
+                    .take(SHOWN_LOCATIONS)
+                    .map(|todo| format!("  {}", todo.location())),
+            );
+            if todos.len() > SHOWN_LOCATIONS {
+                lines.push(format!("  and {} more", todos.len() - SHOWN_LOCATIONS));
+            }
+            lines.join("\n")
+        })
+        .collect::<Vec<String>>()
+        .join("\n")
+}
@@ -0,0 +1,206 @@
+use std::collections::BTreeMap;
+
+use crate::config::Config;
+use crate::tracker::{self, IssueDetails, IssueStatus};
+use crate::{apply_updates, get_printable_note, Todo, TodoMetadata, TodoUpdate, UpdateAction};
+
+const DEFAULT_CLOSED_MARKER: &str = "status:closed";
+
+/// The token `--mark-closed` adds to TODOs citing closed issues.
+pub fn closed_marker(config: &Config) -> &str {
+    config
+        .tracker
+        .as_ref()
+        .and_then(|tracker| tracker.closed_marker.as_deref())
+        .unwrap_or(DEFAULT_CLOSED_MARKER)
+}
+
+/// What `codo sync` changes besides listing TODOs citing closed issues.
+pub struct SyncActions {
+    pub create_issues: bool,
+    pub close_done: bool,
+    pub mark_closed: bool,
+    pub unblock: bool,
+}
+
+/// Checks each issue cited by `todos`, and each issue blocking them, with the
+/// configured tracker, listing TODOs whose issue or blocker is closed.
+/// Optionally files issues for untracked TODOs, closes the issues cited by
+/// DONE and RESOLVED markers, marks TODOs citing closed issues, and removes
+/// closed blockers.
+pub fn sync(
+    todos: Vec<Todo>,
+    done_markers: Vec<Todo>,
+    actions: SyncActions,
+    force: bool,
+    config: &Config,
+) -> Result<(), String> {
+    let SyncActions {
+        create_issues,
+        close_done,
+        mark_closed,
+        unblock,
+    } = actions;
+    let tracker = tracker::provider(config)?;
+
+    // Issues are often cited more than once, so each is looked up only once.
+    let mut details: BTreeMap<String, Option<IssueDetails>> = BTreeMap::new();
+    let mut closed: Vec<(&Todo, String)> = vec![];
+    let mut unblocked: Vec<(&Todo, String)> = vec![];
+    for todo in &todos {
+        let cited = [
+            (&todo.metadata.issue, &mut closed),
+            (&todo.metadata.blocked_by, &mut unblocked),
+        ];
+        for (issue, found) in cited {
+            let Some(issue) = issue else {
+                continue;
+            };
+
+            let issue_details = details.entry(issue.as_string()).or_insert_with(|| {
+                tracker
+                    .lookup(issue)
+                    .map_err(|e| eprintln!("Skipped {}", e))
+                    .ok()
+            });
+            if let Some(issue_details) = issue_details {
+                if issue_details.status == IssueStatus::Closed {
+                    found.push((todo, issue_details.title.to_owned()));
+                }
+            }
+        }
+    }
+
+    for (todo, title) in &closed {
+        println!("{} (closed: {})", todo.as_search_result(), title);
+    }
+    for (todo, title) in &unblocked {
+        println!("{} (unblocked, closed: {})", todo.as_search_result(), title);
+    }
+
+    // All edits of a TODO, like removing its blocker and citing its new
+    // issue, go in one update, since the second would be stale after the
+    // first.
+    let mut edits: Vec<(&Todo, TodoMetadata)> = vec![];
+    let mut messages: Vec<&str> = vec![];
+    if unblock && !unblocked.is_empty() {
+        for (todo, _) in &unblocked {
+            edited_metadata(&mut edits, todo).blocked_by = None;
+        }
+        messages.push("Removed the closed blockers of TODOs.");
+    }
+    if mark_closed {
+        let marker = closed_marker(config);
+
+        // Marked TODOs are still listed, but not marked twice.
+        let unmarked: Vec<&Todo> = closed
+            .iter()
+            .map(|(todo, _)| *todo)
+            .filter(|todo| !todo.metadata.extra.iter().any(|token| token == marker))
+            .collect();
+        for todo in &unmarked {
+            edited_metadata(&mut edits, todo)
+                .extra
+                .push(marker.to_owned());
+        }
+        if !unmarked.is_empty() {
+            messages.push("Marked TODOs citing closed issues.");
+        }
+    }
+
+    // A failure stops filing, but the issues filed so far are still cited,
+    // so they aren't filed twice.
+    let mut filing_error: Option<String> = None;
+    if create_issues {
+        let mut filed = false;
+        for todo in todos.iter().filter(|todo| todo.metadata.issue.is_none()) {
+            let title = get_printable_note(&todo.delimiter, &todo.note)
+                .trim()
+                .to_owned();
+            match tracker.create_issue(&title, &format!("Found at {}", todo.location())) {
+                Ok(issue) => {
+                    println!(
+                        "Filed {} for {}",
+                        issue.as_display_string(),
+                        todo.location()
+                    );
+                    edited_metadata(&mut edits, todo).issue = Some(issue);
+                    filed = true;
+                }
+                Err(e) => {
+                    filing_error = Some(e);
+                    break;
+                }
+            }
+        }
+        if filed {
+            messages.push("Cited the filed issues in their TODOs.");
+        }
+    }
+
+    if !edits.is_empty() {
+        let updates: Vec<TodoUpdate> = edits
+            .into_iter()
+            .map(|(todo, metadata)| TodoUpdate {
+                metadata,
+                note: todo.note.clone(),
+                path: todo.path.clone(),
+                line_number: todo.line_number,
+                cell: todo.cell,
+                delimiter: todo.delimiter.clone(),
+                marker: todo.marker.clone(),
+                raw: todo.raw.clone(),
+                action: UpdateAction::Edit,
+            })
+            .collect();
+        let summary =
+            apply_updates(updates, force, config).map_err(|write_error| match &filing_error {
+                Some(e) => format!("{}\n{}", e, write_error),
+                None => write_error,
+            })?;
+        println!(
+            "\n{}\n{}",
+            messages.join("\n"),
+            summary.as_cli_result(false)
+        );
+    }
+    if let Some(e) = filing_error {
+        return Err(e);
+    }
+
+    if close_done {
+        for marker in &done_markers {
+            if let Some(issue) = &marker.metadata.issue {
+                tracker.close_issue(issue)?;
+                println!(
+                    "Closed {} for {}",
+                    issue.as_display_string(),
+                    marker.location()
+                );
+            }
+        }
+    }
+
+    if !closed.is_empty() {
+        return Err(format!("\nTODOs citing closed issues ({})", closed.len()));
+    }
+    Ok(())
+}
+
+/// The metadata `sync` writes for the TODO, starting from its own.
+fn edited_metadata<'a, 'b>(
+    edits: &'b mut Vec<(&'a Todo, TodoMetadata)>,
+    todo: &'a Todo,
+) -> &'b mut TodoMetadata {
+    let index = match edits
+        .iter()
+        .position(|(edited, _)| std::ptr::eq(*edited, todo))
+    {
+        Some(index) => index,
+        None => {
+            edits.push((todo, todo.metadata.clone()));
+            edits.len() - 1
+        }
+    };
+    &mut edits[index].1
+}
@@ -0,0 +1,427 @@
+use serde::Deserialize;
+use serde_json::{json, Value};
+
+use crate::config::Config;
+use crate::credentials::token;
+use crate::http::{Auth, HttpClient, Response};
+use crate::{parse_issue, Issue, IssueProvider};
+
+#[derive(Clone, Copy, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum TrackerKind {
+    GitHub,
+    GitLab,
+    Jira,
+    Linear,
+}
+
+/// The issue tracker `sync` talks to, from the `[tracker]` table.
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct TrackerConfig {
+    pub provider: TrackerKind,
+
+    /// Where new issues are filed and bare `#42` numbers point: `owner/repo`
+    /// on GitHub, the project path on GitLab, or the project or team key on
+    /// Jira and Linear.
+    pub project: Option<String>,
+
+    /// API base URL, required for Jira, e.g. `https://example.atlassian.net`.
+    /// GitHub, GitLab, and Linear default to their hosted APIs.
+    pub url: Option<String>,
+
+    /// The token `sync --mark-closed` adds to TODOs citing closed issues.
+    /// Defaults to `status:closed`.
+    pub closed_marker: Option<String>,
+}
+
+#[derive(Clone, Copy, PartialEq)]
+pub enum IssueStatus {
+    Open,
+    Closed,
+}
+
+pub struct IssueDetails {
+    pub status: IssueStatus,
+    pub title: String,
+}
+
+/// What codo needs from an issue tracker. Each provider turns the issues it
+/// recognizes into API calls, and errors on ones from other trackers.
+pub trait TrackerProvider {
+    fn lookup(&self, issue: &Issue) -> Result<IssueDetails, String>;
+
+    /// Files a new issue in the configured project, returning how TODOs
+    /// should cite it.
+    fn create_issue(&self, title: &str, body: &str) -> Result<Issue, String>;
+
+    fn close_issue(&self, issue: &Issue) -> Result<(), String>;
+}
+
+/// The provider selected by the config, authenticated with its token. Jira
+/// tokens are `email:api_token` pairs sent with Basic auth, and Linear ones
+/// are personal API keys sent as is.
+pub fn provider(config: &Config) -> Result<Box<dyn TrackerProvider + '_>, String> {
+    let tracker = config.tracker.as_ref().ok_or(
+        "No issue tracker configured, add a [tracker] table with a provider to the config",
+    )?;
+    let project = tracker.project.clone();
+    let url = |default: &str| {
+        tracker
+            .url
+            .as_deref()
+            .unwrap_or(default)
+            .trim_end_matches('/')
+            .to_owned()
+    };
+
+    Ok(match tracker.provider {
+        TrackerKind::GitHub => Box::new(GitHub {
+            client: HttpClient::new(&config.http, token("github").map(Auth::Bearer)),
+            api: url("https://api.github.com"),
+            project,
+        }),
+        TrackerKind::GitLab => Box::new(GitLab {
+            client: HttpClient::new(&config.http, token("gitlab").map(Auth::Bearer)),
+            api: format!("{}/api/v4", url("https://gitlab.com")),
+            project,
+        }),
+        TrackerKind::Jira => Box::new(Jira {
+            client: HttpClient::new(&config.http, token("jira").map(Auth::Basic)),
+            api: format!(
+                "{}/rest/api/2",
+                tracker
+                    .url
+                    .as_deref()
+                    .ok_or("The Jira tracker needs a url")?
+                    .trim_end_matches('/')
+            ),
+            project,
+        }),
+        TrackerKind::Linear => Box::new(Linear {
+            client: HttpClient::new(&config.http, token("linear").map(Auth::Raw)),
+            api: url("https://api.linear.app/graphql"),
+            project,
+        }),
+    })
+}
+
+fn expect_success(response: Response, action: &str) -> Result<Value, String> {
+    if !response.is_success() {
+        return Err(format!(
+            "Failed to {}: HTTP {} {}",
+            action,
+            response.status,
+            response.body.trim()
+        ));
+    }
+    if response.body.trim().is_empty() {
+        return Ok(Value::Null);
+    }
+    response.json()
+}
+
+fn unsupported(issue: &Issue, tracker: &str) -> String {
+    format!("{} isn't a {} issue", issue.as_display_string(), tracker)
+}
+
+fn configured_project<'a>(project: &'a Option<String>, tracker: &str) -> Result<&'a str, String> {
+    project
+        .as_deref()
+        .ok_or_else(|| format!("The {} tracker needs a project", tracker))
+}
+
+/// Splits `repo#42` style references into the repository and number.
+fn split_reference(reference: &str) -> Option<(String, String)> {
+    let (repo, number) = reference.rsplit_once('#')?;
+    Some((repo.to_owned(), number.to_owned()))
+}
+
+/// The repository and number of a `#42` issue in the configured project, or
+/// of a link to the given tracker.
+fn numbered_reference(
+    issue: &Issue,
+    project: &Option<String>,
+    provider: IssueProvider,
+    tracker: &str,
+) -> Result<(String, String), String> {
+    match issue {
+        Issue::Numbered(number) => Ok((
+            configured_project(project, tracker)?.to_owned(),
+            number.trim_start_matches('#').to_owned(),
+        )),
+        Issue::Url {
+            provider: url_provider,
+            reference: Some(reference),
+            ..
+        } if *url_provider == provider => {
+            split_reference(reference).ok_or_else(|| unsupported(issue, tracker))
+        }
+        _ => Err(unsupported(issue, tracker)),
+    }
+}
+
+/// The `PROJ-123` key of a project key issue, or of a link to the given
+/// tracker.
+fn key_reference(issue: &Issue, provider: IssueProvider, tracker: &str) -> Result<String, String> {
+    match issue {
+        Issue::ProjectKey { .. } => Ok(issue.as_string()),
+        Issue::Url {
+            provider: url_provider,
+            reference: Some(reference),
+            ..
+        } if *url_provider == provider => Ok(reference.to_owned()),
+        _ => Err(unsupported(issue, tracker)),
+    }
+}
+
+struct GitHub<'a> {
+    client: HttpClient<'a>,
+    api: String,
+    project: Option<String>,
+}
+
+impl GitHub<'_> {
+    fn issue_url(&self, issue: &Issue) -> Result<String, String> {
+        let (repo, number) =
+            numbered_reference(issue, &self.project, IssueProvider::GitHub, "GitHub")?;
+        Ok(format!("{}/repos/{}/issues/{}", self.api, repo, number))
+    }
+}
+
+impl TrackerProvider for GitHub<'_> {
+    fn lookup(&self, issue: &Issue) -> Result<IssueDetails, String> {
+        let action = format!("look up {}", issue.as_display_string());
+        let body = expect_success(self.client.get(&self.issue_url(issue)?)?, &action)?;
+        Ok(IssueDetails {
+            status: match body["state"].as_str() {
+                Some("closed") => IssueStatus::Closed,
+                _ => IssueStatus::Open,
+            },
+            title: body["title"].as_str().unwrap_or("").to_owned(),
+        })
+    }
+
+    fn create_issue(&self, title: &str, body: &str) -> Result<Issue, String> {
+        let repo = configured_project(&self.project, "GitHub")?;
+        let url = format!("{}/repos/{}/issues", self.api, repo);
+        let response = self
+            .client
+            .post(&url, &json!({ "title": title, "body": body }))?;
+        let created = expect_success(response, "create an issue")?;
+        let number = created["number"]
+            .as_u64()
+            .ok_or("Failed to create an issue: no number in the response")?;
+        Ok(Issue::Numbered(format!("#{}", number)))
+    }
+
+    fn close_issue(&self, issue: &Issue) -> Result<(), String> {
+        let response = self
+            .client
+            .patch(&self.issue_url(issue)?, &json!({ "state": "closed" }))?;
+        expect_success(response, &format!("close {}", issue.as_display_string())).map(|_| ())
+    }
+}
+
+struct GitLab<'a> {
+    client: HttpClient<'a>,
+    api: String,
+    project: Option<String>,
+}
+
+impl GitLab<'_> {
+    fn project_url(&self, project: &str) -> String {
+        format!("{}/projects/{}", self.api, project.replace('/', "%2F"))
+    }
+
+    fn issue_url(&self, issue: &Issue) -> Result<String, String> {
+        let (project, number) =
+            numbered_reference(issue, &self.project, IssueProvider::GitLab, "GitLab")?;
+        Ok(format!("{}/issues/{}", self.project_url(&project), number))
+    }
+}
+
+impl TrackerProvider for GitLab<'_> {
+    fn lookup(&self, issue: &Issue) -> Result<IssueDetails, String> {
+        let action = format!("look up {}", issue.as_display_string());
+        let body = expect_success(self.client.get(&self.issue_url(issue)?)?, &action)?;
+        Ok(IssueDetails {
+            status: match body["state"].as_str() {
+                Some("closed") => IssueStatus::Closed,
+                _ => IssueStatus::Open,
+            },
+            title: body["title"].as_str().unwrap_or("").to_owned(),
+        })
+    }
+
+    fn create_issue(&self, title: &str, body: &str) -> Result<Issue, String> {
+        let project = configured_project(&self.project, "GitLab")?;
+        let url = format!("{}/issues", self.project_url(project));
+        let response = self
+            .client
+            .post(&url, &json!({ "title": title, "description": body }))?;
+        let created = expect_success(response, "create an issue")?;
+        let number = created["iid"]
+            .as_u64()
+            .ok_or("Failed to create an issue: no iid in the response")?;
+        Ok(Issue::Numbered(format!("#{}", number)))
+    }
+
+    fn close_issue(&self, issue: &Issue) -> Result<(), String> {
+        let response = self.client.request(
+            "PUT",
+            &self.issue_url(issue)?,
+            Some(&json!({ "state_event": "close" })),
+        )?;
+        expect_success(response, &format!("close {}", issue.as_display_string())).map(|_| ())
+    }
+}
+
+struct Jira<'a> {
+    client: HttpClient<'a>,
+    api: String,
+    project: Option<String>,
+}
+
+impl TrackerProvider for Jira<'_> {
+    fn lookup(&self, issue: &Issue) -> Result<IssueDetails, String> {
+        let key = key_reference(issue, IssueProvider::Jira, "Jira")?;
+        let url = format!("{}/issue/{}?fields=summary,status", self.api, key);
+        let body = expect_success(self.client.get(&url)?, &format!("look up {}", key))?;
+        let fields = &body["fields"];
+        Ok(IssueDetails {
+            status: match fields["status"]["statusCategory"]["key"].as_str() {
+                Some("done") => IssueStatus::Closed,
+                _ => IssueStatus::Open,
+            },
+            title: fields["summary"].as_str().unwrap_or("").to_owned(),
+        })
+    }
+
+    fn create_issue(&self, title: &str, body: &str) -> Result<Issue, String> {
+        let project = configured_project(&self.project, "Jira")?;
+        let fields = json!({
+            "fields": {
+                "project": { "key": project },
+                "summary": title,
+                "description": body,
+                "issuetype": { "name": "Task" },
+            }
+        });
+        let response = self.client.post(&format!("{}/issue", self.api), &fields)?;
+        let created = expect_success(response, "create an issue")?;
+        created["key"]
+            .as_str()
+            .and_then(parse_issue)
+            .ok_or("Failed to create an issue: no key in the response".to_owned())
+    }
+
+    /// Workflows differ between projects, so this takes whichever transition
+    /// leads to a done status.
+    fn close_issue(&self, issue: &Issue) -> Result<(), String> {
+        let key = key_reference(issue, IssueProvider::Jira, "Jira")?;
+        let url = format!("{}/issue/{}/transitions", self.api, key);
+        let action = format!("close {}", key);
+        let transitions = expect_success(self.client.get(&url)?, &action)?;
+        let transition = transitions["transitions"]
+            .as_array()
+            .into_iter()
+            .flatten()
+            .find(|t| t["to"]["statusCategory"]["key"].as_str() == Some("done"))
+            .and_then(|t| t["id"].as_str())
+            .ok_or_else(|| format!("Failed to {}: no transition to a done status", action))?;
+
+        let response = self
+            .client
+            .post(&url, &json!({ "transition": { "id": transition } }))?;
+        expect_success(response, &action).map(|_| ())
+    }
+}
+
+struct Linear<'a> {
+    client: HttpClient<'a>,
+    api: String,
+    project: Option<String>,
+}
+
+impl Linear<'_> {
+    fn query(&self, query: &str, variables: Value, action: &str) -> Result<Value, String> {
+        let response = self.client.post(
+            &self.api,
+            &json!({ "query": query, "variables": variables }),
+        )?;
+        let body = expect_success(response, action)?;
+        if let Some(error) = body["errors"][0]["message"].as_str() {
+            return Err(format!("Failed to {}: {}", action, error));
+        }
+        Ok(body["data"].clone())
+    }
+
+    /// The configured team's ID, and the ID of its first completed state.
+    fn team(&self, action: &str) -> Result<(String, Option<String>), String> {
+        let key = configured_project(&self.project, "Linear")?;
+        let data = self.query(
+            "query($key: String!) { teams(filter: { key: { eq: $key } }) { nodes { id \
+             states(filter: { type: { eq: \"completed\" } }) { nodes { id } } } } }",
+            json!({ "key": key }),
+            action,
+        )?;
+        let team = &data["teams"]["nodes"][0];
+        let id = team["id"]
+            .as_str()
+            .ok_or_else(|| format!("Failed to {}: no team {}", action, key))?;
+        let done = team["states"]["nodes"][0]["id"].as_str();
+        Ok((id.to_owned(), done.map(str::to_owned)))
+    }
+}
+
+impl TrackerProvider for Linear<'_> {
+    fn lookup(&self, issue: &Issue) -> Result<IssueDetails, String> {
+        let key = key_reference(issue, IssueProvider::Linear, "Linear")?;
+        let data = self.query(
+            "query($id: String!) { issue(id: $id) { title state { type } } }",
+            json!({ "id": key }),
+            &format!("look up {}", key),
+        )?;
+        let issue = &data["issue"];
+        Ok(IssueDetails {
+            status: match issue["state"]["type"].as_str() {
+                Some("completed" | "canceled") => IssueStatus::Closed,
+                _ => IssueStatus::Open,
+            },
+            title: issue["title"].as_str().unwrap_or("").to_owned(),
+        })
+    }
+
+    fn create_issue(&self, title: &str, body: &str) -> Result<Issue, String> {
+        let action = "create an issue";
+        let (team, _) = self.team(action)?;
+        let data = self.query(
+            "mutation($input: IssueCreateInput!) { issueCreate(input: $input) { \
+             issue { identifier } } }",
+            json!({ "input": { "teamId": team, "title": title, "description": body } }),
+            action,
+        )?;
+        data["issueCreate"]["issue"]["identifier"]
+            .as_str()
+            .and_then(parse_issue)
+            .ok_or(format!(
+                "Failed to {}: no identifier in the response",
+                action
+            ))
+    }
+
+    fn close_issue(&self, issue: &Issue) -> Result<(), String> {
+        let key = key_reference(issue, IssueProvider::Linear, "Linear")?;
+        let action = format!("close {}", key);
+        let (_, done) = self.team(&action)?;
+        let done = done.ok_or_else(|| format!("Failed to {}: no completed state", action))?;
+        self.query(
+            "mutation($id: String!, $state: String!) { issueUpdate(id: $id, \
+             input: { stateId: $state }) { success } }",
+            json!({ "id": key, "state": done }),
+            &action,
+        )
+        .map(|_| ())
+    }
+}
@@ -0,0 +1,90 @@
+use std::collections::BTreeMap;
+
+use crate::globs::normalize;
+use crate::Todo;
+
+/// A directory or file, with the TODOs found directly in it.
+#[derive(Default)]
+struct Node<'a> {
+    children: BTreeMap<String, Node<'a>>,
+    todos: Vec<&'a Todo>,
+}
+
+impl Node<'_> {
+    fn count(&self) -> usize {
+        self.todos.len() + self.children.values().map(|c| c.count()).sum::<usize>()
+    }
+
+    /// Directories holding nothing but another directory are shown as one
+    /// node, like `src/api/v2`.
+    fn collapse(&self, name: &str) -> (String, &Self) {
+        let mut name = name.to_owned();
+        let mut node = self;
+        while node.todos.is_empty() && node.children.len() == 1 {
+            let (child_name, child) = node.children.iter().next().unwrap();
+            if !child.todos.is_empty() {
+                break;
+            }
+            name = format!("{}/{}", name, child_name);
+            node = child;
+        }
+
+        (name, node)
+    }
+}
+
+fn leaf(todo: &Todo) -> String {
+    match todo.cell {
+        Some(cell) => format!("[{}]:{} {}", cell, todo.line_number, todo.summary()),
+        None => format!("{} {}", todo.line_number, todo.summary()),
+    }
+}
+
+fn render_children(node: &Node, prefix: &str, lines: &mut Vec<String>) {
+    let entry_count = node.todos.len() + node.children.len();
+    let entries = node
+        .todos
+        .iter()
+        .map(|todo| (leaf(todo), None))
+        .chain(node.children.iter().map(|(name, child)| {
+            let (name, child) = child.collapse(name);
+            (format!("{} ({})", name, child.count()), Some(child))
+        }));
+
+    for (index, (text, child)) in entries.enumerate() {
+        let is_last = index + 1 == entry_count;
+        let (branch, indent) = if is_last {
+            ("└── ", "    ")
+        } else {
+            ("├── ", "│   ")
+        };
+
+        lines.push(format!("{}{}{}", prefix, branch, text));
+        if let Some(child) = child {
+            render_children(child, &format!("{}{}", prefix, indent), lines);
+        }
+    }
+}
+
+/// Renders `todos` as a tree of their directories and files, each with the
+/// number of TODOs under it, and the TODOs themselves as leaves.
+pub fn render(todos: &[&Todo]) -> String {
+    let mut root = Node::default();
+    for todo in todos {
+        let mut node = &mut root;
+        for component in normalize(&todo.path).components() {
+            let name = component.as_os_str().to_string_lossy().into_owned();
+            node = node.children.entry(name).or_default();
+        }
+        node.todos.push(todo);
+    }
+
+    let mut lines: Vec<String> = vec![];
+    for (name, node) in &root.children {
+        let (name, node) = node.collapse(name);
+        lines.push(format!("{} ({})", name, node.count()));
+        render_children(node, "", &mut lines);
+    }
+
+    lines.join("\n")
+}
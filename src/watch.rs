@@ -0,0 +1,134 @@
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::PathBuf;
+use std::thread;
+use std::time::{Duration, SystemTime};
+
+use grep::regex::RegexMatcher;
+
+use crate::config::Config;
+use crate::{
+    build_searcher, relocate_lines, scan_walked_file, walk_files, ScanOptions, Todo,
+    RELOCATE_WINDOW,
+};
+
+const POLL_INTERVAL: Duration = Duration::from_millis(500);
+
+/// Changes are only picked up once files stop changing for this long, so a
+/// burst of saves (or a checkout) leads to one rescan.
+const DEBOUNCE: Duration = Duration::from_millis(200);
+
+/// Identifies a version of a file, so unchanged files aren't rescanned.
+type Stamp = (Option<SystemTime>, u64);
+
+fn stamp(metadata: Option<&fs::Metadata>) -> Stamp {
+    match metadata {
+        Some(metadata) => (metadata.modified().ok(), metadata.len()),
+        None => (None, 0),
+    }
+}
+
+fn walk_stamps(options: &ScanOptions) -> BTreeMap<PathBuf, Stamp> {
+    walk_files(options)
+        .into_iter()
+        .map(|(path, metadata)| {
+            let stamp = stamp(metadata.as_ref());
+            (path, stamp)
+        })
+        .collect()
+}
+
+/// Waits until the files under the roots differ from `scanned`, and then
+/// until they stop changing.
+fn wait_for_changes(
+    options: &ScanOptions,
+    scanned: &BTreeMap<PathBuf, (Stamp, Vec<Todo>)>,
+) -> BTreeMap<PathBuf, Stamp> {
+    let is_changed = |stamps: &BTreeMap<PathBuf, Stamp>| {
+        stamps.len() != scanned.len()
+            || stamps
+                .iter()
+                .any(|(path, stamp)| scanned.get(path).map(|(s, _)| s) != Some(stamp))
+    };
+
+    loop {
+        let mut stamps = walk_stamps(options);
+        if !is_changed(&stamps) {
+            thread::sleep(POLL_INTERVAL);
+            continue;
+        }
+
+        loop {
+            thread::sleep(DEBOUNCE);
+            let settled = walk_stamps(options);
+            if settled == stamps {
+                return stamps;
+            }
+            stamps = settled;
+        }
+    }
+}
+
+/// Moves the TODOs of a file that changed while it was scanned to the lines
+/// now holding them, so they don't point at stale lines until the file
+/// settles and is scanned again. Notebook TODOs are numbered within their
+/// cell, and are left as they are.
+fn reconcile_lines(path: &PathBuf, todos: &mut [Todo]) {
+    let Ok(contents) = fs::read_to_string(path) else {
+        return;
+    };
+    let lines: Vec<&str> = contents.lines().collect();
+    let mut located: Vec<(&mut u64, &str)> = todos
+        .iter_mut()
+        .filter(|todo| todo.cell.is_none())
+        .map(|todo| (&mut todo.line_number, todo.raw.as_str()))
+        .collect();
+    relocate_lines(&lines, &mut located, RELOCATE_WINDOW);
+}
+
+/// Rescans files as they change, calling `render` with all TODOs after every
+/// change. Only added and modified files are scanned again, and a file that
+/// can't be scanned, like a notebook being written, keeps its TODOs from
+/// before.
+pub fn watch(
+    matcher: &RegexMatcher,
+    config: &Config,
+    options: &ScanOptions,
+    render: &dyn Fn(Vec<&Todo>),
+) -> ! {
+    let mut searcher = build_searcher(None);
+    let mut scanned: BTreeMap<PathBuf, (Stamp, Vec<Todo>)> = BTreeMap::new();
+    let mut stamps = walk_stamps(options);
+
+    loop {
+        scanned.retain(|path, _| stamps.contains_key(path));
+        for (path, walked) in stamps {
+            if scanned.get(&path).is_some_and(|(s, _)| *s == walked) {
+                continue;
+            }
+
+            // Keeping the stamp from before the scan means a file modified
+            // while being scanned is scanned again on the next change.
+            let todos = match scan_walked_file(matcher, config, options, &mut searcher, &path) {
+                Ok(mut todos) => {
+                    let rescanned = fs::metadata(&path).ok();
+                    if stamp(rescanned.as_ref()) != walked {
+                        reconcile_lines(&path, &mut todos);
+                    }
+                    todos
+                }
+                Err(e) => {
+                    eprintln!("{}", e);
+                    scanned
+                        .remove(&path)
+                        .map(|(_, todos)| todos)
+                        .unwrap_or_default()
+                }
+            };
+            scanned.insert(path, (walked, todos));
+        }
+
+        render(scanned.values().flat_map(|(_, todos)| todos).collect());
+        stamps = wait_for_changes(options, &scanned);
+    }
+}
@@ -0,0 +1,7 @@
+package server
+
+// TODO(@alice, due:2001-03-04): Drain connections before shutting down
+func Shutdown() {}
+
+// TODO(@bob): Log the request id
+func Handle() {}
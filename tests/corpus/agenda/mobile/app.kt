@@ -0,0 +1,6 @@
+// TODO(@alice, @bob, due:2999-01-02): Cache images offline
+fun main() {}
+
+// TODO: Support tablets
+
+// HACK(@bob): Retry the flaky sync
@@ -0,0 +1,2 @@
+// TODO: Never committed
+fn draft() {}
@@ -0,0 +1,2 @@
+// TODO: Written this week
+fn new() {}
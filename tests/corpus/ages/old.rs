@@ -0,0 +1,2 @@
+// TODO: Written long ago
+fn old() {}
@@ -0,0 +1,11 @@
+// TODO(ABC-12, blocked-by:ABC-99, @alice): Switch to the batched consumer
+fn consume() {}
+
+// TODO(@bob, blocked-by:https://github.com/org/broker/issues/7): Drop the retry shim
+fn retry() {}
+
+// TODO(blocked-by:#31, due:2001-02-03, blocked-by:#32): Remove the fallback queue
+fn fallback() {}
+
+// TODO(@alice): Not blocked
+fn other() {}
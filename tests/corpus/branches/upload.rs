@@ -0,0 +1,2 @@
+// TODO: Retry failed uploads
+fn upload() {}
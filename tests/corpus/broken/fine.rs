@@ -0,0 +1 @@
+// TODO: Still scanned next to broken files
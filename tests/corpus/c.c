@@ -0,0 +1,7 @@
+#include <stdio.h>
+
+/* TODO(@alice): C block comment */
+int main(void) {
+    // TODO(data): Unknown metadata
+    return 0;
+}
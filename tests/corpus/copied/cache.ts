@@ -0,0 +1,8 @@
+function evict(cache: Cache) {
+  // TODO(#12): Remove  this hack
+  cache.clear();
+}
+
+function warm(cache: Cache) {
+  // TODO: Prefetch the hot keys
+}
@@ -0,0 +1,5 @@
+fn drain(queue: &mut Queue) {
+    // TODO: Remove this hack!
+    queue.clear();
+    // TODO(@bob): Prefetch the hot keys.
+}
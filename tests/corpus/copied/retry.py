@@ -0,0 +1,8 @@
+def retry(call):
+    # TODO(@alice): Remove this hack.
+    return call()
+
+
+def backoff(attempt):
+    # TODO: remove this hack
+    return 2 ** attempt
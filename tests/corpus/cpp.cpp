@@ -0,0 +1,8 @@
+#include <iostream>
+
+int main() {
+    // TODO(PROJ-9, @erin): C++ example
+    /* TODO(@erin): Block spanning lines
+       is left alone */
+    return 0;
+}
@@ -0,0 +1,5 @@
+/** Fetches users. TODO: paginate */
+export function fetchUsers() {}
+
+/** TODO: evict stale entries */
+function cache() {}
@@ -0,0 +1,17 @@
+//! Loads configs. TODO: document the file format
+
+/// Loads the config.
+/// TODO(@alice): Explain the search order
+#[inline]
+pub fn load() {}
+
+/**
+ * Parses one file. TODO: support includes
+ */
+fn parse() {}
+
+/// Crate internals. TODO: split up
+pub(crate) fn internal() {}
+
+// TODO: Regular comments are fine
+pub fn save() {}
@@ -0,0 +1,19 @@
+"""Runs the CLI. TODO: describe the flags"""
+
+
+def run(
+    args,
+):
+    """Runs the command.
+
+    TODO: handle signals
+    """
+    # TODO: Regular comments are fine
+    return 1
+
+
+def _helper():
+    """TODO: private helpers are only reported without =public"""
+
+
+MESSAGE = """TODO: not a docstring"""
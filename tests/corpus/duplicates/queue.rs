@@ -0,0 +1,6 @@
+fn drain(queue: &mut Queue) {
+    // TODO(@alice, @alice): Batch the acknowledgements
+    // TODO(#31, 2001-02-03, 2001-03-04, @@dave): Bound the retries
+    // TODO(@bob): Already clean
+    queue.drain();
+}
@@ -0,0 +1,19 @@
+// Comments mentioning TODOs without being TODOs, which no command may report;
+// used by the false-positives golden case.
+
+//! Client for the todo.example.com board, see https://example.com/TODO for the spec.
+
+// TODOs are tracked in the issue tracker, not here.
+// todos pile up when nobody owns them
+// TODO_LIST is filled in by the loader
+// TODO-list maintained by ops
+// todo.example.com hosts the board
+//todos.json is generated
+// See TODO.md for the roadmap.
+const TODO_LIST: &[&str] = &["https://todo.example.com/todos"];
+
+/// Returns the TODOs, see https://docs.example.com/TODO-api.
+pub fn todos() -> &'static [&'static str] {
+    // The board lists TODO_LIST in order.
+    TODO_LIST
+}
@@ -0,0 +1,5 @@
+// Code generated by protoc-gen-go. DO NOT EDIT.
+
+package api
+
+// TODO: Regenerate once the schema settles
@@ -0,0 +1,6 @@
+package main
+
+// TODO(@bob, PROJ-8): Assignee before issue
+func main() {
+	// TODO: Tab indented
+}
@@ -0,0 +1,8 @@
+class Main {
+    // TODO(2001-03-04): Due date only
+    // TODO(@hana, p0): Critical, without a due date
+    // TODO(2999-01-01, p1): Important, without an assignee
+    // TODO(p3): Nice to have someday
+    public static void main(String[] args) {}
+}
+// RESOLVED(#9): Fixed upstream
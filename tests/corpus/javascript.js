@@ -0,0 +1,6 @@
+/* TODO: Block comment on one line */
+function main() {
+  // TODO(2001-01-01, @alice): Metadata out of order
+  const s = "// TODO: inside a string";
+  return s;
+}
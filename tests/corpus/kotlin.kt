@@ -0,0 +1,4 @@
+fun main() {
+    // TODO(@gina, every:90d): Revisit this workaround quarterly
+    // TODO(every:often): Unparseable recurrence
+}
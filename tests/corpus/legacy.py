@@ -0,0 +1,7 @@
+# TODO [ABC-123] (alice): Issue and assignee before the note
+# TODO [#41]: Issue only
+def legacy():
+    # TODO: Due date after the note (due 2001-05-06)
+    # TODO: Assignee and issue after the note (@bob, #42)
+    # TODO: Keep remarks as they are (see the wiki)
+    pass
@@ -0,0 +1,7 @@
+def load(key):
+    # hack(@alice): Retry once, the cache drops keys under load
+    value = cache.get(key) or cache.get(key)
+    # NOTE: Keys are case sensitive
+    # NOTE(@bob): Hashed before lookup
+    return value
+    # Note that this is prose, not a marker
@@ -0,0 +1,2 @@
+// TODO: Kept next to a long line without a TODO
+queue.run().done(function(){return 1});var a=queue.next().done(function(x){return x+1});var a=queue.next().done(function(x){return x+1});var a=queue.next().done(function(x){return x+1});var a=queue.next().done(function(x){return x+1});var a=queue.next().done(function(x){return x+1});var a=queue.next().done(function(x){return x+1});var a=queue.next().done(function(x){return x+1});var a=queue.next().done(function(x){return x+1});var a=queue.next().done(function(x){return x+1});var a=queue.next().done(function(x){return x+1});var a=queue.next().done(function(x){return x+1});var a=queue.next().done(function(x){return x+1});var a=queue.next().done(function(x){return x+1});var a=queue.next().done(function(x){return x+1});var a=queue.next().done(function(x){return x+1});var a=queue.next().done(function(x){return x+1});var a=queue.next().done(function(x){return x+1});var a=queue.next().done(function(x){return x+1});var a=queue.next().done(function(x){return x+1});var a=queue.next().done(function(x){return x+1});var a=queue.next().done(function(x){return x+1});var a=queue.next().done(function(x){return x+1});var a=queue.next().done(function(x){return x+1});var a=queue.next().done(function(x){return x+1});var a=queue.next().done(function(x){return x+1});var a=queue.next().done(function(x){return x+1});var a=queue.next().done(function(x){return x+1});var a=queue.next().done(function(x){return x+1});var a=queue.next().done(function(x){return x+1});var a=queue.next().done(function(x){return x+1});var a=queue.next().done(function(x){return x+1});var a=queue.next().done(function(x){return x+1});var a=queue.next().done(function(x){return x+1});var a=queue.next().done(function(x){return x+1});var a=queue.next().done(function(x){return x+1});var a=queue.next().done(function(x){return x+1});var a=queue.next().done(function(x){return x+1});var a=queue.next().done(function(x){return x+1});var a=queue.next().done(function(x){return x+1});var a=queue.next().done(function(x){return x+1});var a=queue.next().done(function(x){return x+1});var a=queue.next().done(function(x){return x+1});var a=queue.next().done(function(x){return x+1});var a=queue.next().done(function(x){return x+1});var a=queue.next().done(function(x){return x+1});var a=queue.next().done(function(x){return x+1});var a=queue.next().done(function(x){return x+1});var a=queue.next().done(function(x){return x+1});var a=queue.next().done(function(x){return x+1});var a=queue.next().done(function(x){return x+1});var a=queue.next().done(function(x){return x+1});var a=queue.next().done(function(x){return x+1});var a=queue.next().done(function(x){return x+1});var a=queue.next().done(function(x){return x+1});var a=queue.next().done(function(x){return x+1});var a=queue.next().done(function(x){return x+1});var a=queue.next().done(function(x){return x+1});var a=queue.next().done(function(x){return x+1});var a=queue.next().done(function(x){return x+1});var a=queue.next().done(function(x){return x+1});var a=queue.next().done(function(x){return x+1});var a=queue.next().done(function(x){return x+1});var a=queue.next().done(function(x){return x+1});var a=queue.next().done(function(x){return x+1});var a=queue.next().done(function(x){return x+1});var a=queue.next().done(function(x){return x+1});var a=queue.next().done(function(x){return x+1});var a=queue.next().done(function(x){return x+1});var a=queue.next().done(function(x){return x+1});var a=queue.next().done(function(x){return x+1});var a=queue.next().done(function(x){return x+1});var a=queue.next().done(function(x){return x+1});var a=queue.next().done(function(x){return x+1});var a=queue.next().done(function(x){return x+1});var a=queue.next().done(function(x){return x+1});var a=queue.next().done(function(x){return x+1});var a=queue.next().done(function(x){return x+1});var a=queue.next().done(function(x){return x+1});var a=queue.next().done(function(x){return x+1});var a=queue.next().done(function(x){return x+1});var a=queue.next().done(function(x){return x+1});var a=queue.next().done(function(x){return x+1});var a=queue.next().done(function(x){return x+1});var a=queue.next().done(function(x){return x+1});var a=queue.next().done(function(x){return x+1});var a=queue.next().done(function(x){return x+1});var a=queue.next().done(function(x){return x+1});var a=queue.next().done(function(x){return x+1});var a=queue.next().done(function(x){return x+1});var a=queue.next().done(function(x){return x+1});
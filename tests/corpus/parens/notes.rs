@@ -0,0 +1,5 @@
+// TODO(@alice): Retry (with backoff) before giving up
+fn fetch() {}
+
+// TODO(#8): Split parse() and validate()
+fn load() {}
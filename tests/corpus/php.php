@@ -0,0 +1,3 @@
+<?php
+// TODO(@alice, 2999-01-01): PHP comment
+# TODO: PHP hash comment
@@ -0,0 +1,6 @@
+def refresh(session):
+    # TODO(): Rotate the token before it expires
+    # TODO( , ): Drop the legacy cookie
+    # todo(): Share the lock with the worker
+    # TODO(@alice): Cap the retries
+    return session
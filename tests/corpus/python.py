@@ -0,0 +1,7 @@
+import os
+
+# TODO(PROJ-7): Cite a Jira-like issue
+def main():
+    #    TODO: Extra spaces before the keyword
+    # ToDo(@carol, 2999-12-31): Mixed case keyword, due far in the future
+    return os.getcwd()
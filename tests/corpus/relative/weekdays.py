@@ -0,0 +1,3 @@
+# TODO(due:fri): Due the coming Friday
+# TODO(@dana, due:next-mon): Due a week after the coming Monday
+# TODO(due:vendredi): A weekday name set in weekdays.toml
@@ -0,0 +1,2 @@
+// TODO(#906): Closed issue, wrapped once it is marked
+// TODO: Untracked, filed by sync after the line above wraps
@@ -0,0 +1,2 @@
+// TODO(#906): Closed issue with a note long enough to wrap onto more lines than codo looks around for a moved TODO once it's marked, so the untracked TODO below it is only found again by reading the whole file once more before sync files its issue, instead of being skipped as if someone had edited its line since the scan. Queues drain in the order jobs were pushed, retrying failed jobs with a backoff that doubles each time, up to a limit after which the job is parked for someone to look at, and the queue keeps going with the next job rather than stalling on it. Parked jobs are kept for a week.
+// TODO: Untracked, filed by sync after the TODO above wraps far
@@ -0,0 +1,3 @@
+// TODO(@alice): Handle escaped quotes
+fn parse() {}
+// TODO: Report the column of errors
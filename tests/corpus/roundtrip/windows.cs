@@ -0,0 +1,2 @@
+// TODO(P1, @alice, due:2999-01-05, later): Metadata written by hand
+// TODO(@alice): Windows line endings
@@ -0,0 +1,7 @@
+# TODO(@carol): Ruby comment
+def main
+  # FIXME: Not a TODO keyword
+end
+
+# TODO(proj-12, @carol): Lowercase project key
+# TODO(# 13): Space inside an issue number
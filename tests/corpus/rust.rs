@@ -0,0 +1,9 @@
+// A plain comment that is not a TODO.
+fn main() {
+    // TODO: Simple example with no metadata
+    let answer = 42; // TODO(@alice): Trailing comments are not TODOs
+    // TODO(@alice): Assigned example
+    // TODO(#12, @bob, 2001-02-03): Overdue with all metadata
+    // todo lowercase without a colon
+    println!("{}", answer);
+}
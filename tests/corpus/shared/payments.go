@@ -0,0 +1,7 @@
+func charge(card Card) error {
+	// TODO(ABC-1, @alice, @bob): Retry declined cards once
+	// TODO(@bob, 2001-02-03): Log the gateway's response
+	// TODO(@carol, @alice, @bob): Split the refund path out
+	// TODO: Validate the currency
+	return nil
+}
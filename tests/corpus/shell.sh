@@ -0,0 +1,4 @@
+#!/bin/sh
+# TODO(#4, 2001-05-06): Shell script
+echo "done"
+# DONE: Left behind after the fix
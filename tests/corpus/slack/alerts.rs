@@ -0,0 +1,3 @@
+fn alert() {
+    // TODO(@bob): Page <!channel> when p99 > 2s & rising
+}
@@ -0,0 +1 @@
+# TODO(@zed): Recorded output, not ours to fix
@@ -0,0 +1,10 @@
+package uploads
+
+// TODO(#40, 2020-01-06, snooze:2999-01-01): Retry flaky uploads
+func Upload() {}
+
+// TODO(#41, 2020-01-06): Rotate the signing keys
+func Sign() {}
+
+// TODO(#41, 2020-02-03, snooze:2020-03-02): Check the signatures before upload
+func Verify() {}
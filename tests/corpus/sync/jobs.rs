@@ -0,0 +1,4 @@
+// TODO(#7, blocked-by:#901): Open issue, closed blocker
+// TODO(#902, blocked-by:#8): Closed issue, open blocker
+// TODO(#903, @alice, blocked-by:#904): Closed issue and closed blocker
+fn run() {}
@@ -0,0 +1,2 @@
+// @generated by the schema compiler
+// TODO(#905): Closed issue in a generated file
@@ -0,0 +1,8 @@
+export function main(): void {
+  // TODO(#3): Numbered issue
+  // TODO(@dave): Another assignee
+}
+
+// TODO(@@dave, ABC_123, #3): Typos are kept, not dropped
+// TODO(https://github.com/org/repo/issues/42, @bob): Issue linked by URL
+// TODO(https://tracker.example.com/tickets/7): Unknown tracker URL
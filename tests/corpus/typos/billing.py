@@ -0,0 +1,5 @@
+def invoice(order):
+    # TODO(@alice, 2025-02-30): Round per line item
+    # TODO(due:frday, #21): Send the reminder email
+    # TODO(@bob, 2001-02-03): Drop the legacy tax table
+    return order.total
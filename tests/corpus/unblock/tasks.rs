@@ -0,0 +1,2 @@
+// TODO(blocked-by:#907): Untracked, filed once its closed blocker is removed
+fn drain() {}
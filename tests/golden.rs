@@ -0,0 +1,1661 @@
+//! Runs codo against a copy of `tests/corpus` and compares the transcript of
+//! each case with `tests/golden/<case>.txt`. Set `CODO_BLESS=1` to rewrite the
+//! golden files after an intended behavior change.
+
+use std::fs;
+use std::io::{BufRead, BufReader, Read, Write};
+use std::net::{SocketAddr, TcpListener};
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+use std::sync::OnceLock;
+use std::thread;
+
+type Case = (&'static str, &'static [&'static [&'static str]]);
+
+/// Prints the lines of the corpus's top level files with a TODO as written,
+/// to show what a command rewrote.
+const TODO_LINES: &[&str] = &[
+    "git",
+    "grep",
+    "--no-index",
+    "-n",
+    "-i",
+    "-w",
+    "--max-depth",
+    "0",
+    "todo",
+    "--",
+    "corpus",
+    ":!*.toml",
+    ":!*.jsonl",
+    ":!corpus/.*",
+    ":!corpus/false-positives.rs",
+];
+
+const CASES: &[Case] = &[
+    ("list", &[&["list"]]),
+    ("list-overdue", &[&["list", "--overdue"]]),
+    ("list-unassigned", &[&["list", "--unassigned"]]),
+    ("list-vimgrep", &[&["list", "--vimgrep"]]),
+    ("list-tree", &[&["list", "--tree", "--overdue"]]),
+    (
+        "list-permalinks",
+        &[
+            &["list", "--permalinks"],
+            &["export", "json", "--permalinks"],
+        ],
+    ),
+    (
+        "list-long-lines",
+        &[
+            &["list", "--path", "corpus/oversized/long-line.js"],
+            &["list", "--path", "corpus/oversized/bundle.js"],
+        ],
+    ),
+    (
+        "list-archives",
+        &[
+            &[
+                "list",
+                "--scan-archives",
+                "--path",
+                "corpus/archives/vendor.zip",
+                "--path",
+                "corpus/archives/vendor.tar.gz",
+            ],
+            &[
+                "mod",
+                "assign-unassigned",
+                "--assignee",
+                "ivan",
+                "--scan-archives",
+                "--path",
+                "corpus/archives/vendor.zip",
+            ],
+            &[
+                "sync",
+                "--create-issues",
+                "--scan-archives",
+                "--path",
+                "corpus/archives/vendor.zip",
+            ],
+        ],
+    ),
+    (
+        "list-broken-containers",
+        &[&["list", "--path", "corpus/broken"]],
+    ),
+    (
+        "list-files-with-matches",
+        &[&["list", "--overdue", "--files-with-matches"]],
+    ),
+    (
+        "list-issue-due-dates",
+        &[
+            &["list", "--overdue", "--config", "corpus/issue-dates.toml"],
+            &["list", "--due-within", "30d"],
+            &[
+                "list",
+                "--due",
+                "2999-06-30",
+                "--config",
+                "corpus/issue-dates.toml",
+            ],
+        ],
+    ),
+    (
+        "due-weekdays",
+        &[
+            &[
+                "stat",
+                "--path",
+                "corpus/relative/weekdays.py",
+                "--due-within",
+                "6d",
+            ],
+            &[
+                "stat",
+                "--path",
+                "corpus/relative/weekdays.py",
+                "--due-within",
+                "6d",
+                "--config",
+                "corpus/weekdays.toml",
+            ],
+            &[
+                "stat",
+                "--path",
+                "corpus/relative/weekdays.py",
+                "--due-within",
+                "13d",
+                "--config",
+                "corpus/weekdays.toml",
+            ],
+            &[
+                "format",
+                "--path",
+                "corpus/relative/weekdays.py",
+                "--config",
+                "corpus/weekdays.toml",
+            ],
+            &[
+                "stat",
+                "--path",
+                "corpus/relative/weekdays.py",
+                "--due-within",
+                "13d",
+            ],
+        ],
+    ),
+    (
+        "alternate-syntax",
+        &[
+            &["list", "--path", "corpus/legacy.py"],
+            &[
+                "list",
+                "--path",
+                "corpus/legacy.py",
+                "--config",
+                "corpus/syntax.toml",
+            ],
+            &[
+                "format",
+                "--path",
+                "corpus/legacy.py",
+                "--config",
+                "corpus/syntax.toml",
+            ],
+            &[
+                "git",
+                "grep",
+                "--no-index",
+                "-n",
+                "-i",
+                "-w",
+                "todo",
+                "--",
+                "corpus/legacy.py",
+            ],
+        ],
+    ),
+    (
+        "list-codoignore",
+        &[
+            &["list", "--assignee", "zed"],
+            &["list", "--path", "corpus/snapshots"],
+        ],
+    ),
+    (
+        "list-path-glob",
+        &[&[
+            "list",
+            "--path-glob",
+            "corpus/*.{rs,go}",
+            "--path-glob",
+            "!**/go.go",
+        ]],
+    ),
+    (
+        "list-types",
+        &[
+            &["list", "--type", "rust", "--type", "py"],
+            &[
+                "list",
+                "--type-add",
+                "script:*.sh",
+                "--type-add",
+                "script:*.rb",
+                "--type",
+                "script",
+            ],
+            &["list", "--type", "nope"],
+            &["list", "--type-add", "bad", "--type", "rust"],
+            &["--debug-ignores", "corpus/go.go", "--type", "rust"],
+        ],
+    ),
+    (
+        "list-roots",
+        &[
+            &[
+                "--path",
+                "rs=corpus/rust.rs",
+                "--path",
+                "ts=corpus/typescript.ts",
+                "list",
+                "--root",
+                "rs",
+                "--root",
+                "ts",
+            ],
+            &["--path", "rs=corpus/rust.rs", "stat", "--group-by", "root"],
+        ],
+    ),
+    (
+        "due-calendar",
+        &[
+            &["due", "--assignee", "bob"],
+            &["due", "--calendar", "--from", "2001-01", "--months", "2"],
+            &["due", "--calendar", "--from", "January"],
+            &["due", "--calendar", "--months", "0"],
+            &["due", "--calendar", "--months", "4000000000"],
+        ],
+    ),
+    (
+        "agenda",
+        &[
+            &["agenda", "--repos", "corpus/agenda/repos.txt"],
+            &[
+                "agenda",
+                "--repos",
+                "corpus/agenda/repos.txt",
+                "--assignee",
+                "bob",
+            ],
+            &[
+                "agenda",
+                "--repos",
+                "corpus/agenda/repos.txt",
+                "--assignee",
+                "dave",
+            ],
+            &["agenda", "--repos", "corpus/agenda/bad-repos.txt"],
+        ],
+    ),
+    (
+        "blocked-by",
+        &[
+            &["list", "--path", "corpus/blocked"],
+            &[
+                "list",
+                "--path",
+                "corpus/blocked",
+                "--blocked-by",
+                "abc-99",
+                "--blocked-by",
+                "org/broker#7",
+            ],
+            &[
+                "list",
+                "--path",
+                "corpus/blocked",
+                "--blocked-by",
+                "#31",
+                "--format",
+                "ndjson",
+            ],
+            &["format", "--check", "--path", "corpus/blocked"],
+        ],
+    ),
+    (
+        "format-verify",
+        &[
+            &["format", "--verify"],
+            &["format", "--verify", "--config", "corpus/styles.toml"],
+            &["format", "--verify", "--config", "corpus/syntax.toml"],
+            &[
+                "format",
+                "--verify",
+                "--path",
+                "corpus/relative/weekdays.py",
+                "--config",
+                "corpus/weekdays.toml",
+            ],
+            &["format", "--verify", "--config", "corpus/wrap.toml"],
+            &["list", "--path", "corpus/parens"],
+            &["format", "--check", "--path", "corpus/parens"],
+        ],
+    ),
+    (
+        "lint-quiet",
+        &[
+            &["lint", "--require-assignees", "--quiet"],
+            &["lint", "--require-assignees", "--max-violations", "2"],
+            &[
+                "lint",
+                "--require-due-for-priority",
+                "p1",
+                "--quiet",
+                "--path",
+                "corpus/java.java",
+            ],
+            &[
+                "lint",
+                "--quiet",
+                "--config",
+                "corpus/markers.toml",
+                "--path",
+                "corpus/markers",
+            ],
+        ],
+    ),
+    ("stat", &[&["stat"]]),
+    ("stat-prometheus", &[&["stat", "--format", "prometheus"]]),
+    (
+        "vcs-files-outside-git",
+        &[&["list", "--vcs-files"], &["list", "--vcs-untracked"]],
+    ),
+    (
+        "debug-ignores",
+        &[
+            &["--debug-ignores", "corpus/rust.rs"],
+            &["--debug-ignores", "corpus/relative/weekdays.py"],
+            &["--debug-ignores", "corpus/snapshots/output.py"],
+            &["--debug-ignores", "corpus/missing.py"],
+            &["--debug-ignores", "corpus/rust.rs", "--path-glob", "*.py"],
+            &[
+                "--debug-ignores",
+                "corpus/relative/weekdays.py",
+                "--path",
+                "corpus/relative",
+            ],
+        ],
+    ),
+    (
+        "stat-group-by-path",
+        &[
+            &["stat", "--group-by", "path", "--path", "corpus/relative"],
+            &[
+                "stat",
+                "--group-by",
+                "path",
+                "--depth",
+                "2",
+                "--path",
+                "corpus",
+                "--path",
+                "corpus/relative",
+            ],
+            &["stat", "--group-by", "language", "--depth", "2"],
+        ],
+    ),
+    (
+        "stat-group-by-language",
+        &[
+            &["stat", "--group-by", "language"],
+            &[
+                "stat",
+                "--group-by",
+                "language",
+                "--config",
+                "corpus/languages.toml",
+            ],
+        ],
+    ),
+    (
+        "stat-metrics",
+        &[
+            // Only assignees without overdue TODOs, whose metrics don't change daily.
+            &[
+                "stat",
+                "--metrics",
+                "--assignee",
+                "carol",
+                "--assignee",
+                "dave",
+            ],
+            &[
+                "stat",
+                "--metrics",
+                "--assignee",
+                "carol",
+                "--format",
+                "json",
+            ],
+            &["stat", "--format", "json"],
+        ],
+    ),
+    (
+        "auth-errors",
+        &[
+            &["auth", "login", "github"],
+            &["auth", "login", "bitbucket"],
+            &["auth", "logout", "bitbucket"],
+        ],
+    ),
+    (
+        "sync-unconfigured",
+        &[&["sync"], &["sync", "--mark-closed"]],
+    ),
+    (
+        "sync-closed",
+        &[
+            &[
+                "sync",
+                "--config",
+                "corpus/tracker.toml",
+                "--path",
+                "corpus/sync",
+            ],
+            &[
+                "sync",
+                "--config",
+                "corpus/tracker.toml",
+                "--path",
+                "corpus/sync",
+                "--unblock",
+                "--mark-closed",
+            ],
+            &["list", "--path", "corpus/sync"],
+        ],
+    ),
+    (
+        "stat-burndown-renames",
+        &[
+            &["git", "init", "-q", "-b", "main"],
+            &["git", "add", "corpus/renames"],
+            &["git", "commit", "-q", "-m", "Add the parser"],
+            &[
+                "git",
+                "mv",
+                "corpus/renames/parse.rs",
+                "corpus/renames/parser.rs",
+            ],
+            &["git", "commit", "-q", "-m", "Rename the parser"],
+            &[
+                "stat",
+                "--burndown",
+                "--since",
+                "7d",
+                "--path",
+                "corpus/renames",
+            ],
+        ],
+    ),
+    (
+        "sync-relocate",
+        &[
+            &[
+                "sync",
+                "--config",
+                "corpus/relocate.toml",
+                "--path",
+                "corpus/relocate",
+                "--mark-closed",
+                "--create-issues",
+            ],
+            &["list", "--path", "corpus/relocate"],
+        ],
+    ),
+    (
+        "mod-issue-from-branch",
+        &[
+            &["git", "init", "-q", "-b", "fix/utf-8-decoding"],
+            &["git", "add", "corpus/branches"],
+            &["git", "commit", "-q", "-m", "Add uploads"],
+            &[
+                "mod",
+                "add-issue-for-all-untracked",
+                "--from-branch",
+                "--path",
+                "corpus/branches",
+            ],
+            &[
+                "git",
+                "checkout",
+                "-q",
+                "-b",
+                "feature/ABC-123-retry-uploads",
+            ],
+            &[
+                "mod",
+                "add-issue-for-all-untracked",
+                "--from-branch",
+                "--path",
+                "corpus/branches",
+            ],
+            &["list", "--path", "corpus/branches"],
+            &["git", "checkout", "-q", "--detach"],
+            &[
+                "mod",
+                "add-issue-for-all-untracked",
+                "--from-branch",
+                "--path",
+                "corpus/branches",
+            ],
+        ],
+    ),
+    (
+        "complete",
+        &[
+            &["_complete", "--assignee", "--path", "corpus/agenda/backend"],
+            &[
+                "_complete",
+                "--assignee",
+                "b",
+                "--path",
+                "corpus/agenda/backend",
+            ],
+            &["_complete", "--due", "--path", "corpus/agenda/backend"],
+            &["_complete", "--issue", "--path", "corpus/agenda/backend"],
+        ],
+    ),
+    (
+        "stat-by-age",
+        &[
+            &["git", "init", "-q", "-b", "main"],
+            &["git", "add", "corpus/ages/old.rs"],
+            &[
+                "GIT_AUTHOR_DATE=2001-02-03T12:00:00",
+                "GIT_COMMITTER_DATE=2001-02-03T12:00:00",
+                "git",
+                "commit",
+                "-q",
+                "-m",
+                "Add the old TODO",
+            ],
+            &["git", "add", "corpus/ages/new.rs"],
+            &["git", "commit", "-q", "-m", "Add the new TODO"],
+            &["stat", "--by-age", "--path", "corpus/ages"],
+        ],
+    ),
+    (
+        "sync-unblock-create",
+        &[
+            &[
+                "sync",
+                "--config",
+                "corpus/tracker.toml",
+                "--path",
+                "corpus/unblock",
+                "--unblock",
+                "--create-issues",
+            ],
+            &["list", "--path", "corpus/unblock"],
+        ],
+    ),
+    (
+        "lint-doc-todos",
+        &[
+            &["lint", "--forbid-doc-todos", "--path", "corpus/docs"],
+            &["lint", "--forbid-doc-todos=public", "--path", "corpus/docs"],
+            &[
+                "lint",
+                "--forbid-doc-todos=private",
+                "--path",
+                "corpus/docs",
+            ],
+        ],
+    ),
+    (
+        "lint-emit-patch",
+        &[&[
+            "lint",
+            "--emit-patch",
+            "-",
+            "--path",
+            "corpus/ruby.rb",
+            "--path",
+            "corpus/go.go",
+        ]],
+    ),
+    (
+        "lint-assignee-left",
+        &[
+            &[
+                "lint",
+                "--forbid-assignee-left",
+                "--config",
+                "corpus/members.toml",
+                "--path",
+                "corpus/rust.rs",
+                "--path",
+                "corpus/kotlin.kt",
+            ],
+            &["lint", "--forbid-assignee-left"],
+        ],
+    ),
+    (
+        "lint-new-since",
+        &[&["lint", "--new-since", "main", "--require-author-assignee"]],
+    ),
+    (
+        "lint-compare-ref",
+        &[
+            &["lint", "--compare-ref", "main"],
+            &["lint", "--compare-ref", "main", "--new-since", "main"],
+        ],
+    ),
+    (
+        "mod-generated",
+        &[
+            &[
+                "mod",
+                "add-issue-for-all-untracked",
+                "--issue",
+                "#98",
+                "--path",
+                "corpus/generated.go",
+            ],
+            &[
+                "mod",
+                "add-issue-for-all-untracked",
+                "--issue",
+                "#98",
+                "--path",
+                "corpus/generated.go",
+                "--include-generated",
+            ],
+            &["export", "json", "--path", "corpus/generated.go"],
+        ],
+    ),
+    (
+        "mod-normalize-issue-case",
+        &[
+            &["lint", "--path", "corpus/ruby.rb"],
+            &["mod", "normalize-issue-case"],
+            &["list", "--path", "corpus/ruby.rb"],
+            &["mod", "normalize-issue-case"],
+        ],
+    ),
+    (
+        "mod-path-selectors",
+        &[
+            &[
+                "mod",
+                "assign-unassigned",
+                "--assignee",
+                "payments",
+                "--path",
+                "corpus/python.py",
+            ],
+            &[
+                "mod",
+                "assign-unassigned",
+                "--assignee",
+                "shells",
+                "--path-glob",
+                "**/*.sh",
+            ],
+            &["list", "--assignee", "payments", "--assignee", "shells"],
+        ],
+    ),
+    (
+        "stat-sort",
+        &[
+            &["stat", "--group-by", "assignee"],
+            &["stat", "--group-by", "due", "--sort", "key"],
+            &["stat", "--group-by", "due", "--sort", "bogus"],
+        ],
+    ),
+    ("lint", &[&["lint"]]),
+    ("lint-pedantic", &[&["lint", "--pedantic"]]),
+    (
+        "false-positives",
+        &[
+            &["list", "--path", "corpus/false-positives.rs"],
+            &[
+                "lint",
+                "--pedantic",
+                "--forbid-doc-todos",
+                "--path",
+                "corpus/false-positives.rs",
+            ],
+        ],
+    ),
+    (
+        "lint-stdin-json",
+        &[
+            &[
+                "lint",
+                "--stdin-json",
+                "--require-assignees",
+                "<",
+                "corpus/lint-requests.jsonl",
+            ],
+            &["lint", "--stdin-json", "--forbid-assignee-left"],
+        ],
+    ),
+    (
+        "bad-periods",
+        &[
+            &["list", "--due-within", "9é"],
+            &["list", "--due-within", "99999999999999d"],
+            &["stat", "--due-within", "999999999d"],
+            &["stat", "--burndown", "--since", "9é"],
+            &["stat", "--burndown", "--since", "99999999999d"],
+            &["lint", "--stdin-json", "<", "corpus/bad-periods.jsonl"],
+        ],
+    ),
+    ("lint-forbid-done", &[&["lint", "--forbid-done"]]),
+    (
+        "lint-priority",
+        &[
+            &[
+                "lint",
+                "--path",
+                "corpus/java.java",
+                "--require-due-for-priority",
+                "p1",
+            ],
+            &[
+                "lint",
+                "--path",
+                "corpus/java.java",
+                "--require-due-for-priority",
+                "high",
+            ],
+        ],
+    ),
+    (
+        "lint-context-comment",
+        &[&[
+            "--path-glob",
+            "corpus/{cpp.cpp,kotlin.kt,rust.rs}",
+            "lint",
+            "--require-context-comment",
+            "25",
+        ]],
+    ),
+    (
+        "lint-strict",
+        &[&[
+            "lint",
+            "--require-assignees",
+            "--require-issues",
+            "--require-due-dates",
+            "--forbid-unknown-metadata",
+        ]],
+    ),
+    (
+        "lint-closed-markers",
+        &[&[
+            "lint",
+            "--forbid-unknown-metadata",
+            "--stdin-json",
+            "<",
+            "corpus/closed-markers.jsonl",
+        ]],
+    ),
+    (
+        "lint-scoped",
+        &[&[
+            "lint",
+            "--config",
+            "corpus/rules.toml",
+            "--forbid-bare",
+            "--require-issues-for",
+            "corpus/go.go",
+        ]],
+    ),
+    (
+        "format-check",
+        &[&["format", "--check"], &["format"], &["format", "--check"]],
+    ),
+    ("format", &[&["format"], TODO_LINES]),
+    (
+        "budget",
+        &[
+            &["budget", "--config", "corpus/budget.toml"],
+            &["lint", "--config", "corpus/budget.toml"],
+        ],
+    ),
+    (
+        "digest-mentions",
+        &[
+            &[
+                "digest",
+                "--assignee=alice",
+                "--config",
+                "corpus/assignees.toml",
+            ],
+            &[
+                "digest",
+                "--assignee=bob",
+                "--format=slack",
+                "--config",
+                "corpus/assignees.toml",
+            ],
+            &[
+                "digest",
+                "--assignee=bob",
+                "--format=slack",
+                "--config",
+                "corpus/assignees.toml",
+                "--path",
+                "corpus/slack",
+            ],
+        ],
+    ),
+    (
+        "export-context",
+        &[&[
+            "--path-glob",
+            "corpus/{rust.rs,notebook.ipynb}",
+            "export",
+            "json",
+            "--context",
+            "1",
+        ]],
+    ),
+    (
+        "format-wrap",
+        &[
+            &["format", "--config", "corpus/wrap.toml"],
+            &["list", "--vimgrep"],
+            &["format", "--config", "corpus/wrap.toml"],
+            &["format", "--check"],
+            &[
+                "mod",
+                "--verbose",
+                "remove-issue",
+                "--issue",
+                "#12",
+                "--strategy",
+                "delete",
+            ],
+            &["format", "--verbose", "--path-glob", "corpus/java.java"],
+        ],
+    ),
+    (
+        "review",
+        &[
+            &["review", "--overdue", "<", "corpus/review-input.txt"],
+            &["review", "--overdue", "<", "corpus/review-input.txt"],
+            &["list", "--overdue"],
+            &["review", "--overdue", "<", "corpus/review-overflow.txt"],
+        ],
+    ),
+    ("schema", &[&["schema", "--format", "json-schema"]]),
+    (
+        "explain",
+        &[
+            &["explain"],
+            &["explain", "issue-case"],
+            &["explain", "missing-owner"],
+        ],
+    ),
+    (
+        "list-open",
+        &[
+            &[
+                "list",
+                "--open",
+                "--config",
+                "corpus/editor.toml",
+                "--path",
+                "corpus/rust.rs",
+            ],
+            &[
+                "list",
+                "--open",
+                "--pick",
+                "--config",
+                "corpus/editor.toml",
+                "--path",
+                "corpus/rust.rs",
+                "<",
+                "corpus/pick-input.txt",
+            ],
+            &[
+                "list",
+                "--open",
+                "--config",
+                "corpus/empty-editor.toml",
+                "--path",
+                "corpus/rust.rs",
+            ],
+        ],
+    ),
+    (
+        "mod-assign-by-rules",
+        &[
+            &["mod", "assign-by-rules"],
+            &["mod", "assign-by-rules", "--config", "corpus/owners.toml"],
+            &[
+                "list",
+                "--assignee",
+                "scripting-team",
+                "--assignee",
+                "dana",
+                "--assignee",
+                "rustaceans",
+            ],
+        ],
+    ),
+    (
+        "mod-rename-assignee",
+        &[
+            &["mod", "rename-assignee", "--from", "alice", "--to", "zoe"],
+            TODO_LINES,
+        ],
+    ),
+    (
+        "mod-keeps-metadata-as-written",
+        &[
+            &[
+                "mod",
+                "rename-assignee",
+                "--from",
+                "alice",
+                "--to",
+                "zoe",
+                "--path",
+                "corpus/roundtrip",
+            ],
+            &[
+                "git",
+                "grep",
+                "--no-index",
+                "-n",
+                "-i",
+                "-w",
+                "todo",
+                "--",
+                "corpus/roundtrip",
+            ],
+        ],
+    ),
+    (
+        "mod-interactive",
+        &[
+            &[
+                "mod",
+                "--interactive",
+                "rename-assignee",
+                "--from",
+                "alice",
+                "--to",
+                "zoe",
+                "<",
+                "corpus/interactive-input.txt",
+            ],
+            &["list", "--assignee", "zoe", "--assignee", "alice"],
+            &["mod", "apply", "--stdin", "--interactive"],
+        ],
+    ),
+    (
+        "mod-add-issue-for-all-untracked",
+        &[
+            &["mod", "add-issue-for-all-untracked", "--issue", "#99"],
+            TODO_LINES,
+        ],
+    ),
+    (
+        "mod-change-delimiter",
+        &[&["mod", "change-delimiter", "--to", "//"], TODO_LINES],
+    ),
+    (
+        "mod-remove-issue-strategies",
+        &[
+            &[
+                "mod",
+                "remove-issue",
+                "--issue",
+                "#3",
+                "--strategy",
+                "delete",
+                "--verbose",
+            ],
+            &[
+                "mod",
+                "remove-issue",
+                "--issue",
+                "#12",
+                "--strategy",
+                "convert-to-note",
+            ],
+            &["mod", "remove-issue", "--issue", "#5"],
+            TODO_LINES,
+        ],
+    ),
+    (
+        "mod-apply",
+        &[
+            &["mod", "apply", "--stdin", "<", "corpus/edits.jsonl"],
+            TODO_LINES,
+            &["mod", "apply", "--stdin", "<", "corpus/bad-edits.jsonl"],
+        ],
+    ),
+    (
+        "mod-apply-export",
+        &[
+            TODO_LINES,
+            &["export", "json", "--context", "1", ">", "exported.json"],
+            &["mod", "apply", "--stdin", "<", "exported.json"],
+            TODO_LINES,
+        ],
+    ),
+    (
+        "mod-remove-done",
+        &[&["mod", "remove-done"], &["lint", "--forbid-done"]],
+    ),
+    (
+        "stat-json-lines",
+        &[
+            &["list", "--format", "ndjson", "--path", "corpus/rust.rs"],
+            &["list", "--format", "yaml"],
+            &[
+                "stat",
+                "--json-lines",
+                "corpus/saved-list.ndjson",
+                "--group-by",
+                "assignee",
+            ],
+            &[
+                "stat",
+                "--json-lines",
+                "-",
+                "--overdue",
+                "<",
+                "corpus/saved-list.ndjson",
+            ],
+            &["stat", "--json-lines", "corpus/bad-saved-list.ndjson"],
+            &["stat", "--json-lines", "corpus/missing.ndjson"],
+        ],
+    ),
+    (
+        "format-todo-styles",
+        &[
+            &[
+                "format",
+                "--check",
+                "--config",
+                "corpus/styles.toml",
+                "--path",
+                "corpus/python.py",
+                "--path",
+                "corpus/shell.sh",
+                "--path",
+                "corpus/rust.rs",
+            ],
+            &[
+                "format",
+                "--config",
+                "corpus/styles.toml",
+                "--path",
+                "corpus/python.py",
+                "--path",
+                "corpus/shell.sh",
+            ],
+            &[
+                "lint",
+                "--config",
+                "corpus/styles.toml",
+                "--path",
+                "corpus/python.py",
+                "--path",
+                "corpus/shell.sh",
+            ],
+            &["format", "--check", "--config", "corpus/bad-styles.toml"],
+        ],
+    ),
+    (
+        "config-check",
+        &[
+            &["config", "check"],
+            &["config", "check", "--config", "corpus/owners.toml"],
+            &["config", "check", "--config", "corpus/typos.toml"],
+            &["config", "check", "--config", "corpus/bad-long-lines.toml"],
+            &["list", "--config", "corpus/typos.toml"],
+        ],
+    ),
+    (
+        "list-summary",
+        &[
+            &["list", "--summary", "--path", "corpus/copied"],
+            &["list", "--summary", "--tree"],
+        ],
+    ),
+    (
+        "multiple-assignees",
+        &[
+            &["list", "--path", "corpus/shared"],
+            &["list", "--assignee", "bob", "--path", "corpus/shared"],
+            &["stat", "--group-by", "assignee", "--path", "corpus/shared"],
+            &[
+                "stat",
+                "--group-by",
+                "assignee",
+                "--split-count",
+                "--format",
+                "csv",
+                "--path",
+                "corpus/shared",
+            ],
+            &["lint", "--single-assignee", "--path", "corpus/shared"],
+            &[
+                "mod",
+                "rename-assignee",
+                "--from",
+                "alice",
+                "--to",
+                "bob",
+                "--path",
+                "corpus/shared",
+            ],
+            &[
+                "mod",
+                "remove-assignee",
+                "--assignee",
+                "carol",
+                "--path",
+                "corpus/shared",
+            ],
+            &["list", "--path", "corpus/shared"],
+        ],
+    ),
+    (
+        "invalid-due-dates",
+        &[
+            &["list", "--path", "corpus/typos"],
+            &["list", "--overdue", "--path", "corpus/typos"],
+            &[
+                "list",
+                "--overdue",
+                "--assignee",
+                "bob",
+                "--path",
+                "corpus/typos",
+            ],
+            &["stat", "--group-by", "due", "--path", "corpus/typos"],
+            &["lint", "--path", "corpus/typos"],
+            &["format", "--check", "--path", "corpus/typos"],
+        ],
+    ),
+    (
+        "stat-delimited",
+        &[
+            &["stat", "--group-by", "assignee", "--format", "csv"],
+            &[
+                "stat",
+                "--group-by",
+                "root",
+                "--format",
+                "tsv",
+                "--path",
+                "api=corpus/typos",
+                "--path",
+                "web=corpus/copied",
+            ],
+            &[
+                "stat",
+                "--group-by",
+                "language",
+                "--format",
+                "csv",
+                "--config",
+                "corpus/spreadsheet.toml",
+            ],
+            &["stat", "--format", "csv"],
+        ],
+    ),
+    (
+        "stat-include-empty",
+        &[
+            &[
+                "stat",
+                "--group-by",
+                "assignee",
+                "--include-empty",
+                "--config",
+                "corpus/members.toml",
+            ],
+            &[
+                "stat",
+                "--group-by",
+                "assignee",
+                "--include-empty",
+                "--assignee",
+                "alice",
+                "--assignee",
+                "zoe",
+            ],
+            &[
+                "stat",
+                "--group-by",
+                "due",
+                "--sort",
+                "key",
+                "--include-empty",
+                "--config",
+                "corpus/issue-dates.toml",
+            ],
+            &[
+                "stat",
+                "--group-by",
+                "root",
+                "--include-empty",
+                "--path",
+                "app=corpus/go.go",
+                "--path",
+                "docs=corpus/docs",
+                "--path",
+                "idle=corpus/roster.txt",
+            ],
+            &["stat", "--group-by", "path", "--include-empty"],
+        ],
+    ),
+    (
+        "list-issue-filters",
+        &[
+            &["list", "--issue", "proj-7", "--issue", "org/repo#42"],
+            &["list", "--project-key", "proj"],
+            &["list", "--issue-gt", "PROJ-7"],
+            &["list", "--issue-gt", "#3", "--issue-lt", "#12"],
+            &["stat", "--issue-lt", "PROJ-9", "--group-by", "issue"],
+            &["list", "--issue-gt", "soon"],
+        ],
+    ),
+    (
+        "mod-dedupe-metadata",
+        &[
+            &["lint", "--path", "corpus/duplicates"],
+            &["mod", "dedupe-metadata", "--path", "corpus/duplicates"],
+            &[
+                "git",
+                "grep",
+                "--no-index",
+                "-n",
+                "-i",
+                "-w",
+                "todo",
+                "--",
+                "corpus/duplicates",
+            ],
+            &["mod", "dedupe-metadata", "--path", "corpus/duplicates"],
+        ],
+    ),
+    (
+        "mod-prune-empty-meta",
+        &[
+            &["lint", "--path", "corpus/pruned"],
+            &["mod", "prune-empty-meta", "--path", "corpus/pruned"],
+            &[
+                "git",
+                "grep",
+                "--no-index",
+                "-n",
+                "-i",
+                "-w",
+                "todo",
+                "--",
+                "corpus/pruned",
+            ],
+            &["mod", "prune-empty-meta", "--path", "corpus/pruned"],
+        ],
+    ),
+    (
+        "issues",
+        &[
+            &["issues"],
+            &[
+                "issues",
+                "--format",
+                "markdown",
+                "--path",
+                "corpus/typescript.ts",
+            ],
+            &[
+                "issues",
+                "--format",
+                "json",
+                "--path",
+                "corpus/typescript.ts",
+            ],
+            &["issues", "--path", "corpus/c.c"],
+            &["issues", "--format", "csv"],
+        ],
+    ),
+    (
+        "markers",
+        &[
+            &[
+                "list",
+                "--config",
+                "corpus/markers.toml",
+                "--path",
+                "corpus/markers",
+            ],
+            &[
+                "lint",
+                "--require-assignees",
+                "--config",
+                "corpus/markers.toml",
+                "--path",
+                "corpus/markers",
+            ],
+            &[
+                "list",
+                "--format",
+                "ndjson",
+                "--config",
+                "corpus/markers.toml",
+                "--path",
+                "corpus/markers",
+            ],
+            &[
+                "format",
+                "--config",
+                "corpus/markers.toml",
+                "--path",
+                "corpus/markers",
+            ],
+            &[
+                "git",
+                "grep",
+                "--no-index",
+                "-n",
+                "-i",
+                "-w",
+                "-e",
+                "todo",
+                "-e",
+                "hack",
+                "-e",
+                "note",
+                "--",
+                "corpus/markers",
+            ],
+            &["list", "--config", "corpus/bad-markers.toml"],
+        ],
+    ),
+    (
+        "stat-assignees-missing-from",
+        &[
+            &["stat", "--assignees-missing-from=corpus/roster.txt"],
+            &[
+                "stat",
+                "--assignees-missing-from=corpus/roster.txt",
+                "--path",
+                "corpus/rust.rs",
+            ],
+            &[
+                "stat",
+                "--assignees-missing-from",
+                "--config",
+                "corpus/members.toml",
+                "--path",
+                "corpus/rust.rs",
+            ],
+            &["stat", "--assignees-missing-from"],
+            &["stat", "--assignees-missing-from=corpus/missing.txt"],
+        ],
+    ),
+    (
+        "mod-snooze",
+        &[
+            &["list", "--overdue", "--path", "corpus/snooze"],
+            &[
+                "stat",
+                "--group-by",
+                "issue",
+                "--format",
+                "csv",
+                "--path",
+                "corpus/snooze",
+            ],
+            &["stat", "--format", "prometheus", "--path", "corpus/snooze"],
+            &[
+                "mod",
+                "snooze",
+                "--issue",
+                "#41",
+                "--until",
+                "2999-01-01",
+                "--path",
+                "corpus/snooze",
+            ],
+            &["list", "--overdue", "--path", "corpus/snooze"],
+            &["list", "--due-within", "30d", "--path", "corpus/snooze"],
+            &["lint", "--max-snooze", "30d", "--path", "corpus/snooze"],
+            &["lint", "--max-snooze", "soon", "--path", "corpus/snooze"],
+            &["mod", "snooze", "--issue", "#41", "--until", "later"],
+        ],
+    ),
+    (
+        "mod-remove-all-due-dates",
+        &[&["mod", "remove-all-due-dates"], TODO_LINES],
+    ),
+];
+
+fn manifest_dir() -> PathBuf {
+    PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+}
+
+fn copy_dir(from: &Path, to: &Path) {
+    fs::create_dir_all(to).unwrap();
+    for entry in fs::read_dir(from).unwrap() {
+        let entry = entry.unwrap();
+        let target = to.join(entry.file_name());
+        if entry.file_type().unwrap().is_dir() {
+            copy_dir(&entry.path(), &target);
+        } else if target
+            .extension()
+            .is_some_and(|extension| extension == "toml")
+        {
+            // Configs reach the tracker stub at `{tracker}`.
+            let config = fs::read_to_string(entry.path()).unwrap();
+            fs::write(
+                target,
+                config.replace("{tracker}", &tracker_stub().to_string()),
+            )
+            .unwrap();
+        } else {
+            fs::copy(entry.path(), target).unwrap();
+        }
+    }
+}
+
+/// A stand-in for GitHub's API. Issues numbered 900 and up are closed, the
+/// others open, and filed issues are numbered 950.
+fn tracker_stub() -> SocketAddr {
+    static ADDRESS: OnceLock<SocketAddr> = OnceLock::new();
+    *ADDRESS.get_or_init(|| {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let address = listener.local_addr().unwrap();
+        thread::spawn(move || {
+            for mut stream in listener.incoming().flatten() {
+                let mut reader = BufReader::new(&stream);
+                let mut request_line = String::new();
+                let _ = reader.read_line(&mut request_line);
+                let mut content_length = 0;
+                let mut header = String::new();
+                while reader.read_line(&mut header).is_ok_and(|n| n > 2) {
+                    if let Some((name, value)) = header.split_once(':') {
+                        if name.eq_ignore_ascii_case("content-length") {
+                            content_length = value.trim().parse().unwrap_or(0);
+                        }
+                    }
+                    header.clear();
+                }
+                let mut request_body = vec![0; content_length];
+                let _ = reader.read_exact(&mut request_body);
+
+                let mut request = request_line.split_whitespace();
+                let (method, path) = (request.next(), request.next().unwrap_or(""));
+                let number: u64 = path
+                    .rsplit('/')
+                    .next()
+                    .and_then(|number| number.parse().ok())
+                    .unwrap_or(0);
+                let body = match (method, number) {
+                    (Some("POST"), _) => r#"{"number": 950}"#.to_owned(),
+                    (_, 900..) => format!(r#"{{"state": "closed", "title": "Closed {}"}}"#, number),
+                    _ => format!(r#"{{"state": "open", "title": "Open {}"}}"#, number),
+                };
+                let _ = write!(
+                    stream,
+                    "HTTP/1.1 200 OK\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+                    body.len(),
+                    body
+                );
+            }
+        });
+        address
+    })
+}
+
+fn run_case(name: &str, commands: &[&[&str]]) -> String {
+    let workspace =
+        std::env::temp_dir().join(format!("codo-golden-{}-{}", std::process::id(), name));
+    let _ = fs::remove_dir_all(&workspace);
+    copy_dir(
+        &manifest_dir().join("tests/corpus"),
+        &workspace.join("corpus"),
+    );
+
+    let mut transcript = String::new();
+    for args in commands {
+        // A trailing `< file` feeds the file to stdin and `> file` writes
+        // stdout to it, like in a shell.
+        let (codo_args, stdin, stdout_file) = match args {
+            [codo_args @ .., "<", file] => (
+                codo_args,
+                Stdio::from(fs::File::open(workspace.join(file)).unwrap()),
+                None,
+            ),
+            [codo_args @ .., ">", file] => (codo_args, Stdio::null(), Some(workspace.join(file))),
+            _ => (*args, Stdio::null(), None),
+        };
+
+        // Leading `NAME=value` words set environment variables, like
+        // `GIT_COMMITTER_DATE` for commits of a fixed age.
+        let env_count = codo_args
+            .iter()
+            .take_while(|arg| {
+                arg.contains('=') && arg.starts_with(|c: char| c.is_ascii_uppercase())
+            })
+            .count();
+        let (env, codo_args) = codo_args.split_at(env_count);
+
+        // Commands starting with `git` build a history in the workspace,
+        // with no settings from the user's config. Completions are cached in
+        // the workspace too.
+        let (program, output) = match codo_args {
+            ["git", git_args @ ..] => (
+                "git",
+                Command::new("git")
+                    .current_dir(&workspace)
+                    .envs(env.iter().filter_map(|var| var.split_once('=')))
+                    .env("GIT_CONFIG_GLOBAL", "/dev/null")
+                    .env("GIT_CONFIG_NOSYSTEM", "1")
+                    .args(["-c", "user.name=codo", "-c", "user.email=codo@example.com"])
+                    .args(git_args)
+                    .stdin(stdin)
+                    .output()
+                    .unwrap(),
+            ),
+            _ => (
+                "codo",
+                Command::new(env!("CARGO_BIN_EXE_codo"))
+                    .current_dir(&workspace)
+                    .envs(env.iter().filter_map(|var| var.split_once('=')))
+                    .env("XDG_CACHE_HOME", workspace.join(".cache"))
+                    .args(["--path", "corpus"])
+                    .args(codo_args)
+                    .stdin(stdin)
+                    .output()
+                    .unwrap(),
+            ),
+        };
+
+        let args = match program {
+            "git" => &args[env.len() + 1..],
+            _ => &args[env.len()..],
+        };
+        let env_prefix: String = env.iter().map(|var| format!("{} ", var)).collect();
+        transcript.push_str(&format!("$ {}{} {}\n", env_prefix, program, args.join(" ")));
+        match stdout_file {
+            Some(file) => fs::write(file, &output.stdout).unwrap(),
+            None => transcript.push_str(&String::from_utf8_lossy(&output.stdout)),
+        }
+        transcript.push_str(&String::from_utf8_lossy(&output.stderr));
+        if !output.status.success() {
+            transcript.push_str(&format!("[exit {}]\n", output.status.code().unwrap_or(-1)));
+        }
+    }
+
+    fs::remove_dir_all(&workspace).unwrap();
+    normalize_dates(&transcript)
+}
+
+/// Replaces the dates of the last 30 days, as in charts of recent history,
+/// with `<today>` and `<today-Nd>`, so transcripts don't change from day to
+/// day.
+fn normalize_dates(transcript: &str) -> String {
+    let today = chrono::Local::now().date_naive();
+    (0..=30).fold(transcript.to_owned(), |transcript, days| {
+        let date = today - chrono::Duration::days(days);
+        let placeholder = match days {
+            0 => "<today>".to_owned(),
+            _ => format!("<today-{}d>", days),
+        };
+        transcript.replace(&date.to_string(), &placeholder)
+    })
+}
+
+#[test]
+fn golden_transcripts() {
+    let bless = std::env::var_os("CODO_BLESS").is_some();
+    let mut failures: Vec<String> = vec![];
+
+    for (name, commands) in CASES {
+        let actual = run_case(name, commands);
+        let golden_path = manifest_dir()
+            .join("tests/golden")
+            .join(format!("{}.txt", name));
+
+        if bless {
+            fs::write(&golden_path, &actual).unwrap();
+            continue;
+        }
+
+        let expected = fs::read_to_string(&golden_path).unwrap_or_default();
+        if actual != expected {
+            failures.push(format!(
+                "{}\n--- expected\n{}\n--- actual\n{}",
+                golden_path.display(),
+                expected,
+                actual
+            ));
+        }
+    }
+
+    assert!(failures.is_empty(), "\n{}", failures.join("\n\n"));
+}
+
+#[test]
+fn selftest_passes() {
+    let output = Command::new(env!("CARGO_BIN_EXE_codo"))
+        .current_dir(manifest_dir())
+        .args(["--path", "tests/corpus", "selftest"])
+        .output()
+        .unwrap();
+
+    assert!(
+        output.status.success(),
+        "{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+}
+
+/// Every corpus subdirectory, including the ones only golden cases naming
+/// them scan, formats the same on a second run.
+#[test]
+fn format_is_stable_across_the_corpus() {
+    let corpus = manifest_dir().join("tests/corpus");
+    let mut dirs: Vec<String> = fs::read_dir(&corpus)
+        .unwrap()
+        .flatten()
+        .filter(|entry| entry.path().is_dir())
+        .map(|entry| entry.file_name().to_string_lossy().into_owned())
+        .collect();
+    dirs.sort();
+
+    let mut command = Command::new(env!("CARGO_BIN_EXE_codo"));
+    command.current_dir(&corpus).args(["format", "--verify"]);
+    for dir in &dirs {
+        command.args(["--path", dir]);
+    }
+    let output = command.output().unwrap();
+
+    assert!(
+        output.status.success(),
+        "{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    assert_eq!(
+        String::from_utf8_lossy(&output.stdout),
+        "Formatting is stable.\n"
+    );
+}